@@ -3,6 +3,7 @@ use fuzzy_matcher::FuzzyMatcher;
 use itertools::Itertools;
 
 use crate::{
+    collection_search::SEARCH_PLACEHOLDER,
     collections::SEARCH,
     main_page::ARROW_LEFT,
     BaseComponents::{
@@ -45,10 +46,10 @@ pub fn FilterSearch(
 pub fn SearchBar(sender: Option<Signal<String>>) -> Element {
     let mut total_clicked = use_signal(|| false);
     let mut input_clicked = use_signal(|| false);
-    let mut search = use_signal(|| String::from("搜尋"));
+    let mut search = use_signal(|| String::from(SEARCH_PLACEHOLDER));
     use_effect(move || {
         if !total_clicked() {
-            search.set(String::from("搜尋"));
+            search.set(String::from(SEARCH_PLACEHOLDER));
         }
         if input_clicked() {
             search.set(String::new());
@@ -0,0 +1,215 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    journal::{attach_snapshot, collapse_since, entry_count},
+    local_mods::{import_download, remove_local_mod},
+    mod_actions::{remove_mod, set_mod_enabled, update_modrinth_mod},
+    mod_query::ModSource,
+    modrinth::ModrinthVersion,
+    persistence,
+    snapshots::{take_snapshot, SnapshotReason},
+};
+
+const PINNED_FILE: &str = "pinned_mods";
+
+/// Mods excluded from updates, keyed by `CollectionId::to_string`.
+pub static PINNED_MODS: GlobalSignal<BTreeMap<String, BTreeSet<String>>> =
+    GlobalSignal::new(|| persistence::load(PINNED_FILE));
+
+/// The bulk action currently running, shown in place of the action bar.
+pub static BULK_PROGRESS: GlobalSignal<Option<BulkProgress>> = GlobalSignal::new(|| None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    Enable,
+    Disable,
+    Update,
+    Delete,
+    Pin,
+    Unpin,
+    Export,
+}
+
+impl std::fmt::Display for BulkAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Enable => "啟用",
+            Self::Disable => "停用",
+            Self::Update => "更新",
+            Self::Delete => "刪除",
+            Self::Pin => "釘選",
+            Self::Unpin => "取消釘選",
+            Self::Export => "匯出清單",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkProgress {
+    pub action: BulkAction,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// A selected row of the mod list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedMod {
+    pub name: String,
+    pub source: ModSource,
+    /// Mod id of a dropped jar, which is how local mods are removed
+    pub local_id: Option<String>,
+    pub file_name: Option<String>,
+    pub version: Option<String>,
+    pub update: Option<ModrinthVersion>,
+}
+
+pub fn is_pinned(collection_id: &CollectionId, mod_name: &str) -> bool {
+    PINNED_MODS
+        .read()
+        .get(&collection_id.to_string())
+        .is_some_and(|x| x.contains(mod_name))
+}
+
+fn set_pinned(
+    collection_id: &CollectionId,
+    mods: &[SelectedMod],
+    pinned: bool,
+) -> anyhow::Result<()> {
+    {
+        let mut pins = PINNED_MODS.write();
+        let pins = pins.entry(collection_id.to_string()).or_default();
+        for x in mods {
+            if pinned {
+                pins.insert(x.name.clone());
+            } else {
+                pins.remove(&x.name);
+            }
+        }
+    }
+    persistence::save(PINNED_FILE, &*PINNED_MODS.read())?;
+    Ok(())
+}
+
+/// Writes the selection as a plain text list next to the user's downloads.
+fn export_list(collection_id: &CollectionId, mods: &[SelectedMod]) -> anyhow::Result<PathBuf> {
+    let display_name = collection_id.get_collection_owned().display_name().to_string();
+    let mut list = String::new();
+    for x in mods {
+        let source = match x.source {
+            ModSource::Modrinth => "Modrinth",
+            ModSource::Local => "本地",
+        };
+        writeln!(
+            list,
+            "{}\t{}\t{source}",
+            x.name,
+            x.version.as_deref().unwrap_or("-")
+        )?;
+    }
+    let dir = dirs::download_dir().unwrap_or_else(persistence::data_dir);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{display_name} 模組清單.txt"));
+    std::fs::write(&path, list)?;
+    Ok(path)
+}
+
+async fn apply(
+    collection_id: &CollectionId,
+    action: BulkAction,
+    target: &SelectedMod,
+) -> anyhow::Result<()> {
+    match (action, target.source) {
+        (BulkAction::Enable | BulkAction::Disable, _) => {
+            set_mod_enabled(collection_id, &target.name, action == BulkAction::Enable)?;
+        }
+        (BulkAction::Delete, ModSource::Modrinth) => {
+            remove_mod(collection_id, &target.name, target.file_name.as_deref())?;
+        }
+        (BulkAction::Delete, ModSource::Local) => {
+            if let Some(id) = &target.local_id {
+                remove_local_mod(collection_id, id)?;
+            }
+        }
+        // pinned mods keep their version
+        (BulkAction::Update, _) if is_pinned(collection_id, &target.name) => {}
+        (BulkAction::Update, ModSource::Modrinth) => {
+            if let Some(version) = &target.update {
+                update_modrinth_mod(
+                    collection_id,
+                    &target.name,
+                    target.file_name.as_deref(),
+                    version,
+                )
+                .await?;
+            }
+        }
+        (BulkAction::Update, ModSource::Local) => {
+            if let Some(file) = target.update.as_ref().and_then(ModrinthVersion::primary_file) {
+                import_download(collection_id, file).await?;
+            }
+        }
+        (BulkAction::Pin | BulkAction::Unpin | BulkAction::Export, _) => {}
+    }
+    Ok(())
+}
+
+fn advance(done: usize) {
+    if let Some(progress) = BULK_PROGRESS.write().as_mut() {
+        progress.done = done;
+    }
+}
+
+/// Runs `action` on every selected mod, publishing progress to [`BULK_PROGRESS`]. Everything
+/// the action changed is folded into one journal entry so it can be undone in one step, updates
+/// and deletions by restoring the snapshot they take first. Returns the exported file for
+/// [`BulkAction::Export`].
+pub async fn run_bulk(
+    collection_id: &CollectionId,
+    action: BulkAction,
+    mods: Vec<SelectedMod>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let reason = match action {
+        BulkAction::Export => return export_list(collection_id, &mods).map(Some),
+        BulkAction::Pin | BulkAction::Unpin => {
+            set_pinned(collection_id, &mods, action == BulkAction::Pin)?;
+            return Ok(None);
+        }
+        BulkAction::Update => Some(SnapshotReason::BulkModUpdate),
+        BulkAction::Delete => Some(SnapshotReason::BulkModRemoval),
+        BulkAction::Enable | BulkAction::Disable => None,
+    };
+    let snapshot = match reason {
+        Some(x) => Some(take_snapshot(collection_id, x).await?),
+        None => None,
+    };
+    *BULK_PROGRESS.write() = Some(BulkProgress {
+        action,
+        done: 0,
+        total: mods.len(),
+    });
+    let start = entry_count(collection_id);
+    let mut result = Ok(());
+    for (index, target) in mods.iter().enumerate() {
+        result = apply(collection_id, action, target).await;
+        if result.is_err() {
+            break;
+        }
+        advance(index + 1);
+    }
+    // whatever was done before a failure is still one step
+    collapse_since(
+        collection_id,
+        start,
+        format!("批次{action} {} 個模組", mods.len()),
+    );
+    if let Some(snapshot) = snapshot {
+        attach_snapshot(collection_id, start, snapshot.created);
+    }
+    *BULK_PROGRESS.write() = None;
+    result.map(|()| None)
+}
@@ -1,7 +1,14 @@
+use std::collections::BTreeSet;
 use std::ops::Deref;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::SystemTime;
 
 use dioxus::prelude::*;
 use dioxus_elements::geometry::PixelsRect;
+use dioxus_logger::tracing::error;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use itertools::Itertools;
 use manganis::ImageAsset;
 use rust_lib::api::{
     backend_exclusive::mod_management::mods::ModMetadata,
@@ -12,9 +19,29 @@ use tailwind_fuse::tw_merge;
 use tokio_stream::StreamExt;
 
 use crate::{
+    bulk_actions::{is_pinned, run_bulk, BulkAction, SelectedMod, BULK_PROGRESS},
+    collection_edit::advanced::PillAction,
+    collection_stats::{mark_running, record_session},
     collections::{ARROW_DOWN, SEARCH},
+    conflicts::{
+        blocking_conflicts, schedule_conflict_check, ConflictBanner, FORCE_LAUNCH, PRE_LAUNCH_BLOCK,
+    },
+    crash_report::{detect_crash, CRASH_REPORT},
+    dependency_graph::DependencyTree,
+    favorites::{is_favorite, toggle_favorite},
     impl_context_switcher,
+    launch_hooks::{launch_hooks, HookStage},
+    launch_options::{
+        apply_window_options, launch_options, take_quick_play, WorldViewer, LAUNCH_REQUEST,
+    },
+    launch_plan::plan_launch,
+    loaders::loader_label,
+    local_mods::{
+        collection_loader, import_jar, local_mods_of, remove_local_mod, LocalMod, LOCAL_MODS,
+    },
     main_page::{ARROW_LEFT, STAR},
+    mod_index::{index_collection, ModIndex},
+    mod_query::{completions, ModFacts, ModQuery, ModSource, FILTER_KEYS},
     pages::Pages,
     text_scroller::use_text_scroller,
     BaseComponents::{
@@ -23,12 +50,12 @@ use crate::{
             switch::Switch,
         },
         molecules::{
-            search_bar::{FilterSearch, SearchBar},
+            search_bar::SearchBar,
             switcher::{Comparison, StateSwitcher, StateSwitcherSelectionBar, ToClass},
         },
         string_placements::{Alignment, ContentType, Contents, Hint, StringPlacements, Text},
     },
-    EXPLORE,
+    use_error_handler, ErrorFormatted, EXPLORE,
 };
 
 pub static DISPLAY_BACKGROUND: ImageAsset = asset!(image("./public/cool_image.png").preload());
@@ -57,6 +84,8 @@ impl_context_switcher!(CollectionDisplayTopSelection);
 #[derive(Clone)]
 pub enum Action {
     Start,
+    /// Start without the pre-launch conflict check
+    StartAnyway,
     Stop,
 }
 
@@ -84,28 +113,103 @@ pub fn ScrollableFootBar(main: Element, footer: Element, bottom: Element) -> Ele
 
 #[component]
 fn CollectionBackground(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
     let launch_game = use_coroutine(move |mut rx| async move {
         while let Some(action) = rx.next().await {
             match action {
-                Action::Start => {
-                    let mut collection = collection_id().get_collection_owned();
-                    collection.launch_game().await.unwrap();
-                    let collection_to_replace =
-                        &mut *collection_id().try_get_raw_mut_collection().unwrap();
-                    *collection_to_replace = collection;
+                Action::Start | Action::StartAnyway => {
+                    if matches!(action, Action::Start) {
+                        if let Some(issues) = blocking_conflicts(&collection_id()).await {
+                            *PRE_LAUNCH_BLOCK.write() = Some((collection_id(), issues));
+                            continue;
+                        }
+                    }
+                    let quick_play = take_quick_play(&collection_id());
+                    let game_directory = collection_id().get_collection_owned().game_directory();
+                    let options = launch_options(&collection_id());
+                    if let Err(x) = apply_window_options(&game_directory, &options) {
+                        error!("{}", x.to_formatted());
+                    }
+                    let hooks = launch_hooks(&collection_id());
+                    let plan = match plan_launch(&collection_id(), quick_play).await {
+                        Ok(x) => x,
+                        Err(x) => {
+                            error.set(Some(Err(x.into())));
+                            continue;
+                        }
+                    };
+                    if let Err(x) = hooks.run(HookStage::PreLaunch, &game_directory).await {
+                        error.set(Some(Err(x.into())));
+                        continue;
+                    }
+                    let game = match plan.spawn() {
+                        Ok(x) => x,
+                        Err(x) => {
+                            error.set(Some(Err(x.into())));
+                            continue;
+                        }
+                    };
+                    // the session runs from the spawn to the exit of the game process itself
+                    let started = SystemTime::now();
+                    mark_running(&collection_id());
+                    let status = game.wait().await;
+                    if let Err(x) = hooks.run(HookStage::PostExit, &game_directory).await {
+                        error!("{}", x.to_formatted());
+                    }
+                    let mods = collection_id()
+                        .get_collection_owned()
+                        .mod_controller()
+                        .map(|x| x.manager.mods.clone())
+                        .unwrap_or_default();
+                    let exited_cleanly = match status {
+                        Ok(x) => x.success(),
+                        // the exit status is unknown, look for a crash either way
+                        Err(x) => {
+                            error.set(Some(Err(x.into())));
+                            false
+                        }
+                    };
+                    let detected = detect_crash(&game_directory, started, exited_cleanly, &mods);
+                    let crashed = match detected {
+                        Ok(Some(analysis)) => {
+                            *CRASH_REPORT.write() = Some((collection_id(), analysis));
+                            true
+                        }
+                        Ok(None) => false,
+                        Err(x) => {
+                            error!("{}", x.to_formatted());
+                            false
+                        }
+                    };
+                    if let Err(x) = record_session(&collection_id(), started, crashed) {
+                        error!("{}", x.to_formatted());
+                    }
                 }
                 Action::Stop => {}
             }
         }
     });
+    use_effect(move || {
+        if FORCE_LAUNCH.read().as_ref() == Some(&collection_id()) {
+            *FORCE_LAUNCH.write() = None;
+            launch_game.send(Action::StartAnyway);
+        }
+    });
+    use_effect(move || {
+        if LAUNCH_REQUEST.read().as_ref() == Some(&collection_id()) {
+            *LAUNCH_REQUEST.write() = None;
+            launch_game.send(Action::Start);
+        }
+    });
     let (onmounted, status, style) = use_text_scroller();
     let collection = collection_id().get_collection();
     let len = collection.with(|x| x.mod_controller().map(|x| x.manager.mods.len()));
-    let mod_loader = collection.read().mod_loader().map(ToString::to_string);
+    let mod_loader = loader_label(&collection_id());
     let status: Signal<Comparison<CollectionDisplayTopSelection>> =
         use_signal(|| (CollectionDisplayTopSelection::Mods, None));
     let default = CopyValue::new(String::from("搜尋合集中的內容"));
     let mut value = use_signal(|| default.cloned());
+    let mut show_dependencies = use_signal(|| false);
     rsx! {
         ScrollableFootBar {
             footer: rsx!(
@@ -124,11 +228,11 @@ fn CollectionBackground(collection_id: ReadOnlySignal<CollectionId>) -> Element
                         roundness: Roundness::Squircle,
                         extended_css_class: "bg-background pl-[25px] pr-[20px] min-w-[280px] max-w-[280px]",
                         fill_mode: FillMode::Fit,
-                        clickable: false,
+                        onclick: move |()| show_dependencies.toggle(),
                         string_placements: vec![
                             Contents::new(
                                 vec![
-                                    ContentType::text("模組").css("font-medium"),
+                                    ContentType::text(if show_dependencies() { "依賴關係" } else { "模組" }).css("font-medium"),
                                     ContentType::text(format!("({})", len.unwrap_or_default())).css("text-hint font-english font-medium"),
                                 ],
                                 Alignment::Left,
@@ -145,6 +249,7 @@ fn CollectionBackground(collection_id: ReadOnlySignal<CollectionId>) -> Element
                             ContentType::custom(rsx!(
                                 input {
                                     class: "w-full text-hint font-medium text-xl leading-[1.2] capsize",
+                                    list: "mod-query-completions",
                                     onfocusin: move |_| {
                                         if &*value.read() == &default.cloned() {
                                             value.set(String::new());
@@ -158,6 +263,18 @@ fn CollectionBackground(collection_id: ReadOnlySignal<CollectionId>) -> Element
                                     },
                                     value: value(),
                                 }
+                                datalist {
+                                    id: "mod-query-completions",
+                                    if value() != default.cloned() {
+                                        for completion in completions(&value()) {
+                                            option {
+                                                key: "{completion}",
+                                                value: "{completion}",
+                                                {completion_label(&completion)}
+                                            }
+                                        }
+                                    }
+                                }
                             ))
                             .align_left()
                             .css("grow min-w-full justify-self-stretch"),
@@ -177,8 +294,11 @@ fn CollectionBackground(collection_id: ReadOnlySignal<CollectionId>) -> Element
                     }
                     Button {
                         roundness: Roundness::Squircle,
-                        extended_css_class: "bg-background",
+                        extended_css_class: if is_favorite(&collection_id()) { "bg-lime-300 text-black" } else { "bg-background" },
                         fill_mode: FillMode::Fit,
+                        onclick: move |()| {
+                            error.set(Some(toggle_favorite(&collection_id()).map_err(Into::into)));
+                        },
                         string_placements: vec![
                             ContentType::svg(STAR).css("svg-[40px]").align_center(),
                         ],
@@ -187,6 +307,9 @@ fn CollectionBackground(collection_id: ReadOnlySignal<CollectionId>) -> Element
                         roundness: Roundness::Squircle,
                         extended_css_class: "bg-background min-w-[150px]",
                         fill_mode: FillMode::Fit,
+                        onclick: move |()| {
+                            launch_game.send(Action::Start);
+                        },
                         string_placements: rsx![
                             div {
                                 background: "var(--unnamed, linear-gradient(90deg, #C92B460%, #C92B4620%, #9747FF20%, #9747FF40%, #7CAED340%, #7CAED360%, #14AE5C60%, #14AE5C80%, #CDE34780%, #CDE347100%));",
@@ -261,12 +384,16 @@ fn CollectionBackground(collection_id: ReadOnlySignal<CollectionId>) -> Element
             bottom: rsx!(
                 div {
                     class: "relative overflow-y-scroll min-w-full max-w-full flex flex-col h-full",
-                    if status().0 == CollectionDisplayTopSelection::Mods {
+                    if status().0 == CollectionDisplayTopSelection::Mods && show_dependencies() {
+                        DependencyTree { collection_id }
+                    } else if status().0 == CollectionDisplayTopSelection::Mods {
                         ModViewer {
                             collection_id,
                             default,
                             search: value()
                         }
+                    } else if status().0 == CollectionDisplayTopSelection::World {
+                        WorldViewer { collection_id }
                     }
                 }
             ),
@@ -328,6 +455,165 @@ pub fn CollectionDisplay(collection_id: ReadOnlySignal<CollectionId>) -> Element
     }
 }
 
+/// The description of the filter key a completion ends with.
+fn completion_label(completion: &str) -> &'static str {
+    let last = completion.rsplit(' ').next().unwrap_or_default();
+    let key = last.split(':').next().unwrap_or_default();
+    FILTER_KEYS
+        .iter()
+        .find(|x| x.key == key)
+        .map_or("", |x| x.description)
+}
+
+/// Identifies a row across renders, names are only unique within a source.
+fn row_key(target: &SelectedMod) -> String {
+    match (target.source, &target.local_id) {
+        (ModSource::Local, Some(id)) => format!("local:{id}"),
+        _ => format!("modrinth:{}", target.name),
+    }
+}
+
+/// Toggles `key`, or with shift held selects every row between the last clicked one and `key`.
+fn select_rows(
+    selected: &mut BTreeSet<String>,
+    anchor: Option<&str>,
+    keys: &[String],
+    key: &str,
+    shift: bool,
+) {
+    let range = anchor.filter(|_| shift).and_then(|anchor| {
+        let from = keys.iter().position(|x| x == anchor)?;
+        let to = keys.iter().position(|x| x == key)?;
+        Some(from.min(to)..=from.max(to))
+    });
+    match range {
+        Some(range) => selected.extend(keys[range].iter().cloned()),
+        None => {
+            if !selected.remove(key) {
+                selected.insert(key.to_owned());
+            }
+        }
+    }
+}
+
+/// A mod row with a checkbox in front, shift-clicking anywhere on it selects a range.
+#[component]
+fn SelectableRow(selected: bool, onselect: EventHandler<bool>, children: Element) -> Element {
+    rsx! {
+        div {
+            class: "flex items-center gap-[15px]",
+            onclick: move |evt: MouseEvent| {
+                if evt.modifiers().shift() {
+                    onselect.call(true);
+                }
+            },
+            input {
+                r#type: "checkbox",
+                class: "flex-none size-[20px] accent-white",
+                checked: selected,
+                onclick: move |evt: MouseEvent| {
+                    evt.stop_propagation();
+                    onselect.call(evt.modifiers().shift());
+                }
+            }
+            div {
+                class: "grow min-w-0",
+                {children}
+            }
+        }
+    }
+}
+
+#[component]
+fn BulkActionBar(
+    collection_id: ReadOnlySignal<CollectionId>,
+    targets: Vec<SelectedMod>,
+    selected: Signal<BTreeSet<String>>,
+) -> Element {
+    let exported: Signal<Option<PathBuf>> = use_signal(|| None);
+    let all_pinned = targets.iter().all(|x| is_pinned(&collection_id(), &x.name));
+    let updatable = targets.iter().filter(|x| x.update.is_some()).count();
+    let actions = [
+        BulkAction::Enable,
+        BulkAction::Disable,
+        BulkAction::Update,
+        BulkAction::Delete,
+        if all_pinned {
+            BulkAction::Unpin
+        } else {
+            BulkAction::Pin
+        },
+        BulkAction::Export,
+    ];
+    rsx! {
+        div {
+            class: "flex items-center justify-between gap-[10px] rounded-[20px] bg-deep-background px-[20px] py-[15px]",
+            if let Some(progress) = BULK_PROGRESS() {
+                Text {
+                    css: "text-white text-[18px]",
+                    {format!("{}中… {}/{}", progress.action, progress.done, progress.total)}
+                }
+            } else {
+                div {
+                    class: "flex flex-col gap-[5px]",
+                    Text { css: "text-white text-[18px]", {format!("已選取 {} 個模組", targets.len())} }
+                    if let Some(path) = exported() {
+                        Hint { css: "text-[13px] font-english", {format!("已匯出到 {}", path.display())} }
+                    } else if updatable > 0 {
+                        Hint { css: "text-[13px]", {format!("{updatable} 個有可用更新，已釘選的模組不會更新")} }
+                    }
+                }
+                div {
+                    class: "flex flex-wrap justify-end gap-[10px]",
+                    for action in actions {
+                        BulkPill {
+                            key: "{action}",
+                            collection_id,
+                            action,
+                            targets: targets.clone(),
+                            selected,
+                            exported
+                        }
+                    }
+                    PillAction {
+                        label: "取消選取",
+                        onclick: move |()| selected.write().clear()
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn BulkPill(
+    collection_id: ReadOnlySignal<CollectionId>,
+    action: BulkAction,
+    targets: Vec<SelectedMod>,
+    mut selected: Signal<BTreeSet<String>>,
+    mut exported: Signal<Option<PathBuf>>,
+) -> Element {
+    let mut error = use_error_handler();
+    rsx! {
+        PillAction {
+            label: action.to_string(),
+            onclick: move |()| {
+                let targets = targets.clone();
+                spawn(async move {
+                    let result = run_bulk(&collection_id(), action, targets).await;
+                    if let Ok(path) = &result {
+                        exported.set(path.clone());
+                        if action == BulkAction::Delete {
+                            selected.write().clear();
+                        }
+                    }
+                    error.set(Some(result.map(|_| ())));
+                });
+            }
+        }
+    }
+}
+
 #[component]
 fn ModViewer(
     collection_id: ReadOnlySignal<CollectionId>,
@@ -341,24 +627,154 @@ fn ModViewer(
             x.manager.mods
         })
     });
-    let mods = mods()
+    let index = use_resource(move || async move {
+        // rescan whenever the installed mods change
+        mods.read();
+        LOCAL_MODS.read();
+        schedule_conflict_check(collection_id());
+        match index_collection(&collection_id()).await {
+            Ok(x) => x,
+            Err(x) => {
+                error!("{}", x.to_formatted());
+                ModIndex::default()
+            }
+        }
+    });
+    let index = index.read().clone().unwrap_or_default();
+    let mut selected = use_signal(BTreeSet::<String>::new);
+    let mut anchor: Signal<Option<String>> = use_signal(|| None);
+    let loader = collection_loader(&collection_id());
+    let remote = mods().into_iter().flatten().map(|x| {
+        let jar = index.find(&x.name);
+        let metadata = jar.and_then(|x| x.metadata.as_ref());
+        let facts = ModFacts {
+            name: x.name.clone(),
+            description: metadata.and_then(|x| x.description.clone()),
+            file_name: jar.map(|x| x.file_name.clone()),
+            enabled: x.enabled,
+            source: ModSource::Modrinth,
+            update_available: jar.is_some_and(|x| index.updates.contains_key(&x.file_name)),
+            authors: metadata.map(|x| x.authors.clone()).unwrap_or_default(),
+            loader: metadata.map(|x| x.loader).or(loader),
+        };
+        let target = SelectedMod {
+            name: x.name.clone(),
+            source: ModSource::Modrinth,
+            local_id: None,
+            file_name: jar.map(|x| x.file_name.clone()),
+            version: x.mod_version.clone(),
+            update: jar.and_then(|x| index.updates.get(&x.file_name).cloned()),
+        };
+        (
+            facts,
+            target,
+            rsx! {
+                SubModViewer {
+                    collection_id,
+                    mods: x
+                }
+            },
+        )
+    });
+    let local = local_mods_of(&collection_id()).into_iter().map(|x| {
+        let facts = ModFacts {
+            name: x.metadata.name.clone(),
+            description: x.metadata.description.clone(),
+            file_name: Some(x.file_name.clone()),
+            enabled: x.enabled,
+            source: ModSource::Local,
+            update_available: index.updates.contains_key(&x.file_name),
+            authors: x.metadata.authors.clone(),
+            loader: Some(x.metadata.loader),
+        };
+        let target = SelectedMod {
+            name: x.metadata.name.clone(),
+            source: ModSource::Local,
+            local_id: Some(x.metadata.id.clone()),
+            file_name: Some(x.file_name.clone()),
+            version: x.metadata.version.clone(),
+            update: index.updates.get(&x.file_name).cloned(),
+        };
+        (
+            facts,
+            target,
+            rsx! {
+                LocalSubModViewer {
+                    collection_id,
+                    local: x
+                }
+            },
+        )
+    });
+    let search = search();
+    let query = if search == default {
+        Ok(ModQuery::default())
+    } else {
+        ModQuery::parse(&search)
+    };
+    let matcher = SkimMatcherV2::default();
+    let all = remote.chain(local).collect::<Vec<_>>();
+    // rows hidden by the search stay selected
+    let targets = all
+        .iter()
+        .map(|x| &x.1)
+        .filter(|x| selected.read().contains(&row_key(x)))
+        .cloned()
+        .collect::<Vec<_>>();
+    let rows = all
         .into_iter()
-        .flatten()
-        .map(|x| {
-            (
-                x.name.clone(),
-                rsx! {
-                    SubModViewer {
-                        collection_id,
-                        mods: x
-                    }
-                },
-            )
+        .filter_map(|(facts, target, x)| match &query {
+            Ok(query) => query.score(&matcher, &facts).map(|score| (score, target, x)),
+            // keep showing everything while the query is being fixed
+            Err(_) => Some((0, target, x)),
         })
+        .sorted_by_key(|x| std::cmp::Reverse(x.0))
+        .map(|x| (row_key(&x.1), x.2))
         .collect::<Vec<_>>();
+    let keys = Rc::new(rows.iter().map(|x| x.0.clone()).collect::<Vec<_>>());
+    let rows = rows.into_iter().map(|(key, x)| {
+        let keys = keys.clone();
+        let row = key.clone();
+        rsx! {
+            SelectableRow {
+                key: "{key}",
+                selected: selected.read().contains(&key),
+                onselect: move |shift| {
+                    let last = anchor();
+                    select_rows(&mut selected.write(), last.as_deref(), &keys, &row, shift);
+                    anchor.set(Some(row.clone()));
+                },
+                {x}
+            }
+        }
+    });
+    let mut error = use_error_handler();
     rsx! {
         div {
             class: "bg-background flex flex-col gap-[20px] rounded-t-[30px] pb-[30px] h-full overflow-x-hidden",
+            ondragover: move |evt| evt.prevent_default(),
+            ondrop: move |evt| {
+                evt.prevent_default();
+                let Some(engine) = evt.files() else {
+                    return;
+                };
+                // every jar is tried, the failures are reported together
+                let failures = engine
+                    .files()
+                    .into_iter()
+                    .filter(|x| x.ends_with(".jar"))
+                    .filter_map(|file| {
+                        let result = import_jar(&collection_id(), file.as_ref());
+                        result.err().map(|x| format!("{file}: {x}"))
+                    })
+                    .collect::<Vec<_>>();
+                let result = if failures.is_empty() {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!("Failed to import {}", failures.join("; ")))
+                };
+                error.set(Some(result));
+            },
             GridRow {
                 class: "w-full border-b-[3px] border-b-secondary-surface rounded-t-[30px] px-[50px] py-[10px] backdrop-blur-[7.5px] sticky top-0 z-[2000]",
                 background: "rgba(25, 25, 25, 0.90)",
@@ -406,10 +822,18 @@ fn ModViewer(
                 class: "bg-background w-full h-full flex flex-col px-[30px]",
                 div {
                     class: "flex flex-col gap-[5px]",
-                    FilterSearch {
-                        search,
-                        default,
-                        childrens: mods,
+                    if !targets.is_empty() || BULK_PROGRESS.read().is_some() {
+                        BulkActionBar { collection_id, targets, selected }
+                    }
+                    ConflictBanner { collection_id }
+                    if let Err(x) = &query {
+                        Hint {
+                            css: "px-[20px] py-[10px] text-[15px] font-english",
+                            "搜尋語法錯誤：{x}"
+                        }
+                    }
+                    for x in rows {
+                        {x}
                     }
                 }
             }
@@ -431,11 +855,12 @@ fn SubModViewer(collection_id: ReadOnlySignal<CollectionId>, mods: ModMetadata)
             {mods.name.clone()}
         }
     );
+    let pinned = is_pinned(&collection_id(), &mods.name).then(|| String::from("已釘選"));
     let file_name = rsx!(
-        if let Some(version) = &mods.mod_version {
+        if mods.mod_version.is_some() || pinned.is_some() {
             Hint {
                 css: "font-medium text-hint text-[15px] font-english",
-                {version.clone()}
+                {[mods.mod_version.clone(), pinned].into_iter().flatten().collect::<Vec<_>>().join("•")}
             }
         }
     );
@@ -557,3 +982,87 @@ fn SelectionBar(
         }
     }
 }
+
+#[component]
+fn LocalSubModViewer(collection_id: ReadOnlySignal<CollectionId>, local: LocalMod) -> Element {
+    let mut error = use_error_handler();
+    let clicked = use_signal(|| local.enabled);
+    let icon = rsx!(if let Some(icon) = local.icon_path.as_ref() {
+        {
+            ContentType::image(icon.to_string_lossy().to_string()).css("size-[80px] rounded-[15px]")
+        }
+    });
+    let name = rsx!(
+        Text {
+            css: "text-white text-[28px] font-bold font-english",
+            {local.metadata.name.clone()}
+        }
+    );
+    let source = rsx!(
+        Hint {
+            css: "font-medium text-hint text-[15px] font-english",
+            if is_pinned(&collection_id(), &local.metadata.name) {
+                "本地•{local.file_name}•已釘選"
+            } else {
+                "本地•{local.file_name}"
+            }
+        }
+    );
+    let upgrade = rsx!(Button {
+        roundness: Roundness::None,
+        extended_css_class:
+            "flex items-center justify-center bg-background rounded-[15px] h-[60px]",
+        string_placements: vec![ContentType::svg(UNARCHIVE).align_center()],
+        fill_mode: FillMode::Fill,
+        clickable: false
+    });
+    let id = local.metadata.id.clone();
+    let delete = rsx!(Button {
+        roundness: Roundness::None,
+        extended_css_class:
+            "flex items-center justify-center bg-background rounded-[15px] h-[60px]",
+        string_placements: vec![ContentType::svg(DELETE).align_center()],
+        fill_mode: FillMode::Fill,
+        onclick: move |()| {
+            let result = remove_local_mod(&collection_id(), &id);
+            error.set(Some(result.map_err(Into::into)));
+        }
+    });
+    let more = rsx!(Button {
+        roundness: Roundness::None,
+        extended_css_class:
+            "flex items-center justify-center bg-background rounded-[15px] h-[60px]",
+        string_placements: vec![ContentType::svg(HORIZ).align_center()],
+        fill_mode: FillMode::Fill
+    });
+    let status = rsx!(Switch { clicked });
+    rsx! {
+        GridRow {
+            class: "bg-deep-background items-center rounded-[20px] p-[20px]",
+            items: [
+                rsx!(
+                    {icon}
+                ),
+                rsx!(
+                    div {
+                        class: "flex flex-col justify-center gap-[15px]",
+                        {name}
+                        {source}
+                    }
+                ),
+                rsx!(
+                    {upgrade}
+                ),
+                rsx!(
+                    {delete}
+                ),
+                rsx!(
+                    {more}
+                ),
+                rsx!(
+                    {status}
+                ),
+            ]
+        }
+    }
+}
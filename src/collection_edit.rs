@@ -1,9 +1,24 @@
+pub mod advanced;
+pub mod data_log;
+pub mod game_options;
+pub mod java_runtime;
+pub mod jvm_options;
+pub mod launch_hooks;
+pub mod launch_preview;
+pub mod launch_settings;
+pub mod loader_settings;
 pub mod personalization;
+pub mod server_list;
 pub mod sidebar;
+pub mod version_upgrade;
 
+use advanced::Advanced;
+use data_log::DataLog;
 use dioxus::prelude::*;
+use game_options::GameOptionsEditor;
 use personalization::Personalization;
 use rust_lib::api::shared_resources::collection::CollectionId;
+use server_list::ServerListEditor;
 use sidebar::EditSidebar;
 use strum::{EnumIter, IntoEnumIterator};
 
@@ -25,6 +40,8 @@ pub enum EditState {
     Personalization,
     DataLog,
     Export,
+    Options,
+    Servers,
     Advanced,
 }
 
@@ -37,6 +54,8 @@ impl std::fmt::Display for EditState {
                 EditState::Personalization => "personalization",
                 EditState::DataLog => "datalog",
                 EditState::Export => "export",
+                EditState::Options => "options",
+                EditState::Servers => "servers",
                 EditState::Advanced => "advanced",
             }
         )
@@ -113,6 +132,20 @@ fn CollectionEdit(collection_id: ReadOnlySignal<CollectionId>) -> Element {
                         collection_id
                     }
                 }
+                div {
+                    class: "absolute inset-0 z-0 min-h-full min-w-full",
+                    id: EditState::Options.scroller_id(),
+                    GameOptionsEditor {
+                        collection_id
+                    }
+                }
+                div {
+                    class: "absolute inset-0 z-0 min-h-full min-w-full",
+                    id: EditState::Servers.scroller_id(),
+                    ServerListEditor {
+                        collection_id
+                    }
+                }
                 div {
                     class: "absolute inset-0 z-0 min-h-full min-w-full",
                     id: EditState::Advanced.scroller_id(),
@@ -148,32 +181,6 @@ fn EditTemplate(children: Element, title: Element) -> Element {
     }
 }
 
-#[component]
-fn DataLog(collection_id: ReadOnlySignal<CollectionId>) -> Element {
-    rsx! {
-        EditTemplate {
-            title: rsx! {
-                Button {
-                    roundness: Roundness::None,
-                    extended_css_class: "rounded-[20px] p-[40px]",
-                    string_placements: vec![
-                        Contents::new(
-                                vec![
-                                    ContentType::text("收藏紀錄")
-                                        .css("font-black text-white text-[40px]"),
-                                    ContentType::hint("查看這個收藏的資訊"),
-                                ],
-                                Alignment::Left,
-                            )
-                            .css("flex flex-col gap-[20px]"),
-                        ContentType::svg(GAME_CONTROLLER).css("svg-[70px]").align_right(),
-                    ]
-                }
-            }
-        }
-    }
-}
-
 #[component]
 fn Export(collection_id: ReadOnlySignal<CollectionId>) -> Element {
     rsx! {
@@ -198,29 +205,3 @@ fn Export(collection_id: ReadOnlySignal<CollectionId>) -> Element {
         }
     }
 }
-
-#[component]
-fn Advanced(collection_id: ReadOnlySignal<CollectionId>) -> Element {
-    rsx! {
-        EditTemplate {
-            title: rsx! {
-                Button {
-                    roundness: Roundness::None,
-                    extended_css_class: "rounded-[20px] p-[40px]",
-                    string_placements: vec![
-                        Contents::new(
-                                vec![
-                                    ContentType::text("進階選項")
-                                        .css("font-black text-white text-[40px]"),
-                                    ContentType::hint("單獨修改此收藏的進階選項"),
-                                ],
-                                Alignment::Left,
-                            )
-                            .css("flex flex-col gap-[20px]"),
-                        ContentType::svg(GAME_CONTROLLER).css("svg-[70px]").align_right(),
-                    ]
-                }
-            }
-        }
-    }
-}
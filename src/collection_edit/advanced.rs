@@ -0,0 +1,311 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_display::GAME_CONTROLLER,
+    collection_edit::{
+        java_runtime::JavaRuntimeSettings, jvm_options::JvmOptions,
+        launch_hooks::LaunchHooksSettings, launch_preview::LaunchPreview,
+        launch_settings::LaunchSettings, loader_settings::LoaderSettings,
+        version_upgrade::VersionUpgrade, EditTemplate,
+    },
+    collection_stats::format_date,
+    download_progress::SizeFromMegaBytes,
+    snapshots::{
+        delete_snapshot, list_snapshots, save_settings, take_snapshot, Snapshot, SnapshotDiff,
+        SnapshotReason, SnapshotSettings, SNAPSHOT_SETTINGS,
+    },
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, FillMode, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+    ThrowResource,
+};
+
+#[component]
+pub fn Advanced(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    rsx! {
+        EditTemplate {
+            title: rsx! {
+                Button {
+                    roundness: Roundness::None,
+                    extended_css_class: "rounded-[20px] p-[40px]",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![
+                                    ContentType::text("進階選項")
+                                        .css("font-black text-white text-[40px]"),
+                                    ContentType::hint("單獨修改此收藏的進階選項"),
+                                ],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[20px]"),
+                        ContentType::svg(GAME_CONTROLLER).css("svg-[70px]").align_right(),
+                    ]
+                }
+            },
+            JavaRuntimeSettings { collection_id }
+            JvmOptions { collection_id }
+            LaunchSettings { collection_id }
+            LaunchHooksSettings { collection_id }
+            LaunchPreview { collection_id }
+            LoaderSettings { collection_id }
+            VersionUpgrade { collection_id }
+            Snapshots { collection_id }
+            SnapshotRetention {}
+        }
+    }
+}
+
+/// A pill with a label, used for the small actions on the advanced page.
+#[component]
+pub(crate) fn PillAction(
+    #[props(into)] label: String,
+    onclick: EventHandler,
+    #[props(default)] selected: bool,
+) -> Element {
+    rsx! {
+        Button {
+            roundness: Roundness::Pill,
+            fill_mode: FillMode::Fit,
+            extended_css_class: if selected { "bg-white text-black px-[25px] py-[10px]" } else { "bg-background px-[25px] py-[10px]" },
+            onclick,
+            string_placements: vec![ContentType::text(label).align_center()]
+        }
+    }
+}
+
+#[component]
+fn Snapshots(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut refresh = use_signal(|| 0_u32);
+    let mut error = use_error_handler();
+    let snapshots = use_resource(move || async move {
+        refresh();
+        list_snapshots(&collection_id()).map_err(anyhow::Error::from)
+    });
+    let snapshots = snapshots.throw()?.unwrap_or_default();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("快照"),
+                                ContentType::hint(
+                                    "批次更新模組、更換載入器與遷移版本前會自動建立快照",
+                                ),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        PillAction {
+                            label: "立即建立",
+                            onclick: move |()| {
+                                spawn(async move {
+                                    let result = take_snapshot(&collection_id(), SnapshotReason::Manual).await;
+                                    error.set(Some(result.map(|_| ()).map_err(Into::into)));
+                                    *refresh.write() += 1;
+                                });
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            if snapshots.is_empty() {
+                Button {
+                    roundness: Roundness::Bottom,
+                    clickable: false,
+                    extended_css_class: "p-[25px]",
+                    string_placements: vec![ContentType::hint("目前沒有任何快照").align_left()]
+                }
+            }
+            for snapshot in snapshots {
+                SnapshotRow {
+                    key: "{snapshot.created}",
+                    collection_id,
+                    created: snapshot.created,
+                    reason: snapshot.reason,
+                    size: snapshot.size,
+                    refresh
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SnapshotRow(
+    collection_id: ReadOnlySignal<CollectionId>,
+    created: u64,
+    reason: SnapshotReason,
+    size: u64,
+    mut refresh: Signal<u32>,
+) -> Element {
+    let mut error = use_error_handler();
+    let mut diff: Signal<Option<SnapshotDiff>> = use_signal(|| None);
+    let find = move || -> anyhow::Result<Snapshot> {
+        list_snapshots(&collection_id())?
+            .into_iter()
+            .find(|x| x.created == created)
+            .ok_or_else(|| anyhow::anyhow!("snapshot {created} no longer exists"))
+    };
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[20px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text(format_date(created)).css("text-xl font-english"),
+                                ContentType::hint(format!("{reason}•{}", (size as f64).display_size_from_megabytes()))
+                                    .css("text-[13px]"),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[10px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            PillAction {
+                                label: if diff.read().is_some() { "收合" } else { "比較" },
+                                onclick: move |()| {
+                                    if diff.read().is_some() {
+                                        diff.set(None);
+                                        return;
+                                    }
+                                    match find().and_then(|x| Ok(x.diff(&collection_id())?)) {
+                                        Ok(x) => diff.set(Some(x)),
+                                        Err(x) => error.set(Some(Err(x))),
+                                    }
+                                }
+                            }
+                            PillAction {
+                                label: "還原",
+                                onclick: move |()| {
+                                    let result = find().and_then(|x| Ok(x.restore(&collection_id())?));
+                                    error.set(Some(result));
+                                    diff.set(None);
+                                }
+                            }
+                            PillAction {
+                                label: "刪除",
+                                onclick: move |()| {
+                                    let result = delete_snapshot(&collection_id(), created);
+                                    error.set(Some(result.map_err(Into::into)));
+                                    *refresh.write() += 1;
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            if let Some(diff) = diff() {
+                SnapshotDiffView { diff }
+            }
+        }
+    }
+}
+
+#[component]
+fn SnapshotDiffView(diff: SnapshotDiff) -> Element {
+    let lines = diff
+        .changes
+        .iter()
+        .map(|x| x.describe())
+        .chain(diff.added_files.iter().map(|x| format!("+ {}", x.display())))
+        .chain(diff.removed_files.iter().map(|x| format!("- {}", x.display())))
+        .chain(diff.modified_files.iter().map(|x| format!("~ {}", x.display())))
+        .collect::<Vec<_>>();
+    rsx! {
+        Button {
+            roundness: Roundness::None,
+            clickable: false,
+            extended_css_class: "p-[20px] bg-background",
+            string_placements: rsx! {
+                div {
+                    class: "flex flex-col gap-[10px]",
+                    if lines.is_empty() {
+                        Hint { css: "text-[15px]", "與目前的狀態相同" }
+                    }
+                    for line in lines {
+                        Text { css: "text-[15px] font-english", {line} }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SnapshotRetention() -> Element {
+    let mut error = use_error_handler();
+    let settings = SNAPSHOT_SETTINGS();
+    let mut update = move |settings: SnapshotSettings| {
+        error.set(Some(save_settings(settings).map_err(Into::into)));
+    };
+    let count = settings.max_per_collection;
+    let days = settings.max_age_days;
+    let rows = [
+        (
+            "保留數量",
+            format!("最多保留 {count} 個快照"),
+            SnapshotSettings {
+                max_per_collection: count.saturating_sub(1).max(1),
+                ..settings.clone()
+            },
+            SnapshotSettings {
+                max_per_collection: count + 1,
+                ..settings.clone()
+            },
+        ),
+        (
+            "保留天數",
+            days.map_or_else(|| String::from("永久保留"), |x| format!("保留 {x} 天內的快照")),
+            SnapshotSettings {
+                // going below one day turns the age limit off
+                max_age_days: days.and_then(|x| x.checked_sub(1)).filter(|x| *x > 0),
+                ..settings.clone()
+            },
+            SnapshotSettings {
+                max_age_days: Some(days.map_or(1, |x| x + 1)),
+                ..settings.clone()
+            },
+        ),
+    ];
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            for (title , hint , less , more) in rows {
+                Button {
+                    key: "{title}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[25px] first:rounded-t-3xl last:rounded-b-3xl",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![ContentType::text(title), ContentType::hint(hint)],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[15px]"),
+                        ContentType::custom(rsx! {
+                            div {
+                                class: "flex gap-[10px]",
+                                PillAction { label: "-", onclick: move |()| update(less.clone()) }
+                                PillAction { label: "+", onclick: move |()| update(more.clone()) }
+                            }
+                        }).align_right(),
+                    ]
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::BTreeSet;
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_display::GAME_CONTROLLER,
+    collection_edit::EditTemplate,
+    collection_stats::{format_playtime, format_relative, COLLECTION_STATS},
+    journal::{JournalEntry, RevertPlan, JOURNAL},
+    snapshots::list_snapshots,
+    BaseComponents::{
+        atoms::button::{Button, FillMode, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+};
+
+const CHART_DAYS: u64 = 14;
+
+#[component]
+pub fn DataLog(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    rsx! {
+        EditTemplate {
+            title: rsx! {
+                Button {
+                    roundness: Roundness::None,
+                    extended_css_class: "rounded-[20px] p-[40px]",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![
+                                    ContentType::text("收藏紀錄")
+                                        .css("font-black text-white text-[40px]"),
+                                    ContentType::hint("查看這個收藏的資訊"),
+                                ],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[20px]"),
+                        ContentType::svg(GAME_CONTROLLER).css("svg-[70px]").align_right(),
+                    ]
+                }
+            },
+            StatisticsCards { collection_id }
+            PlaytimeChart { collection_id }
+            ChangeTimeline { collection_id }
+        }
+    }
+}
+
+#[component]
+fn StatisticsCards(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let stats = COLLECTION_STATS
+        .read()
+        .get(&collection_id().to_string())
+        .cloned()
+        .unwrap_or_default();
+    let cards = [
+        ("啟動次數", stats.launches.to_string()),
+        ("總遊玩時間", format_playtime(stats.total_playtime)),
+        ("上次遊玩時長", format_playtime(stats.last_session)),
+        ("崩潰次數", stats.crashes.to_string()),
+        (
+            "上次遊玩",
+            stats
+                .last_played
+                .map_or_else(|| String::from("尚未遊玩"), format_relative),
+        ),
+    ];
+    rsx! {
+        div {
+            class: "grid grid-cols-[repeat(auto-fill,minmax(220px,1fr))] gap-[3px]",
+            for (title , value) in cards {
+                Button {
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "rounded-[5px] first:rounded-l-[20px] last:rounded-r-[20px] p-[25px]",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![
+                                    ContentType::hint(title).css("text-[15px]"),
+                                    ContentType::text(value).css("text-[30px] font-black"),
+                                ],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[15px]"),
+                    ]
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PlaytimeChart(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let days = COLLECTION_STATS
+        .read()
+        .get(&collection_id().to_string())
+        .cloned()
+        .unwrap_or_default()
+        .recent_days(CHART_DAYS);
+    let max = days.iter().map(|x| x.1).max().unwrap_or_default().max(1);
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("每日遊玩時間"),
+                                ContentType::hint(format!("最近 {CHART_DAYS} 天")),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                ]
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: rsx! {
+                    div {
+                        class: "flex items-end gap-[10px] h-[200px] w-full",
+                        for (day , seconds) in days {
+                            div {
+                                key: "{day}",
+                                class: "group flex flex-col justify-end items-center gap-[10px] h-full grow",
+                                title: format_playtime(seconds),
+                                div {
+                                    class: "w-full rounded-[5px] bg-secondary-surface group-hover:bg-white transition-all",
+                                    height: format!("{}%", seconds * 100 / max),
+                                    min_height: "3px",
+                                }
+                            }
+                        }
+                    }
+                    div {
+                        class: "flex justify-between pt-[10px]",
+                        Hint { css: "text-[13px]", "{CHART_DAYS} 天前" }
+                        Text { css: "text-[13px]", "今天" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ChangeTimeline(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut selected = use_signal(BTreeSet::<usize>::new);
+    let mut error = use_signal(|| None);
+    // the plan the user was warned about, a changed selection has to be confirmed again
+    let mut confirmed = use_signal(|| None::<RevertPlan>);
+    let entries = JOURNAL
+        .read()
+        .get(&collection_id().to_string())
+        .cloned()
+        .unwrap_or_default();
+    let revert_entries = entries.clone();
+    let snapshots = list_snapshots(&collection_id()).unwrap_or_default();
+    let plan = RevertPlan::new(&entries, &selected.read());
+    let confirming = confirmed.read().as_ref() == Some(&plan);
+    let hint = if confirming {
+        format!(
+            "還原會一併捨棄之後的 {} 項變更：{}，再按一次確認",
+            plan.lost.len(),
+            plan.lost
+                .iter()
+                .map(|x| entries[*x].change.describe())
+                .collect::<Vec<_>>()
+                .join("、")
+        )
+    } else {
+        error().unwrap_or_else(|| String::from("選取可還原的項目並將它們還原"))
+    };
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("變更紀錄"),
+                                ContentType::hint(hint),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        Button {
+                            roundness: Roundness::Pill,
+                            fill_mode: FillMode::Fit,
+                            extended_css_class: "bg-background px-[25px]",
+                            onclick: move |()| {
+                                let plan = RevertPlan::new(&revert_entries, &selected());
+                                if !plan.lost.is_empty() && confirmed.read().as_ref() != Some(&plan) {
+                                    confirmed.set(Some(plan));
+                                    return;
+                                }
+                                confirmed.set(None);
+                                error.set(plan.apply(&collection_id(), &revert_entries).err().map(|x| x.to_string()));
+                                selected.write().clear();
+                            },
+                            string_placements: vec![
+                                ContentType::text(
+                                        if confirming {
+                                            String::from("確認還原")
+                                        } else {
+                                            format!("還原所選 ({})", selected.read().len())
+                                        },
+                                    )
+                                    .align_center(),
+                            ]
+                        }
+                    }).align_right(),
+                ]
+            }
+            if entries.is_empty() {
+                Button {
+                    roundness: Roundness::Bottom,
+                    clickable: false,
+                    extended_css_class: "p-[25px]",
+                    string_placements: vec![ContentType::hint("目前沒有任何變更").align_left()]
+                }
+            }
+            for (index , entry) in entries.into_iter().enumerate().rev() {
+                TimelineRow {
+                    key: "{index}",
+                    index,
+                    revertible: entry.is_revertible(&snapshots),
+                    lost: confirming && plan.lost.contains(&index),
+                    entry,
+                    selected
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn TimelineRow(
+    index: usize,
+    entry: JournalEntry,
+    revertible: bool,
+    /// Undone by the snapshot restore the user is confirming
+    lost: bool,
+    selected: Signal<BTreeSet<usize>>,
+) -> Element {
+    let status = match (lost, revertible) {
+        (true, _) => "將被捨棄",
+        (false, true) => "可還原",
+        (false, false) => "",
+    };
+    rsx! {
+        div {
+            class: "group",
+            aria_selected: selected.read().contains(&index),
+            Button {
+                roundness: Roundness::None,
+                clickable: revertible,
+                extended_css_class: "p-[20px] group-last:rounded-b-3xl group-aria-selected:bg-white group-aria-selected:text-black",
+                onclick: move |()| {
+                    if revertible && !selected.write().remove(&index) {
+                        selected.write().insert(index);
+                    }
+                },
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text(entry.change.describe()).css("text-xl"),
+                                ContentType::hint(
+                                    format!("{}•{}", entry.author, format_relative(entry.timestamp)),
+                                )
+                                .css("text-[13px]"),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[10px]"),
+                    ContentType::hint(status)
+                        .css("text-[13px]")
+                        .align_right(),
+                ]
+            }
+        }
+    }
+}
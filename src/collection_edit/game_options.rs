@@ -0,0 +1,304 @@
+use std::collections::BTreeSet;
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use strum::IntoEnumIterator;
+
+use crate::{
+    collection_display::GAME_CONTROLLER,
+    collection_edit::{advanced::PillAction, EditTemplate},
+    options_txt::{
+        clear_default_options, save_default_options, GameOptions, OptionCategory, DEFAULT_OPTIONS,
+    },
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+};
+
+#[component]
+pub fn GameOptionsEditor(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    let game_directory = use_memo(move || collection_id().get_collection_owned().game_directory());
+    let mut options = use_signal(GameOptions::default);
+    let mut reload = move || match GameOptions::load(&game_directory()) {
+        Ok(x) => options.set(x),
+        Err(x) => error.set(Some(Err(x.into()))),
+    };
+    use_effect(move || reload());
+    let save = move |next: GameOptions| {
+        let result = next.save(&game_directory());
+        if result.is_ok() {
+            options.set(next);
+        }
+        error.set(Some(result.map_err(Into::into)));
+    };
+    rsx! {
+        EditTemplate {
+            title: rsx! {
+                Button {
+                    roundness: Roundness::None,
+                    extended_css_class: "rounded-[20px] p-[40px]",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![
+                                    ContentType::text("遊戲設定").css("font-black text-white text-[40px]"),
+                                    ContentType::hint("編輯這個收藏的按鍵、顯示、音效與操作設定"),
+                                ],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[20px]"),
+                        ContentType::svg(GAME_CONTROLLER).css("svg-[70px]").align_right(),
+                    ]
+                }
+            },
+            SettingsCopy {
+                collection_id,
+                options,
+                onreload: move |()| reload(),
+                onsave: save
+            }
+            for category in OptionCategory::iter() {
+                OptionGroup {
+                    key: "{category:?}",
+                    category,
+                    options,
+                    onsave: save
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SettingsCopy(
+    collection_id: ReadOnlySignal<CollectionId>,
+    options: Signal<GameOptions>,
+    onreload: EventHandler,
+    onsave: EventHandler<GameOptions>,
+) -> Element {
+    let mut error = use_error_handler();
+    let keys = use_context::<Memo<Vec<CollectionId>>>();
+    let mut categories = use_signal(|| OptionCategory::iter().collect::<BTreeSet<_>>());
+    let mut source: Signal<Option<CollectionId>> = use_signal(|| None);
+    let others = keys()
+        .into_iter()
+        .filter(|x| x != &collection_id())
+        .map(|x| {
+            let name = x.get_collection_owned().display_name().to_string();
+            (x, name)
+        })
+        .collect::<Vec<_>>();
+    let template = DEFAULT_OPTIONS.read().entries.len();
+    let template_hint = if template == 0 {
+        String::from("尚未設定範本，新的收藏會使用遊戲預設值")
+    } else {
+        format!("範本包含 {template} 項設定，會套用到新建立的收藏")
+    };
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("套用範圍"),
+                                ContentType::hint("複製設定與儲存範本時只會包含選取的分類"),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            for category in OptionCategory::iter() {
+                                PillAction {
+                                    key: "{category:?}",
+                                    label: category.to_string(),
+                                    selected: categories.read().contains(&category),
+                                    onclick: move |()| {
+                                        let mut categories = categories.write();
+                                        if !categories.remove(&category) {
+                                            categories.insert(category);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: rsx! {
+                    div {
+                        class: "flex flex-col gap-[15px] w-full",
+                        div {
+                            class: "flex items-center justify-between gap-[15px]",
+                            div {
+                                class: "flex flex-col gap-[15px]",
+                                Text { "從其他收藏複製" }
+                                Hint { "以選取收藏的設定取代這個收藏的設定" }
+                            }
+                            div {
+                                class: "flex gap-[10px]",
+                                PillAction {
+                                    label: "重新載入",
+                                    onclick: move |()| onreload.call(())
+                                }
+                                if let Some(from) = source() {
+                                    PillAction {
+                                        label: "複製",
+                                        onclick: move |()| {
+                                            let from = from.get_collection_owned().game_directory();
+                                            match GameOptions::load(&from) {
+                                                Ok(other) => {
+                                                    let mut next = options();
+                                                    next.copy_from(&other, &categories.read());
+                                                    onsave.call(next);
+                                                }
+                                                Err(x) => error.set(Some(Err(x.into()))),
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        if others.is_empty() {
+                            Hint { css: "text-[15px]", "沒有其他收藏" }
+                        }
+                        div {
+                            class: "flex flex-wrap gap-[10px]",
+                            for (id , name) in others {
+                                PillAction {
+                                    key: "{id}",
+                                    label: name,
+                                    selected: source.read().as_ref() == Some(&id),
+                                    onclick: move |()| source.set(Some(id.clone()))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("預設範本"), ContentType::hint(template_hint)],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            PillAction {
+                                label: "儲存為範本",
+                                onclick: move |()| {
+                                    let result = save_default_options(&options.read(), &categories.read());
+                                    error.set(Some(result.map_err(Into::into)));
+                                }
+                            }
+                            if template > 0 {
+                                PillAction {
+                                    label: "清除範本",
+                                    onclick: move |()| error.set(Some(clear_default_options().map_err(Into::into)))
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+        }
+    }
+}
+
+#[component]
+fn OptionGroup(
+    category: OptionCategory,
+    options: Signal<GameOptions>,
+    onsave: EventHandler<GameOptions>,
+) -> Element {
+    let entries = options
+        .read()
+        .in_category(category)
+        .cloned()
+        .collect::<Vec<_>>();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: if entries.is_empty() { Roundness::Squircle } else { Roundness::Top },
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text(category.to_string()),
+                                ContentType::hint(
+                                    if entries.is_empty() {
+                                        String::from("啟動一次遊戲後才會有這些設定")
+                                    } else {
+                                        format!("{} 項設定", entries.len())
+                                    },
+                                ),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                ]
+            }
+            for (key , value) in entries {
+                Button {
+                    key: "{key}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "px-[25px] py-[15px] last:rounded-b-3xl",
+                    string_placements: vec![
+                        ContentType::text(key.trim_start_matches("key_").to_owned())
+                            .css("font-english text-[18px]")
+                            .align_left(),
+                        ContentType::custom(rsx! {
+                            if value == "true" || value == "false" {
+                                PillAction {
+                                    label: if value == "true" { "已開啟" } else { "已關閉" },
+                                    selected: value == "true",
+                                    onclick: {
+                                        let key = key.clone();
+                                        let toggled = (value != "true").to_string();
+                                        move |()| {
+                                            let mut next = options();
+                                            next.set(&key, toggled.clone());
+                                            onsave.call(next);
+                                        }
+                                    }
+                                }
+                            } else {
+                                input {
+                                    class: "w-[260px] rounded-[15px] bg-deep-background px-[15px] py-[10px] text-white font-english",
+                                    value: value,
+                                    onchange: {
+                                        let key = key.clone();
+                                        move |x: FormEvent| {
+                                            let mut next = options();
+                                            next.set(&key, x.value());
+                                            onsave.call(next);
+                                        }
+                                    }
+                                }
+                            }
+                        }).align_right(),
+                    ]
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_edit::advanced::PillAction,
+    java_runtimes::{
+        best_runtime, download_runtime, redetect_runtimes, required_major, runtime_override,
+        set_java_mirror, set_runtime_override, use_runtimes, JAVA_MIRROR,
+    },
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint},
+    },
+};
+
+#[component]
+pub fn JavaRuntimeSettings(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    let mut busy = use_signal(|| false);
+    let runtimes = use_runtimes();
+    let mirror = JAVA_MIRROR
+        .read()
+        .as_ref()
+        .map(|x| x.display().to_string())
+        .unwrap_or_default();
+    let major = required_major(collection_id().get_collection().read().minecraft_version());
+    let pinned = runtime_override(&collection_id());
+    let automatic = runtimes.as_deref().and_then(|x| best_runtime(x, major));
+    let current = match (&pinned, &runtimes) {
+        (Some(home), Some(runtimes)) => runtimes.iter().find(|x| &x.home == home).cloned(),
+        _ => automatic.clone(),
+    };
+    let hint = match (&current, &runtimes) {
+        (_, None) => String::from("正在尋找已安裝的 Java…"),
+        (Some(x), _) => format!(
+            "此版本需要 Java {major}，目前使用 {} {}",
+            x.vendor, x.version
+        ),
+        (None, _) if pinned.is_some() => String::from("指定的 Java 已不存在，請重新選擇"),
+        (None, _) => format!("此版本需要 Java {major}，但找不到適合的 Java"),
+    };
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("Java 執行環境"), ContentType::hint(hint)],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            PillAction {
+                                label: "自動選擇",
+                                selected: pinned.is_none(),
+                                onclick: move |()| {
+                                    let result = set_runtime_override(&collection_id(), None);
+                                    error.set(Some(result.map_err(Into::into)));
+                                }
+                            }
+                            PillAction {
+                                label: "重新偵測",
+                                onclick: move |()| {
+                                    spawn(async move {
+                                        redetect_runtimes().await;
+                                    });
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            for runtime in runtimes.clone().unwrap_or_default() {
+                Button {
+                    key: "{runtime.home.display()}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[20px]",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![
+                                    ContentType::text(format!("Java {}•{}", runtime.major, runtime.vendor)),
+                                    ContentType::hint(format!("{}•{}•{}", runtime.version, runtime.origin, runtime.home.display()))
+                                        .css("text-[13px] font-english"),
+                                ],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[10px]"),
+                        ContentType::custom(rsx! {
+                            PillAction {
+                                label: if automatic.as_ref() == Some(&runtime) { "使用 (建議)" } else { "使用" },
+                                selected: pinned.as_ref() == Some(&runtime.home),
+                                onclick: {
+                                    let home = runtime.home.clone();
+                                    move |()| {
+                                        let result = set_runtime_override(&collection_id(), Some(home.clone()));
+                                        error.set(Some(result.map_err(Into::into)));
+                                    }
+                                }
+                            }
+                        }).align_right(),
+                    ]
+                }
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("下載來源"),
+                                ContentType::hint("填入存放 Java 壓縮檔的資料夾以離線安裝，留空則從 Adoptium 下載"),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        input {
+                            class: "bg-transparent text-white text-[15px] font-english outline-none text-right",
+                            placeholder: "Adoptium",
+                            value: mirror,
+                            oninput: move |x| {
+                                let dir = Some(x.value()).filter(|x| !x.trim().is_empty()).map(PathBuf::from);
+                                error.set(Some(set_java_mirror(dir).map_err(Into::into)));
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    ContentType::hint(format!("下載 Java {major} 並交由啟動器管理")).align_left(),
+                    ContentType::custom(rsx! {
+                        if busy() {
+                            Hint { css: "text-[15px]", "下載中…" }
+                        } else {
+                            PillAction {
+                                label: format!("下載 Java {major}"),
+                                onclick: move |()| {
+                                    busy.set(true);
+                                    spawn(async move {
+                                        let result = download_runtime(major).await;
+                                        busy.set(false);
+                                        error.set(Some(result.map(|_| ()).map_err(Into::into)));
+                                    });
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+        }
+    }
+}
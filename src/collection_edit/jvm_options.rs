@@ -0,0 +1,243 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use strum::IntoEnumIterator;
+
+use crate::{
+    collection_edit::advanced::PillAction,
+    jvm_settings::{
+        collection_settings, format_env, parse_env, save_collection_settings, save_defaults,
+        system_memory_megabytes, GcPreset, JvmSettings, JVM_SETTINGS,
+    },
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Text},
+    },
+};
+
+/// How much a click on `-`/`+` changes a heap size.
+const HEAP_STEP: u64 = 512;
+/// Where `+` starts when neither the collection nor the defaults set a heap size.
+const HEAP_START: u64 = 2048;
+
+fn heap_label(value: Option<u64>, inherited: Option<u64>, global: bool) -> String {
+    match (value, inherited) {
+        (Some(x), _) => format!("{x} MB"),
+        (None, Some(x)) if !global => format!("沿用預設 {x} MB"),
+        (None, _) => String::from("由 Java 決定"),
+    }
+}
+
+fn step_heap(value: Option<u64>, inherited: Option<u64>, more: bool) -> Option<u64> {
+    let current = value.or(inherited);
+    Some(match (current, more) {
+        (None, _) => HEAP_START,
+        (Some(x), true) => x + HEAP_STEP,
+        (Some(x), false) => x.saturating_sub(HEAP_STEP).max(HEAP_STEP),
+    })
+}
+
+#[component]
+pub fn JvmOptions(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    // editing the global defaults instead of this collection
+    let mut global = use_signal(|| false);
+    let defaults = JVM_SETTINGS.read().defaults.clone();
+    let (stored, inherited) = if global() {
+        (defaults, JvmSettings::default())
+    } else {
+        (collection_settings(&collection_id()), defaults)
+    };
+    let mut update = move |settings: JvmSettings| {
+        let result = if global() {
+            save_defaults(settings)
+        } else {
+            save_collection_settings(&collection_id(), settings)
+        };
+        error.set(Some(result.map_err(Into::into)));
+    };
+    let memory_hint = system_memory_megabytes().map_or_else(
+        || String::from("無法讀取系統記憶體"),
+        |x| format!("系統記憶體 {x} MB"),
+    );
+    let heap_row = |title: &'static str, field: fn(&mut JvmSettings) -> &mut Option<u64>| {
+        let value = *field(&mut stored.clone());
+        let inherited = *field(&mut inherited.clone());
+        let with = |x: Option<u64>| {
+            let mut settings = stored.clone();
+            *field(&mut settings) = x;
+            settings
+        };
+        (
+            title,
+            heap_label(value, inherited, global()),
+            with(step_heap(value, inherited, false)),
+            with(step_heap(value, inherited, true)),
+            with(None),
+        )
+    };
+    let heaps = [
+        heap_row("最小記憶體", |x| &mut x.min_heap_megabytes),
+        heap_row("最大記憶體", |x| &mut x.max_heap_megabytes),
+    ];
+    let gc = stored.gc;
+    let gc_hint = match (gc, inherited.gc) {
+        (Some(x), _) => format!("使用 {x}"),
+        (None, Some(x)) if !global() => format!("沿用預設 {x}"),
+        (None, _) => String::from("由 Java 決定"),
+    };
+    let extra_args = stored.extra_args.clone().unwrap_or_default();
+    let extra_placeholder = if global() {
+        String::from("例如 -Dfile.encoding=UTF-8")
+    } else {
+        inherited.extra_args.clone().map_or_else(
+            || String::from("沒有額外參數"),
+            |x| format!("沿用預設：{x}"),
+        )
+    };
+    let env = stored.env.as_ref().map(format_env).unwrap_or_default();
+    let env_placeholder = if global() {
+        String::from("每行一個 KEY=VALUE")
+    } else {
+        inherited
+            .env
+            .as_ref()
+            .map_or_else(|| String::from("每行一個 KEY=VALUE"), format_env)
+    };
+    let stored_for_args = stored.clone();
+    let stored_for_env = stored.clone();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("Java 虛擬機"),
+                                ContentType::hint(
+                                    if global() {
+                                        format!("所有未單獨設定的收藏都會使用這些選項•{memory_hint}")
+                                    } else {
+                                        format!("未設定的選項會沿用全域預設•{memory_hint}")
+                                    },
+                                ),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            PillAction { label: "此收藏", selected: !global(), onclick: move |()| global.set(false) }
+                            PillAction { label: "全域預設", selected: global(), onclick: move |()| global.set(true) }
+                        }
+                    }).align_right(),
+                ]
+            }
+            for (title , hint , less , more , reset) in heaps {
+                Button {
+                    key: "{title}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[25px]",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![ContentType::text(title), ContentType::hint(hint)],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[15px]"),
+                        ContentType::custom(rsx! {
+                            div {
+                                class: "flex gap-[10px]",
+                                PillAction { label: "-", onclick: move |()| update(less.clone()) }
+                                PillAction { label: "+", onclick: move |()| update(more.clone()) }
+                                PillAction { label: "預設", onclick: move |()| update(reset.clone()) }
+                            }
+                        }).align_right(),
+                    ]
+                }
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("垃圾回收器"), ContentType::hint(gc_hint)],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            for preset in GcPreset::iter() {
+                                PillAction {
+                                    key: "{preset}",
+                                    label: preset.to_string(),
+                                    selected: gc == Some(preset),
+                                    onclick: {
+                                        let stored = stored.clone();
+                                        move |()| update(JvmSettings { gc: Some(preset), ..stored.clone() })
+                                    }
+                                }
+                            }
+                            PillAction {
+                                label: "預設",
+                                selected: gc.is_none(),
+                                onclick: {
+                                    let stored = stored.clone();
+                                    move |()| update(JvmSettings { gc: None, ..stored.clone() })
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: rsx! {
+                    div {
+                        class: "flex flex-col gap-[15px] w-full",
+                        Text { "額外參數" }
+                        input {
+                            class: "w-full rounded-[15px] bg-deep-background px-[20px] py-[10px] text-white font-english",
+                            placeholder: extra_placeholder,
+                            value: extra_args,
+                            onchange: move |x| {
+                                let value = x.value();
+                                let extra_args = (!value.trim().is_empty()).then(|| value.trim().to_owned());
+                                update(JvmSettings { extra_args, ..stored_for_args.clone() });
+                            }
+                        }
+                    }
+                }
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: rsx! {
+                    div {
+                        class: "flex flex-col gap-[15px] w-full",
+                        Text { "環境變數" }
+                        textarea {
+                            class: "w-full min-h-[100px] rounded-[15px] bg-deep-background px-[20px] py-[10px] text-white font-english",
+                            placeholder: env_placeholder,
+                            value: env,
+                            onchange: move |x| {
+                                let env = parse_env(&x.value());
+                                let env = (!env.is_empty()).then_some(env);
+                                update(JvmSettings { env, ..stored_for_env.clone() });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_edit::advanced::PillAction,
+    launch_hooks::{launch_hooks, save_launch_hooks, LaunchHooks, TIMEOUT_PRESETS},
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+};
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+#[component]
+pub fn LaunchHooksSettings(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    let hooks = launch_hooks(&collection_id());
+    let mut update = move |hooks: LaunchHooks| {
+        let result = save_launch_hooks(&collection_id(), hooks);
+        error.set(Some(result.map_err(Into::into)));
+    };
+    let timeout = hooks.timeout_seconds();
+    let commands = [
+        (
+            "包裝指令",
+            "放在 Java 前面執行，例如效能工具或疊加層",
+            "gamemoderun mangohud",
+            hooks.wrapper.clone().unwrap_or_default(),
+            Roundness::None,
+        ),
+        (
+            "啟動前指令",
+            "在遊戲啟動前於遊戲目錄執行，失敗或逾時會中止啟動",
+            "./sync.sh pull",
+            hooks.pre_launch.clone().unwrap_or_default(),
+            Roundness::None,
+        ),
+        (
+            "結束後指令",
+            "在遊戲結束後於遊戲目錄執行",
+            "./sync.sh push",
+            hooks.post_exit.clone().unwrap_or_default(),
+            Roundness::Bottom,
+        ),
+    ];
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("啟動掛鉤"),
+                                ContentType::hint(format!("指令的輸出會寫入紀錄，超過 {timeout} 秒未結束會被終止")),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            for seconds in TIMEOUT_PRESETS {
+                                PillAction {
+                                    key: "{seconds}",
+                                    label: format!("{seconds} 秒"),
+                                    selected: timeout == seconds,
+                                    onclick: {
+                                        let hooks = hooks.clone();
+                                        move |()| update(LaunchHooks { timeout_seconds: Some(seconds), ..hooks.clone() })
+                                    }
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            for (index , (title , hint , placeholder , value , roundness)) in commands.into_iter().enumerate() {
+                Button {
+                    key: "{title}",
+                    roundness: roundness,
+                    clickable: false,
+                    extended_css_class: "p-[25px]",
+                    string_placements: rsx! {
+                        div {
+                            class: "flex flex-col gap-[15px] w-full",
+                            Text { {title} }
+                            Hint { css: "text-[15px]", {hint} }
+                            input {
+                                class: "w-full rounded-[15px] bg-deep-background px-[20px] py-[10px] text-white font-english",
+                                placeholder: placeholder,
+                                value: value,
+                                onchange: {
+                                    let hooks = hooks.clone();
+                                    move |x: FormEvent| {
+                                        let value = non_empty(x.value());
+                                        let hooks = hooks.clone();
+                                        update(match index {
+                                            0 => LaunchHooks { wrapper: value, ..hooks },
+                                            1 => LaunchHooks { pre_launch: value, ..hooks },
+                                            _ => LaunchHooks { post_exit: value, ..hooks },
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_edit::advanced::PillAction,
+    java_runtimes::use_runtimes,
+    launch_plan::{
+        export_script, plan_launch, preview_plan, script_path, shell_quote, GAME_COMMANDS,
+    },
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+};
+
+fn copy_to_clipboard(text: String) -> anyhow::Result<()> {
+    eval("navigator.clipboard.writeText(await dioxus.recv());")
+        .send(text.into())
+        .map_err(|x| anyhow::anyhow!("{x:?}"))
+}
+
+#[component]
+pub fn LaunchPreview(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    let mut busy = use_signal(|| false);
+    let mut confirm_export = use_signal(|| false);
+    let mut exported = use_signal(|| None);
+    use_runtimes();
+    // built from the command of the last launch, so showing it never touches the network
+    let plan = use_memo(move || {
+        GAME_COMMANDS.read();
+        preview_plan(&collection_id())
+    });
+    let hint = match (busy(), exported(), confirm_export()) {
+        (true, _, _) => String::from("正在下載並解析啟動指令…"),
+        (false, Some(path), _) => format!("已匯出至 {path}"),
+        (false, None, true) => plan().map_or_else(String::new, |x| {
+            format!("將寫入 {}，再按一次確認匯出", script_path(&x).display())
+        }),
+        (false, None, false) if plan.read().is_none() => {
+            String::from("啟動過一次或按下解析後即可預覽，登入憑證會被隱藏")
+        }
+        (false, None, false) => String::from("上次啟動時的指令套用目前的設定，登入憑證會被隱藏"),
+    };
+    let sections = plan().map(|x| {
+        [
+            ("包裝指令", x.wrapper.clone()),
+            ("Java", vec![x.java.display().to_string()]),
+            ("工作目錄", vec![x.working_directory.display().to_string()]),
+            ("JVM 參數", x.jvm_args.clone()),
+            ("主類別", vec![x.main_class.clone()]),
+            ("遊戲參數", x.game_args.clone()),
+            (
+                "環境變數",
+                x.env
+                    .iter()
+                    .map(|(key, value)| format!("{key}={}", shell_quote(value)))
+                    .collect(),
+            ),
+            (
+                "Classpath",
+                x.classpath()
+                    .iter()
+                    .map(|x| x.display().to_string())
+                    .collect(),
+            ),
+        ]
+    });
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: if plan.read().is_some() { "p-[25px]" } else { "p-[25px] rounded-b-3xl" },
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("啟動指令預覽"), ContentType::hint(hint)],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            PillAction {
+                                label: "解析",
+                                onclick: move |()| {
+                                    if busy() {
+                                        return;
+                                    }
+                                    busy.set(true);
+                                    exported.set(None);
+                                    confirm_export.set(false);
+                                    spawn(async move {
+                                        // the same path a launch takes, which refreshes the preview
+                                        let result = plan_launch(&collection_id(), None).await;
+                                        busy.set(false);
+                                        if let Err(x) = result {
+                                            error.set(Some(Err(x.into())));
+                                        }
+                                    });
+                                }
+                            }
+                            if let Some(current) = plan() {
+                                PillAction {
+                                    label: "複製",
+                                    onclick: move |()| {
+                                        error.set(Some(copy_to_clipboard(current.shell_script())));
+                                    }
+                                }
+                            }
+                            if let Some(current) = plan() {
+                                PillAction {
+                                    label: if confirm_export() { "確認匯出" } else { "匯出腳本" },
+                                    selected: confirm_export(),
+                                    onclick: move |()| {
+                                        if !confirm_export() {
+                                            exported.set(None);
+                                            confirm_export.set(true);
+                                            return;
+                                        }
+                                        confirm_export.set(false);
+                                        match export_script(&current) {
+                                            Ok(path) => exported.set(Some(path.display().to_string())),
+                                            Err(x) => error.set(Some(Err(x.into()))),
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            for (title , lines) in sections.into_iter().flatten() {
+                Button {
+                    key: "{title}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[20px] last:rounded-b-3xl",
+                    string_placements: rsx! {
+                        div {
+                            class: "flex flex-col gap-[10px] w-full",
+                            Text { css: "text-[15px]", {format!("{title}•{}", lines.len())} }
+                            if lines.is_empty() {
+                                Hint { css: "text-[13px]", "無" }
+                            }
+                            for (index , line) in lines.into_iter().enumerate() {
+                                Hint {
+                                    key: "{index}",
+                                    css: "text-[13px] font-english break-all select-text",
+                                    {line}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_edit::advanced::PillAction,
+    launch_options::{launch_options, save_launch_options, LaunchOptions, QuickPlay},
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents},
+    },
+};
+
+const SIZE_INPUT: &str =
+    "w-[110px] rounded-[15px] bg-deep-background px-[15px] py-[10px] text-white font-english";
+
+#[component]
+pub fn LaunchSettings(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    let options = launch_options(&collection_id());
+    let mut update = move |options: LaunchOptions| {
+        let result = save_launch_options(&collection_id(), options);
+        error.set(Some(result.map_err(Into::into)));
+    };
+    let size_hint = match (options.width, options.height) {
+        (Some(width), Some(height)) => format!("{width} × {height}"),
+        _ => String::from("使用遊戲預設的視窗大小"),
+    };
+    let quick_play_hint = options.quick_play.as_ref().map_or_else(
+        || String::from("啟動後停在主選單，也可以在世界分頁選擇要進入的世界或伺服器"),
+        |x| format!("啟動後直接進入{x}，單人世界需要 Minecraft 1.20 以上"),
+    );
+    let server = match &options.quick_play {
+        Some(QuickPlay::Multiplayer(x)) => x.clone(),
+        _ => String::new(),
+    };
+    let fullscreen_modes = [
+        ("預設", None),
+        ("全螢幕", Some(true)),
+        ("視窗", Some(false)),
+    ]
+    .map(|(label, fullscreen)| {
+        let toggled = LaunchOptions {
+            fullscreen,
+            ..options.clone()
+        };
+        (label, options.fullscreen == fullscreen, toggled)
+    });
+    let demo = LaunchOptions {
+        demo: !options.demo,
+        ..options.clone()
+    };
+    let for_width = options.clone();
+    let for_height = options.clone();
+    let for_reset = options.clone();
+    let for_server = options.clone();
+    let for_clear = options.clone();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("視窗大小"), ContentType::hint(size_hint)],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex items-center gap-[10px]",
+                            input {
+                                class: SIZE_INPUT,
+                                r#type: "number",
+                                min: "1",
+                                placeholder: "寬度",
+                                value: options.width.map(|x| x.to_string()).unwrap_or_default(),
+                                onchange: move |x| {
+                                    update(LaunchOptions { width: x.value().parse().ok(), ..for_width.clone() });
+                                }
+                            }
+                            input {
+                                class: SIZE_INPUT,
+                                r#type: "number",
+                                min: "1",
+                                placeholder: "高度",
+                                value: options.height.map(|x| x.to_string()).unwrap_or_default(),
+                                onchange: move |x| {
+                                    update(LaunchOptions { height: x.value().parse().ok(), ..for_height.clone() });
+                                }
+                            }
+                            PillAction {
+                                label: "預設",
+                                onclick: move |()| {
+                                    update(LaunchOptions { width: None, height: None, ..for_reset.clone() });
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("全螢幕"), ContentType::hint("預設沿用遊戲內的設定")],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex items-center gap-[10px]",
+                            for (label , selected , toggled) in fullscreen_modes {
+                                PillAction {
+                                    key: "{label}",
+                                    label,
+                                    selected,
+                                    onclick: move |()| update(toggled.clone())
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("試玩模式"), ContentType::hint("以試玩版的限制啟動遊戲")],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        PillAction {
+                            label: if options.demo { "已開啟" } else { "已關閉" },
+                            selected: options.demo,
+                            onclick: move |()| update(demo.clone())
+                        }
+                    }).align_right(),
+                ]
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("快速遊玩"), ContentType::hint(quick_play_hint)],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex items-center gap-[10px]",
+                            input {
+                                class: "w-[240px] rounded-[15px] bg-deep-background px-[15px] py-[10px] text-white font-english",
+                                placeholder: "伺服器位址",
+                                value: server,
+                                onchange: move |x| {
+                                    let address = x.value().trim().to_owned();
+                                    let quick_play = (!address.is_empty()).then_some(QuickPlay::Multiplayer(address));
+                                    update(LaunchOptions { quick_play, ..for_server.clone() });
+                                }
+                            }
+                            PillAction {
+                                label: "不使用",
+                                selected: options.quick_play.is_none(),
+                                onclick: move |()| update(LaunchOptions { quick_play: None, ..for_clear.clone() })
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_edit::advanced::PillAction,
+    jar_metadata::LoaderKind,
+    loaders::{current_loader, incompatible_mods, loader_builds, switch_loader},
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+};
+
+/// Builds listed at once, the rest are rarely wanted.
+const SHOWN_BUILDS: usize = 12;
+
+#[component]
+pub fn LoaderSettings(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    let current = current_loader(&collection_id());
+    let game_version = collection_id()
+        .get_collection()
+        .read()
+        .minecraft_version()
+        .id
+        .clone();
+    let mut kind = use_signal(|| current.as_ref().map_or(LoaderKind::Fabric, |x| x.0));
+    let mut build: Signal<Option<String>> = use_signal(|| None);
+    let mut busy = use_signal(|| false);
+    let builds = {
+        let game_version = game_version.clone();
+        use_resource(move || {
+            let game_version = game_version.clone();
+            async move { loader_builds(kind(), &game_version).await }
+        })
+    };
+    let builds = builds
+        .read()
+        .as_ref()
+        .map(|x| x.as_ref().cloned().map_err(ToString::to_string));
+    let incompatible = if current.as_ref().is_some_and(|x| x.0 == kind()) {
+        Vec::new()
+    } else {
+        incompatible_mods(&collection_id(), kind()).unwrap_or_default()
+    };
+    let current_label = match &current {
+        Some((kind, Some(version))) => format!("目前使用 {kind} {version}"),
+        Some((kind, None)) => format!("目前使用 {kind}，未指定版本"),
+        None => String::from("目前沒有使用模組載入器"),
+    };
+    let installer_hint = matches!(kind(), LoaderKind::Forge | LoaderKind::NeoForge)
+        .then(|| format!("將會下載並執行 {} 的安裝程式", kind()));
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![ContentType::text("模組載入器"), ContentType::hint(current_label)],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px]",
+                            for loader in LoaderKind::ALL {
+                                PillAction {
+                                    key: "{loader}",
+                                    label: loader.to_string(),
+                                    selected: kind() == loader,
+                                    onclick: move |()| {
+                                        kind.set(loader);
+                                        build.set(None);
+                                    }
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: rsx! {
+                    div {
+                        class: "flex flex-col gap-[15px]",
+                        Text { css: "text-[15px]", {format!("Minecraft {game_version} 的 {} 版本", kind())} }
+                        div {
+                            class: "flex flex-wrap gap-[10px]",
+                            {
+                                match builds {
+                                    None => rsx! { Hint { css: "text-[15px]", "載入中…" } },
+                                    Some(Err(x)) => rsx! { Hint { css: "text-[15px]", "無法取得版本列表：{x}" } },
+                                    Some(Ok(builds)) if builds.is_empty() => rsx! {
+                                        Hint { css: "text-[15px]", "此遊戲版本沒有可用的版本" }
+                                    },
+                                    Some(Ok(builds)) => rsx! {
+                                        for x in builds.into_iter().take(SHOWN_BUILDS) {
+                                            BuildPill {
+                                                key: "{x.version}",
+                                                version: x.version,
+                                                stable: x.stable,
+                                                build
+                                            }
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if !incompatible.is_empty() {
+                Button {
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[25px] bg-background",
+                    string_placements: rsx! {
+                        div {
+                            class: "flex flex-col gap-[10px]",
+                            Text { css: "text-[15px]", {format!("{} 個模組無法在 {} 上執行", incompatible.len(), kind())} }
+                            for name in incompatible {
+                                Hint { css: "text-[15px] font-english", {name} }
+                            }
+                        }
+                    }
+                }
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    ContentType::hint(
+                            installer_hint.unwrap_or_else(|| String::from("套用前會自動建立快照")),
+                        )
+                        .align_left(),
+                    ContentType::custom(rsx! {
+                        if busy() {
+                            Hint { css: "text-[15px]", "套用中…" }
+                        } else if let Some(version) = build() {
+                            PillAction {
+                                label: "套用",
+                                onclick: move |()| {
+                                    let version = version.clone();
+                                    busy.set(true);
+                                    spawn(async move {
+                                        let result = switch_loader(&collection_id(), kind(), version).await;
+                                        busy.set(false);
+                                        build.set(None);
+                                        error.set(Some(result.map_err(Into::into)));
+                                    });
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+        }
+    }
+}
+
+#[component]
+fn BuildPill(version: String, stable: bool, mut build: Signal<Option<String>>) -> Element {
+    let selected = build.read().as_deref() == Some(version.as_str());
+    rsx! {
+        PillAction {
+            label: if stable { version.clone() } else { format!("{version} (測試版)") },
+            selected,
+            onclick: move |()| build.set(Some(version.clone()))
+        }
+    }
+}
@@ -10,6 +10,7 @@ use rust_lib::api::shared_resources::collection::CollectionId;
 use crate::{
     collection_display::GAME_CONTROLLER,
     collection_edit::EditTemplate,
+    journal::with_mut_collection,
     use_error_handler,
     BaseComponents::{
         atoms::button::{Button, Roundness},
@@ -80,7 +81,7 @@ fn ModifyName(collection_id: ReadOnlySignal<CollectionId>) -> Element {
                     input {
                         oninput: move |x| async move {
                             input.set(Some(x.value()));
-                            collection_id().with_mut_collection(|ele| {
+                            with_mut_collection(&collection_id(), |ele| {
                                 *ele.display_name = x.value()
                             })
                             .unwrap();
@@ -127,7 +128,7 @@ fn ModifyPicture(collection_id: ReadOnlySignal<CollectionId>) -> Element {
             if change() {
                 if let Some(x) = active() {
                     let path = PathBuf::from(COLLECTION_PICS.read().get(x).unwrap().to_string());
-                    collection_id().with_mut_collection(|x| *x.picture_path = path)?;
+                    with_mut_collection(&collection_id(), |x| *x.picture_path = path)?;
                     change.set(false);
                 }
             }
@@ -141,7 +142,7 @@ fn ModifyPicture(collection_id: ReadOnlySignal<CollectionId>) -> Element {
             if let Some(x) = filename() {
                 if !x.is_empty() {
                     let path = PathBuf::from(x);
-                    collection_id().with_mut_collection(|x| *x.picture_path = path)?;
+                    with_mut_collection(&collection_id(), |x| *x.picture_path = path)?;
                 }
             }
             Ok(())
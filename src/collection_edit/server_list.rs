@@ -0,0 +1,474 @@
+use std::collections::BTreeMap;
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_display::GAME_CONTROLLER,
+    collection_edit::{advanced::PillAction, EditTemplate},
+    servers::{
+        ping, read_servers, save_shared_servers, sync_shared, write_servers, ServerEntry,
+        ServerStatus, SharedServer, SHARED_SERVERS,
+    },
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+};
+
+const INPUT: &str = "rounded-[15px] bg-deep-background px-[15px] py-[10px] text-white font-english";
+
+#[derive(Debug, Clone, PartialEq)]
+enum PingState {
+    Pending,
+    Done(ServerStatus),
+    Failed(String),
+}
+
+/// Keyed by address, shared by both lists so a server tested in one shows its status in the other.
+type Statuses = Signal<BTreeMap<String, PingState>>;
+
+fn start_ping(mut statuses: Statuses, address: String) {
+    statuses.write().insert(address.clone(), PingState::Pending);
+    spawn(async move {
+        let state = match ping(&address).await {
+            Ok(x) => PingState::Done(x),
+            Err(x) => PingState::Failed(x.to_string()),
+        };
+        statuses.write().insert(address, state);
+    });
+}
+
+/// Applies `change` to a copy of the list, unless `index` went stale in the meantime.
+fn edit(
+    servers: Signal<Vec<ServerEntry>>,
+    onsave: EventHandler<Vec<ServerEntry>>,
+    index: usize,
+    change: impl FnOnce(&mut Vec<ServerEntry>),
+) {
+    let mut next = servers();
+    if index < next.len() {
+        change(&mut next);
+        onsave.call(next);
+    }
+}
+
+fn status_hint(state: Option<&PingState>) -> String {
+    match state {
+        None => String::from("尚未測試連線"),
+        Some(PingState::Pending) => String::from("正在連線…"),
+        Some(PingState::Done(x)) => {
+            let motd = if x.motd.trim().is_empty() {
+                "沒有描述"
+            } else {
+                x.motd.trim()
+            };
+            let latency = x
+                .latency
+                .map(|x| format!("•{} ms", x.as_millis()))
+                .unwrap_or_default();
+            format!(
+                "{motd}•{}/{} 位玩家•{}{latency}",
+                x.online, x.max, x.version
+            )
+        }
+        Some(PingState::Failed(x)) => format!("無法連線：{x}"),
+    }
+}
+
+#[component]
+pub fn ServerListEditor(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    let game_directory = use_memo(move || collection_id().get_collection_owned().game_directory());
+    let mut servers = use_signal(Vec::<ServerEntry>::new);
+    let statuses: Statuses = use_signal(BTreeMap::new);
+    let mut reload = move || match read_servers(&game_directory()) {
+        Ok(x) => servers.set(x),
+        Err(x) => error.set(Some(Err(x.into()))),
+    };
+    use_effect(move || reload());
+    let save = move |next: Vec<ServerEntry>| {
+        let result = write_servers(&game_directory(), &next);
+        if result.is_ok() {
+            servers.set(next);
+        }
+        error.set(Some(result.map_err(Into::into)));
+    };
+    rsx! {
+        EditTemplate {
+            title: rsx! {
+                Button {
+                    roundness: Roundness::None,
+                    extended_css_class: "rounded-[20px] p-[40px]",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![
+                                    ContentType::text("伺服器列表").css("font-black text-white text-[40px]"),
+                                    ContentType::hint("管理這個收藏在多人遊戲中列出的伺服器"),
+                                ],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[20px]"),
+                        ContentType::svg(GAME_CONTROLLER).css("svg-[70px]").align_right(),
+                    ]
+                }
+            },
+            CollectionServers {
+                servers,
+                statuses,
+                onsave: save
+            }
+            SharedServers {
+                collection_id,
+                statuses,
+                onsynced: move |()| reload()
+            }
+        }
+    }
+}
+
+#[component]
+fn CollectionServers(
+    servers: Signal<Vec<ServerEntry>>,
+    statuses: Statuses,
+    onsave: EventHandler<Vec<ServerEntry>>,
+) -> Element {
+    let mut error = use_error_handler();
+    let mut new_name = use_signal(String::new);
+    let mut new_address = use_signal(String::new);
+    let entries = servers();
+    let len = entries.len();
+    let shared = SHARED_SERVERS.read().clone();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("此收藏的伺服器"),
+                                ContentType::hint(format!("{len} 個伺服器，遊戲中會以這個順序列出")),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        PillAction {
+                            label: "全部測試",
+                            onclick: move |()| {
+                                for entry in servers() {
+                                    start_ping(statuses, entry.address);
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            for (index , entry) in entries.into_iter().enumerate() {
+                Button {
+                    key: "{index}{entry.address}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[25px]",
+                    string_placements: rsx! {
+                        div {
+                            class: "flex flex-col gap-[15px] w-full",
+                            div {
+                                class: "flex items-center gap-[10px]",
+                                input {
+                                    class: "{INPUT} w-[260px]",
+                                    placeholder: "名稱",
+                                    value: entry.name.clone(),
+                                    onchange: move |x: FormEvent| {
+                                        edit(servers, onsave, index, |servers| servers[index].name = x.value());
+                                    }
+                                }
+                                input {
+                                    class: "{INPUT} grow",
+                                    placeholder: "位址",
+                                    value: entry.address.clone(),
+                                    onchange: move |x: FormEvent| {
+                                        let address = x.value().trim().to_owned();
+                                        if !address.is_empty() {
+                                            edit(servers, onsave, index, |servers| servers[index].address = address);
+                                        }
+                                    }
+                                }
+                                if index > 0 {
+                                    PillAction {
+                                        label: "上移",
+                                        onclick: move |()| edit(servers, onsave, index, |servers| servers.swap(index, index - 1))
+                                    }
+                                }
+                                if index + 1 < len {
+                                    PillAction {
+                                        label: "下移",
+                                        onclick: move |()| edit(servers, onsave, index, |servers| servers.swap(index, index + 1))
+                                    }
+                                }
+                                PillAction {
+                                    label: "測試",
+                                    onclick: {
+                                        let address = entry.address.clone();
+                                        move |()| start_ping(statuses, address.clone())
+                                    }
+                                }
+                                if !shared.iter().any(|x| x.address == entry.address) {
+                                    PillAction {
+                                        label: "加入共用",
+                                        onclick: {
+                                            let server = SharedServer {
+                                                name: entry.name.clone(),
+                                                address: entry.address.clone(),
+                                            };
+                                            move |()| {
+                                                let mut shared = SHARED_SERVERS.read().clone();
+                                                shared.push(server.clone());
+                                                error.set(Some(save_shared_servers(shared).map_err(Into::into)));
+                                            }
+                                        }
+                                    }
+                                }
+                                PillAction {
+                                    label: "移除",
+                                    onclick: move |()| edit(servers, onsave, index, |servers| {
+                                        servers.remove(index);
+                                    })
+                                }
+                            }
+                            Hint { css: "text-[15px] font-english", {status_hint(statuses.read().get(&entry.address))} }
+                        }
+                    }
+                }
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: rsx! {
+                    div {
+                        class: "flex items-center gap-[10px] w-full",
+                        input {
+                            class: "{INPUT} w-[260px]",
+                            placeholder: "名稱",
+                            value: new_name(),
+                            oninput: move |x| new_name.set(x.value())
+                        }
+                        input {
+                            class: "{INPUT} grow",
+                            placeholder: "位址，例如 play.example.com:25565",
+                            value: new_address(),
+                            oninput: move |x| new_address.set(x.value())
+                        }
+                        PillAction {
+                            label: "新增",
+                            onclick: move |()| {
+                                let address = new_address().trim().to_owned();
+                                if address.is_empty() {
+                                    return;
+                                }
+                                let name = match new_name().trim() {
+                                    "" => address.clone(),
+                                    x => x.to_owned(),
+                                };
+                                let mut next = servers();
+                                next.push(ServerEntry::new(name, address));
+                                onsave.call(next);
+                                new_name.set(String::new());
+                                new_address.set(String::new());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SharedServers(
+    collection_id: ReadOnlySignal<CollectionId>,
+    statuses: Statuses,
+    onsynced: EventHandler,
+) -> Element {
+    let mut error = use_error_handler();
+    let keys = use_context::<Memo<Vec<CollectionId>>>();
+    let mut targets: Signal<Vec<CollectionId>> = use_signal(|| vec![collection_id()]);
+    let mut synced = use_signal(|| None);
+    let mut new_name = use_signal(String::new);
+    let mut new_address = use_signal(String::new);
+    let shared = SHARED_SERVERS.read().clone();
+    let collections = keys()
+        .into_iter()
+        .map(|x| {
+            let name = x.get_collection_owned().display_name().to_string();
+            (x, name)
+        })
+        .collect::<Vec<_>>();
+    let sync_hint = synced().map_or_else(
+        || String::from("加入缺少的共用伺服器，並以共用列表的名稱更新相同位址的伺服器"),
+        |x: usize| format!("已同步到 {x} 個收藏"),
+    );
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("共用伺服器"),
+                                ContentType::hint("在所有收藏間共用的伺服器，例如團隊伺服器"),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                ]
+            }
+            for (index , server) in shared.into_iter().enumerate() {
+                Button {
+                    key: "{server.address}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[25px]",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![
+                                    ContentType::text(server.name.clone()),
+                                    ContentType::hint(
+                                        format!("{}•{}", server.address, status_hint(statuses.read().get(&server.address))),
+                                    ).css("font-english"),
+                                ],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[15px]"),
+                        ContentType::custom(rsx! {
+                            div {
+                                class: "flex gap-[10px]",
+                                PillAction {
+                                    label: "測試",
+                                    onclick: {
+                                        let address = server.address.clone();
+                                        move |()| start_ping(statuses, address.clone())
+                                    }
+                                }
+                                PillAction {
+                                    label: "移除",
+                                    onclick: move |()| {
+                                        let mut shared = SHARED_SERVERS.read().clone();
+                                        if index < shared.len() {
+                                            shared.remove(index);
+                                        }
+                                        error.set(Some(save_shared_servers(shared).map_err(Into::into)));
+                                    }
+                                }
+                            }
+                        }).align_right(),
+                    ]
+                }
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: rsx! {
+                    div {
+                        class: "flex items-center gap-[10px] w-full",
+                        input {
+                            class: "{INPUT} w-[260px]",
+                            placeholder: "名稱",
+                            value: new_name(),
+                            oninput: move |x| new_name.set(x.value())
+                        }
+                        input {
+                            class: "{INPUT} grow",
+                            placeholder: "位址",
+                            value: new_address(),
+                            oninput: move |x| new_address.set(x.value())
+                        }
+                        PillAction {
+                            label: "新增",
+                            onclick: move |()| {
+                                let address = new_address().trim().to_owned();
+                                if address.is_empty() {
+                                    return;
+                                }
+                                let name = match new_name().trim() {
+                                    "" => address.clone(),
+                                    x => x.to_owned(),
+                                };
+                                let mut shared = SHARED_SERVERS.read().clone();
+                                shared.retain(|x| x.address != address);
+                                shared.push(SharedServer { name, address });
+                                error.set(Some(save_shared_servers(shared).map_err(Into::into)));
+                                new_name.set(String::new());
+                                new_address.set(String::new());
+                            }
+                        }
+                    }
+                }
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: rsx! {
+                    div {
+                        class: "flex flex-col gap-[15px] w-full",
+                        div {
+                            class: "flex items-center justify-between gap-[15px]",
+                            div {
+                                class: "flex flex-col gap-[15px]",
+                                Text { "同步到收藏" }
+                                Hint { {sync_hint} }
+                            }
+                            PillAction {
+                                label: "同步",
+                                onclick: move |()| {
+                                    let mut count = 0;
+                                    let mut result = Ok(());
+                                    for target in targets() {
+                                        match sync_shared(&target.get_collection_owned().game_directory()) {
+                                            Ok(()) => count += 1,
+                                            Err(x) => result = Err(x),
+                                        }
+                                    }
+                                    if targets.read().contains(&collection_id()) {
+                                        onsynced.call(());
+                                    }
+                                    synced.set(Some(count));
+                                    error.set(Some(result.map_err(Into::into)));
+                                }
+                            }
+                        }
+                        div {
+                            class: "flex flex-wrap gap-[10px]",
+                            for (id , name) in collections {
+                                PillAction {
+                                    key: "{id}",
+                                    label: name,
+                                    selected: targets.read().contains(&id),
+                                    onclick: move |()| {
+                                        let mut targets = targets.write();
+                                        match targets.iter().position(|x| x == &id) {
+                                            Some(position) => {
+                                                targets.remove(position);
+                                            }
+                                            None => targets.push(id.clone()),
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
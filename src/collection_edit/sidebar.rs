@@ -4,6 +4,7 @@ use rust_lib::api::shared_resources::collection::CollectionId;
 use crate::{
     collection_display::{DISPLAY_BACKGROUND, UNDO},
     collection_edit::EditState,
+    collection_stats::status_hint,
     main_page::ARROW_LEFT,
     pages::Pages,
     text_scroller::use_text_scroller,
@@ -57,6 +58,28 @@ pub fn EditSidebar(collection_id: ReadOnlySignal<CollectionId>) -> Element {
                         ContentType::svg(ARROW_RIGHT).css("svg-[30px]").align_right(),
                     ]
                 }
+                Button {
+                    roundness: Roundness::None,
+                    extended_css_class: "bg-background",
+                    fill_mode: FillMode::Fit,
+                    focus_color_change: true,
+                    switcher: EditState::Options,
+                    string_placements: vec![
+                        ContentType::text("遊戲設定").align_left(),
+                        ContentType::svg(ARROW_RIGHT).css("svg-[30px]").align_right(),
+                    ]
+                }
+                Button {
+                    roundness: Roundness::None,
+                    extended_css_class: "bg-background",
+                    fill_mode: FillMode::Fit,
+                    focus_color_change: true,
+                    switcher: EditState::Servers,
+                    string_placements: vec![
+                        ContentType::text("伺服器列表").align_left(),
+                        ContentType::svg(ARROW_RIGHT).css("svg-[30px]").align_right(),
+                    ]
+                }
                 Button {
                     roundness: Roundness::None,
                     extended_css_class: "bg-background",
@@ -124,7 +147,7 @@ fn EditSidebarInfographic(collection_id: ReadOnlySignal<CollectionId>) -> Elemen
                                     .onmounted(onmounted)
                                     .style(dbg!(style()))
                                     .css("text-3xl font-black w-full group-hover:group-aria-selected:animate-scroll-left overflow-x-clip text-nowrap"),
-                                ContentType::hint("由我建立•18 分鐘•不久前開啟")
+                                ContentType::hint(status_hint(&collection_id()))
                                     .css("font-medium text-[15px]"),
                             ],
                             Alignment::Left,
@@ -0,0 +1,221 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    collection_edit::advanced::PillAction,
+    collection_stats::format_date,
+    upgrade::{
+        apply_in_place, apply_to_copy, plan_upgrade, reports_of, AppliedUpgrade, UpgradeEntry,
+        UpgradeOutcome, UpgradeReport, UPGRADE_REPORTS,
+    },
+    use_error_handler,
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+};
+
+#[component]
+pub fn VersionUpgrade(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    let mut target = use_signal(String::new);
+    let mut busy = use_signal(|| false);
+    let mut selected: Signal<Option<u64>> = use_signal(|| None);
+    // subscribe, so a new or applied report shows up right away
+    UPGRADE_REPORTS.read();
+    let reports = reports_of(&collection_id());
+    let current = collection_id()
+        .get_collection()
+        .read()
+        .minecraft_version()
+        .id
+        .clone();
+    let shown = selected()
+        .and_then(|created| reports.iter().find(|x| x.created == created))
+        .or_else(|| reports.last())
+        .cloned();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[25px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("版本升級助手"),
+                                ContentType::hint(
+                                    format!("檢查所有模組能否從 {current} 升級到指定的 Minecraft 版本"),
+                                ),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                    ContentType::custom(rsx! {
+                        div {
+                            class: "flex gap-[10px] items-center",
+                            input {
+                                class: "bg-background rounded-full px-[20px] py-[10px] w-[150px] text-white font-english",
+                                placeholder: "1.21",
+                                value: target(),
+                                oninput: move |x| target.set(x.value()),
+                            }
+                            PillAction {
+                                label: if busy() { "分析中…" } else { "分析" },
+                                onclick: move |()| {
+                                    if busy() || target.read().trim().is_empty() {
+                                        return;
+                                    }
+                                    busy.set(true);
+                                    spawn(async move {
+                                        let result = plan_upgrade(&collection_id(), &target()).await;
+                                        busy.set(false);
+                                        match result {
+                                            Ok(x) => selected.set(Some(x.created)),
+                                            Err(x) => error.set(Some(Err(x.into()))),
+                                        }
+                                    });
+                                }
+                            }
+                        }
+                    }).align_right(),
+                ]
+            }
+            if let Some(report) = shown {
+                UpgradeReportView { collection_id, report }
+            } else {
+                Button {
+                    roundness: Roundness::Bottom,
+                    clickable: false,
+                    extended_css_class: "p-[25px]",
+                    string_placements: vec![ContentType::hint("尚未分析過任何版本").align_left()]
+                }
+            }
+            if reports.len() > 1 {
+                Button {
+                    roundness: Roundness::Bottom,
+                    clickable: false,
+                    extended_css_class: "p-[25px]",
+                    string_placements: rsx! {
+                        div {
+                            class: "flex flex-wrap gap-[10px]",
+                            for report in reports.into_iter().rev() {
+                                PillAction {
+                                    key: "{report.created}",
+                                    label: format!("{} → {}•{}", report.from, report.to, format_date(report.created)),
+                                    onclick: move |()| selected.set(Some(report.created))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn UpgradeReportView(collection_id: ReadOnlySignal<CollectionId>, report: UpgradeReport) -> Element {
+    let mut error = use_error_handler();
+    let mut busy = use_signal(|| false);
+    let created = report.created;
+    let mut apply = move |in_place: bool| {
+        if busy() {
+            return;
+        }
+        busy.set(true);
+        spawn(async move {
+            let result = if in_place {
+                apply_in_place(&collection_id(), created).await
+            } else {
+                apply_to_copy(&collection_id(), created).await
+            };
+            busy.set(false);
+            error.set(Some(result.map_err(Into::into)));
+        });
+    };
+    let status = match &report.applied {
+        Some(AppliedUpgrade::InPlace { snapshot }) => {
+            format!("已直接套用，套用前的快照建立於 {}", format_date(*snapshot))
+        }
+        Some(AppliedUpgrade::Copy { display_name }) => format!("已套用到副本「{display_name}」"),
+        None => format!(
+            "{} 個可升級•{} 個已替換•{} 個無法取得",
+            report.upgradable().count(),
+            report.replaced().count(),
+            report.unavailable().count()
+        ),
+    };
+    let groups = [
+        ("可升級", report.upgradable().cloned().collect::<Vec<_>>()),
+        ("已替換", report.replaced().cloned().collect()),
+        ("無法取得", report.unavailable().cloned().collect()),
+    ];
+    rsx! {
+        Button {
+            roundness: Roundness::None,
+            clickable: false,
+            extended_css_class: "p-[20px]",
+            string_placements: vec![
+                Contents::new(
+                        vec![
+                            ContentType::text(format!("{} → {}", report.from, report.to))
+                                .css("text-xl font-english"),
+                            ContentType::hint(format!("{}•{status}", format_date(report.created)))
+                                .css("text-[13px]"),
+                        ],
+                        Alignment::Left,
+                    )
+                    .css("flex flex-col gap-[10px]"),
+                ContentType::custom(rsx! {
+                    if busy() {
+                        Hint { css: "text-[15px]", "套用中…" }
+                    } else if report.applied.is_none() {
+                        div {
+                            class: "flex gap-[10px]",
+                            PillAction { label: "套用到副本", onclick: move |()| apply(false) }
+                            PillAction { label: "建立快照後直接套用", onclick: move |()| apply(true) }
+                        }
+                    }
+                }).align_right(),
+            ]
+        }
+        for (title , entries) in groups {
+            if !entries.is_empty() {
+                Button {
+                    key: "{title}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[20px] bg-background",
+                    string_placements: rsx! {
+                        div {
+                            class: "flex flex-col gap-[10px]",
+                            Text { css: "text-[15px]", {format!("{title} ({})", entries.len())} }
+                            for entry in entries {
+                                Hint { css: "text-[15px] font-english", {describe(&entry)} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn describe(entry: &UpgradeEntry) -> String {
+    let current = entry.current_version.as_deref().unwrap_or("?");
+    match &entry.outcome {
+        UpgradeOutcome::Upgradable { version } => {
+            format!("{} {current} → {}", entry.name, version.version_number)
+        }
+        UpgradeOutcome::Replaced {
+            replacement,
+            version,
+        } => format!(
+            "{} → {replacement} {}",
+            entry.name, version.version_number
+        ),
+        UpgradeOutcome::Unavailable => format!("{} {current}", entry.name),
+    }
+}
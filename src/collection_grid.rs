@@ -0,0 +1,238 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use dioxus::prelude::*;
+use itertools::Itertools;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::{
+    collection_stats::stats_of,
+    loaders::loader_label,
+    persistence::{self, PersistenceError},
+};
+
+const GRID_SETTINGS_FILE: &str = "collection_grid";
+
+/// How the Collections grid is sorted, grouped and sized.
+pub static GRID_SETTINGS: GlobalSignal<GridSettings> =
+    GlobalSignal::new(|| persistence::load(GRID_SETTINGS_FILE));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter)]
+pub enum SortKey {
+    #[default]
+    Name,
+    LastPlayed,
+    Created,
+    Size,
+    MinecraftVersion,
+    Loader,
+}
+
+impl std::fmt::Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Name => "名稱",
+            Self::LastPlayed => "最近遊玩",
+            Self::Created => "建立時間",
+            Self::Size => "大小",
+            Self::MinecraftVersion => "遊戲版本",
+            Self::Loader => "模組載入器",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter)]
+pub enum Grouping {
+    #[default]
+    None,
+    MinecraftVersion,
+    Loader,
+}
+
+impl std::fmt::Display for Grouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "不分組",
+            Self::MinecraftVersion => "依遊戲版本",
+            Self::Loader => "依模組載入器",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter)]
+pub enum Density {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl Density {
+    /// Overrides the 280px square of `CollectionBlock`
+    pub const fn tile_class(self) -> &'static str {
+        match self {
+            Self::Small => "size-[200px] min-w-[200px] min-h-[200px]",
+            Self::Medium => "size-[280px] min-w-[280px] min-h-[280px]",
+            Self::Large => "size-[360px] min-w-[360px] min-h-[360px]",
+        }
+    }
+
+    pub const fn grid_class(self) -> &'static str {
+        match self {
+            Self::Small => "grid-cols-[repeat(auto-fill,200px)]",
+            Self::Medium => "grid-cols-[repeat(auto-fill,280px)]",
+            Self::Large => "grid-cols-[repeat(auto-fill,360px)]",
+        }
+    }
+}
+
+impl std::fmt::Display for Density {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Small => "小",
+            Self::Medium => "中",
+            Self::Large => "大",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct GridSettings {
+    pub sort: SortKey,
+    /// Flips the natural order of `sort`
+    pub reversed: bool,
+    pub group: Grouping,
+    pub density: Density,
+}
+
+pub fn save_grid_settings(settings: GridSettings) -> Result<(), PersistenceError> {
+    *GRID_SETTINGS.write() = settings;
+    persistence::save(GRID_SETTINGS_FILE, &*GRID_SETTINGS.read())
+}
+
+/// Size of everything under `path` in bytes, unreadable entries count as empty.
+pub fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|x| match x.file_type() {
+            Ok(kind) if kind.is_dir() => directory_size(&x.path()),
+            _ => x.metadata().map_or(0, |x| x.len()),
+        })
+        .sum()
+}
+
+/// `1.20.4` -> `[1, 20, 4]`, so versions compare numerically. Snapshots like `24w14a` compare
+/// by their leading digits.
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(['.', '-', 'w'])
+        .map(|x| {
+            x.chars()
+                .take_while(char::is_ascii_digit)
+                .collect::<String>()
+                .parse()
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn minecraft_version(collection_id: &CollectionId) -> String {
+    collection_id
+        .get_collection_owned()
+        .minecraft_version()
+        .id
+        .clone()
+}
+
+fn created_at(collection_id: &CollectionId) -> u64 {
+    let game_directory = collection_id.get_collection_owned().game_directory();
+    std::fs::metadata(game_directory)
+        .and_then(|x| x.created().or_else(|_| x.modified()))
+        .ok()
+        .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |x| x.as_secs())
+}
+
+/// What a collection is sorted by, read once per collection rather than on every comparison.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+    Text(Option<String>),
+    /// Recent, big and new first
+    Descending(Reverse<u64>),
+    Version(Reverse<Vec<u64>>),
+}
+
+/// The value for `sort`, then the lowercase name the rest is alphabetical by.
+fn sort_key(
+    sort: SortKey,
+    sizes: &BTreeMap<String, u64>,
+    collection_id: &CollectionId,
+) -> (SortValue, String) {
+    let name = collection_id
+        .get_collection_owned()
+        .display_name()
+        .to_lowercase();
+    let value = match sort {
+        SortKey::Name => SortValue::Text(Some(name.clone())),
+        SortKey::LastPlayed => SortValue::Descending(Reverse(
+            stats_of(collection_id).last_played.unwrap_or_default(),
+        )),
+        SortKey::Created => SortValue::Descending(Reverse(created_at(collection_id))),
+        SortKey::Size => SortValue::Descending(Reverse(
+            sizes
+                .get(&collection_id.to_string())
+                .copied()
+                .unwrap_or_default(),
+        )),
+        SortKey::MinecraftVersion => {
+            SortValue::Version(Reverse(version_key(&minecraft_version(collection_id))))
+        }
+        SortKey::Loader => SortValue::Text(loader_label(collection_id)),
+    };
+    (value, name)
+}
+
+fn group_label(group: Grouping, collection_id: &CollectionId) -> Option<String> {
+    match group {
+        Grouping::None => None,
+        Grouping::MinecraftVersion => Some(minecraft_version(collection_id)),
+        Grouping::Loader => {
+            Some(loader_label(collection_id).unwrap_or_else(|| String::from("原版")))
+        }
+    }
+}
+
+/// Sorts the collections and splits them into titled groups, a single untitled group when
+/// grouping is off. `sizes` is only consulted when sorting by size.
+pub fn arrange(
+    keys: Vec<CollectionId>,
+    settings: GridSettings,
+    sizes: &BTreeMap<String, u64>,
+) -> Vec<(Option<String>, Vec<CollectionId>)> {
+    let mut sorted = keys;
+    sorted.sort_by_cached_key(|x| sort_key(settings.sort, sizes, x));
+    if settings.reversed {
+        sorted.reverse();
+    }
+    if settings.group == Grouping::None {
+        return vec![(None, sorted)];
+    }
+    let mut groups = sorted
+        .into_iter()
+        .into_group_map_by(|x| group_label(settings.group, x))
+        .into_iter()
+        .collect::<Vec<_>>();
+    groups.sort_by(|a, b| match settings.group {
+        Grouping::MinecraftVersion => version_key(b.0.as_deref().unwrap_or_default())
+            .cmp(&version_key(a.0.as_deref().unwrap_or_default())),
+        _ => a.0.cmp(&b.0),
+    });
+    groups
+}
@@ -0,0 +1,129 @@
+use dioxus::prelude::*;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{loaders::loader_label, local_mods::local_mods_of};
+
+/// What `SearchBar` holds before the user types anything.
+pub const SEARCH_PLACEHOLDER: &str = "搜尋";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchField {
+    Version,
+    Loader,
+    Mod,
+}
+
+impl std::fmt::Display for MatchField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Version => "遊戲版本",
+            Self::Loader => "模組載入器",
+            Self::Mod => "模組",
+        })
+    }
+}
+
+/// Why a collection matched a search, with the char indices to highlight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionMatch {
+    pub score: i64,
+    /// Matched chars of the display name, empty when only something else matched
+    pub name_indices: Vec<usize>,
+    /// The best match outside the name
+    pub detail: Option<(MatchField, String, Vec<usize>)>,
+}
+
+/// `None` for the placeholder and blank input, which show every collection.
+pub fn active_query(input: &str) -> Option<&str> {
+    let input = input.trim();
+    (input != SEARCH_PLACEHOLDER && !input.is_empty()).then_some(input)
+}
+
+/// Case-insensitive substring match, as char indices. Fuzzy matching is too loose for the
+/// dozens of mod names in a collection, `iris` would find every mod with an i, r, i and s.
+fn find_substring(text: &str, query: &str) -> Option<(i64, Vec<usize>)> {
+    let lower = |x: &str| {
+        x.chars()
+            .map(|x| x.to_lowercase().next().unwrap_or(x))
+            .collect::<Vec<_>>()
+    };
+    let (text, query) = (lower(text), lower(query));
+    let start = text
+        .windows(query.len())
+        .position(|window| window == query.as_slice())?;
+    // earlier and tighter matches rank higher
+    let score = (query.len() * 100) as i64 - start as i64 - (text.len() - query.len()) as i64;
+    Some((score, (start..start + query.len()).collect()))
+}
+
+pub fn search_collection(
+    matcher: &SkimMatcherV2,
+    collection_id: &CollectionId,
+    query: &str,
+) -> Option<CollectionMatch> {
+    let collection = collection_id.get_collection_owned();
+    let name = matcher.fuzzy_indices(collection.display_name(), query);
+    let mut candidates = vec![
+        (MatchField::Version, collection.minecraft_version().id.clone()),
+    ];
+    if let Some(loader) = loader_label(collection_id) {
+        candidates.push((MatchField::Loader, loader));
+    }
+    if let Some(controller) = collection.mod_controller() {
+        candidates.extend(
+            controller
+                .manager
+                .mods
+                .iter()
+                .map(|x| (MatchField::Mod, x.name.clone())),
+        );
+    }
+    candidates.extend(
+        local_mods_of(collection_id)
+            .into_iter()
+            .map(|x| (MatchField::Mod, x.metadata.name)),
+    );
+    let detail = candidates
+        .into_iter()
+        .filter_map(|(field, text)| {
+            let (score, indices) = find_substring(&text, query)?;
+            Some((score, (field, text, indices)))
+        })
+        .max_by_key(|x| x.0);
+    match (name, detail) {
+        (None, None) => None,
+        (Some((score, name_indices)), detail) => Some(CollectionMatch {
+            score,
+            name_indices,
+            detail: detail.map(|x| x.1),
+        }),
+        (None, Some((score, detail))) => Some(CollectionMatch {
+            score,
+            name_indices: Vec::new(),
+            detail: Some(detail),
+        }),
+    }
+}
+
+/// `text` with the chars at `indices` highlighted.
+#[component]
+pub fn Highlighted(text: String, indices: Vec<usize>) -> Element {
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for (index, c) in text.chars().enumerate() {
+        let matched = indices.contains(&index);
+        match runs.last_mut() {
+            Some((last, run)) if *last == matched => run.push(c),
+            _ => runs.push((matched, c.to_string())),
+        }
+    }
+    rsx! {
+        for (index , (matched , run)) in runs.into_iter().enumerate() {
+            span {
+                key: "{index}",
+                class: if matched { "text-green" } else { "" },
+                {run}
+            }
+        }
+    }
+}
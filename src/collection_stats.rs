@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::{self, PersistenceError};
+
+const STATS_FILE: &str = "collection_stats";
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// Launch statistics of every collection, keyed by `CollectionId::to_string`.
+pub static COLLECTION_STATS: GlobalSignal<BTreeMap<String, CollectionStats>> =
+    GlobalSignal::new(|| persistence::load(STATS_FILE));
+
+/// Collections that currently have a running game.
+pub static RUNNING: GlobalSignal<BTreeSet<String>> = GlobalSignal::new(BTreeSet::new);
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CollectionStats {
+    pub launches: u32,
+    pub crashes: u32,
+    /// Seconds
+    pub total_playtime: u64,
+    /// Seconds
+    pub last_session: u64,
+    /// Unix timestamp in seconds
+    pub last_played: Option<u64>,
+    /// Seconds played, keyed by days since the unix epoch
+    pub daily_playtime: BTreeMap<u64, u64>,
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+pub fn stats_of(collection_id: &CollectionId) -> CollectionStats {
+    COLLECTION_STATS
+        .read()
+        .get(&collection_id.to_string())
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn mark_running(collection_id: &CollectionId) {
+    RUNNING.write().insert(collection_id.to_string());
+}
+
+/// Records a finished game session and persists it.
+pub fn record_session(
+    collection_id: &CollectionId,
+    started: SystemTime,
+    crashed: bool,
+) -> Result<(), PersistenceError> {
+    let key = collection_id.to_string();
+    RUNNING.write().remove(&key);
+    let played = started.elapsed().unwrap_or_default().as_secs();
+    let now = unix_now();
+    let mut stats = COLLECTION_STATS.write();
+    let entry = stats.entry(key).or_default();
+    entry.launches += 1;
+    entry.crashes += u32::from(crashed);
+    entry.total_playtime += played;
+    entry.last_session = played;
+    entry.last_played = Some(now);
+    // split the session across the days it spans, so a session past midnight is not lumped
+    let mut cursor = now.saturating_sub(played);
+    while cursor < now {
+        let day = cursor / SECONDS_PER_DAY;
+        let day_end = ((day + 1) * SECONDS_PER_DAY).min(now);
+        *entry.daily_playtime.entry(day).or_default() += day_end - cursor;
+        cursor = day_end;
+    }
+    persistence::save(STATS_FILE, &*stats)
+}
+
+impl CollectionStats {
+    /// Seconds played per day for the last `days` days, oldest first.
+    pub fn recent_days(&self, days: u64) -> Vec<(u64, u64)> {
+        let today = unix_now() / SECONDS_PER_DAY;
+        (0..days)
+            .rev()
+            .map(|x| {
+                let day = today.saturating_sub(x);
+                (day, self.daily_playtime.get(&day).copied().unwrap_or_default())
+            })
+            .collect()
+    }
+}
+
+/// `3 小時 5 分鐘`, `18 分鐘`
+pub fn format_playtime(seconds: u64) -> String {
+    let duration = Duration::from_secs(seconds);
+    let hours = duration.as_secs() / 3600;
+    let minutes = duration.as_secs() % 3600 / 60;
+    match (hours, minutes) {
+        (0, 0) => String::from("不到 1 分鐘"),
+        (0, m) => format!("{m} 分鐘"),
+        (h, 0) => format!("{h} 小時"),
+        (h, m) => format!("{h} 小時 {m} 分鐘"),
+    }
+}
+
+/// `不久前`, `5 小時前`, `3 天前`
+pub fn format_relative(timestamp: u64) -> String {
+    let elapsed = unix_now().saturating_sub(timestamp);
+    match elapsed {
+        x if x < 60 * 10 => String::from("不久前"),
+        x if x < 60 * 60 => format!("{} 分鐘前", x / 60),
+        x if x < SECONDS_PER_DAY => format!("{} 小時前", x / 3600),
+        x => format!("{} 天前", x / SECONDS_PER_DAY),
+    }
+}
+
+/// `2024-08-12 14:03` in UTC
+pub fn format_date(timestamp: u64) -> String {
+    // Howard Hinnant's `civil_from_days`
+    let days = (timestamp / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let seconds = timestamp % SECONDS_PER_DAY;
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+/// The short status line shown under a collection's name, e.g. `遊玩中•3 小時` or
+/// `5 天前遊玩•18 分鐘`.
+pub fn status_hint(collection_id: &CollectionId) -> String {
+    let stats = stats_of(collection_id);
+    let status = if RUNNING.read().contains(&collection_id.to_string()) {
+        String::from("遊玩中")
+    } else if let Some(last_played) = stats.last_played {
+        format!("{}遊玩", format_relative(last_played))
+    } else {
+        String::from("尚未遊玩")
+    };
+    if stats.total_playtime == 0 {
+        status
+    } else {
+        format!("{status}•{}", format_playtime(stats.total_playtime))
+    }
+}
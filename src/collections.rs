@@ -1,12 +1,29 @@
+use std::collections::BTreeMap;
+
 use dioxus::prelude::*;
+use fuzzy_matcher::skim::SkimMatcherV2;
 use rust_lib::api::shared_resources::collection::CollectionId;
 
+use strum::IntoEnumIterator;
+
 use crate::{
+    collection_edit::advanced::PillAction,
+    collection_grid::{
+        arrange, directory_size, save_grid_settings, Density, GridSettings, Grouping, SortKey,
+        GRID_SETTINGS,
+    },
+    collection_search::{active_query, search_collection, CollectionMatch, Highlighted},
+    favorites::is_favorite,
+    folders::{
+        add_to_folder, create_folder, delete_folder, folder, in_folder, recolor_folder,
+        remove_from_folder, rename_folder, Folder, FolderColor, FOLDERS,
+    },
     main_page::{CollectionBlock, STAR},
+    use_error_handler,
     BaseComponents::{
         atoms::button::{Button, FillMode, Roundness, Size},
         molecules::search_bar::SearchBar,
-        string_placements::{Alignment, ContentType, Contents},
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
     },
     EXPLORE,
 };
@@ -19,10 +36,73 @@ pub static ARROW_DOWN: &str = manganis::mg!("./public/arrow_drop_down.svg");
 pub static BOOKMARK: &str = manganis::mg!("./public/bookmark.svg");
 pub static BOOKMARK_ADD: &str = manganis::mg!("./public/bookmark_add.svg");
 
+/// The dropdowns of the `FILTER_LIST` and `CROP_FREE` buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GridMenu {
+    SortAndGroup,
+    Density,
+}
+
 #[component]
 pub fn Collections() -> Element {
     let keys = use_context::<Memo<Vec<CollectionId>>>();
     let sender = use_signal(String::new);
+    let mut favorites_only = use_signal(|| false);
+    // `None` is 全部, otherwise the id of the opened folder
+    let mut view: Signal<Option<u64>> = use_signal(|| None);
+    let mut show_folders = use_signal(|| false);
+    let dragging: Signal<Option<CollectionId>> = use_signal(|| None);
+    let mut error = use_error_handler();
+    let members = view().and_then(folder).map(|x| x.collections);
+    let search = sender();
+    let query = active_query(&search);
+    let matcher = SkimMatcherV2::default();
+    let mut found = BTreeMap::new();
+    let shown = keys()
+        .into_iter()
+        .filter(|x| !favorites_only() || is_favorite(x))
+        .filter(|x| {
+            members
+                .as_ref()
+                .map_or(true, |members| members.contains(&x.to_string()))
+        })
+        .filter(|x| {
+            let Some(query) = query else {
+                return true;
+            };
+            search_collection(&matcher, x, query)
+                .map(|hit| found.insert(x.to_string(), hit))
+                .is_some()
+        })
+        .collect::<Vec<_>>();
+    let mut menu: Signal<Option<GridMenu>> = use_signal(|| None);
+    let settings = GRID_SETTINGS();
+    // sizes take a walk over every game directory, only done when sorting by them
+    let sizes = use_resource(move || async move {
+        if GRID_SETTINGS().sort != SortKey::Size {
+            return BTreeMap::new();
+        }
+        let directories = keys()
+            .iter()
+            .map(|x| (x.to_string(), x.get_collection_owned().game_directory()))
+            .collect::<Vec<_>>();
+        tokio::task::spawn_blocking(move || {
+            directories
+                .into_iter()
+                .map(|(id, directory)| (id, directory_size(&directory)))
+                .collect()
+        })
+        .await
+        .unwrap_or_default()
+    });
+    let sizes = sizes.read().clone().unwrap_or_default();
+    let groups = arrange(shown, settings, &sizes);
+    let grid_class = settings.density.grid_class();
+    let tile_class = settings.density.tile_class();
+    let mut toggle_menu = move |x: GridMenu| {
+        let next = (menu() != Some(x)).then_some(x);
+        menu.set(next);
+    };
     rsx! {
         div {
             class: "flex flex-col space-y-[10px]",
@@ -38,14 +118,16 @@ pub fn Collections() -> Element {
                             ContentType::svg(EXPLORE).css("svg-[30px]").align_left(),
                             ContentType::text("全部").align_right(),
                         ],
-                        extended_css_class: "pl-[20px] pr-[25px] h-full"
+                        extended_css_class: if view().is_none() { "bg-white text-black pl-[20px] pr-[25px] h-full" } else { "pl-[20px] pr-[25px] h-full" },
+                        onclick: move |()| view.set(None)
                     }
                     Button {
                         roundness: Roundness::Pill,
                         fill_mode: FillMode::Fit,
                         size: Size::Medium,
                         string_placements: vec![ContentType::svg(STAR).css("svg-[30px]").align_center()],
-                        extended_css_class: "px-[20px] h-full"
+                        extended_css_class: if favorites_only() { "bg-lime-300 text-black px-[20px] h-full" } else { "px-[20px] h-full" },
+                        onclick: move |()| favorites_only.toggle()
                     }
                     Button {
                         roundness: Roundness::Pill,
@@ -55,14 +137,24 @@ pub fn Collections() -> Element {
                             ContentType::svg(BOOKMARK).css("svg-[30px]").align_left(),
                             ContentType::text("分類夾").align_right(),
                         ],
-                        extended_css_class: "px-[20px] h-full my-0"
+                        extended_css_class: if show_folders() { "bg-white text-black px-[20px] h-full my-0" } else { "px-[20px] h-full my-0" },
+                        onclick: move |()| show_folders.toggle()
                     }
                     Button {
                         roundness: Roundness::Pill,
                         fill_mode: FillMode::Fit,
                         size: Size::Medium,
                         string_placements: vec![ContentType::svg(BOOKMARK_ADD).css("svg-[30px]").align_center()],
-                        extended_css_class: "px-[25px] h-full"
+                        extended_css_class: "px-[25px] h-full",
+                        onclick: move |()| {
+                            match create_folder() {
+                                Ok(id) => {
+                                    view.set(Some(id));
+                                    show_folders.set(true);
+                                }
+                                Err(x) => error.set(Some(Err(x.into()))),
+                            }
+                        }
                     }
                 }
                 div {
@@ -81,7 +173,8 @@ pub fn Collections() -> Element {
                                 )
                                 .css("gap-[5px]"),
                         ],
-                        extended_css_class: "pl-[20px] pr-[10px] h-full"
+                        extended_css_class: "pl-[20px] pr-[10px] h-full",
+                        onclick: move |()| toggle_menu(GridMenu::SortAndGroup)
                     }
                     Button {
                         roundness: Roundness::Pill,
@@ -97,7 +190,8 @@ pub fn Collections() -> Element {
                                 )
                                 .css("gap-[5px]"),
                         ],
-                        extended_css_class: "pl-[20px] pr-[10px] h-full"
+                        extended_css_class: "pl-[20px] pr-[10px] h-full",
+                        onclick: move |()| toggle_menu(GridMenu::Density)
                     }
                     Button {
                         roundness: Roundness::Pill,
@@ -111,13 +205,56 @@ pub fn Collections() -> Element {
                     }
                 }
             }
-            div {
-                class: "grid grid-flow-row grid-cols-[repeat(auto-fill,280px)] gap-[20px]",
-                for collection_id in keys() {
-                    CollectionBlock {
-                        key: "{collection_id}",
-                        collection_id,
-                        extended_class: "rounded-[20px]"
+            if let Some(x) = menu() {
+                GridMenuPanel { menu: x, settings }
+            }
+            if show_folders() {
+                div {
+                    class: "flex flex-wrap gap-[10px]",
+                    if FOLDERS.read().is_empty() {
+                        Hint { css: "text-[15px]", "還沒有分類夾，按下右邊的按鈕建立，再把收藏拖進去" }
+                    }
+                    for x in FOLDERS() {
+                        FolderPill {
+                            key: "{x.id}",
+                            folder: x,
+                            view,
+                            dragging
+                        }
+                    }
+                }
+            }
+            if let Some(x) = view().and_then(folder) {
+                FolderEditor {
+                    key: "{x.id}",
+                    folder: x,
+                    view
+                }
+            }
+            if let Some(query) = query.filter(|_| groups.iter().all(|x| x.1.is_empty())) {
+                Hint {
+                    css: "px-[20px] py-[30px] text-[18px]",
+                    "沒有符合「{query}」的收藏，也可以搜尋遊戲版本、模組載入器或模組名稱"
+                }
+            }
+            for (index , (label , ids)) in groups.into_iter().enumerate() {
+                div {
+                    key: "{index}",
+                    class: "flex flex-col gap-[10px]",
+                    if let Some(label) = label {
+                        Text { css: "text-white text-[20px] font-english pt-[10px]", {label} }
+                    }
+                    div {
+                        class: "grid grid-flow-row gap-[20px] {grid_class}",
+                        for collection_id in ids {
+                            CollectionTile {
+                                key: "{collection_id}",
+                                found: found.get(&collection_id.to_string()).cloned(),
+                                collection_id,
+                                dragging,
+                                tile_class
+                            }
+                        }
                     }
                 }
             }
@@ -145,3 +282,243 @@ pub fn Collections() -> Element {
         }
     }
 }
+
+fn update_settings(update: impl FnOnce(&mut GridSettings)) -> anyhow::Result<()> {
+    let mut settings = GRID_SETTINGS();
+    update(&mut settings);
+    Ok(save_grid_settings(settings)?)
+}
+
+#[component]
+fn GridMenuPanel(menu: GridMenu, settings: GridSettings) -> Element {
+    let mut error = use_error_handler();
+    let row = "flex flex-wrap items-center gap-[10px]";
+    rsx! {
+        div {
+            class: "flex flex-col gap-[15px] rounded-[20px] bg-deep-background px-[20px] py-[15px]",
+            if menu == GridMenu::SortAndGroup {
+                div {
+                    class: row,
+                    Text { css: "text-white text-[15px] w-[80px]", "排序" }
+                    for sort in SortKey::iter() {
+                        PillAction {
+                            key: "{sort:?}",
+                            label: sort.to_string(),
+                            selected: settings.sort == sort,
+                            onclick: move |()| error.set(Some(update_settings(|x| x.sort = sort)))
+                        }
+                    }
+                    PillAction {
+                        label: "反向",
+                        selected: settings.reversed,
+                        onclick: move |()| error.set(Some(update_settings(|x| x.reversed = !x.reversed)))
+                    }
+                }
+                div {
+                    class: row,
+                    Text { css: "text-white text-[15px] w-[80px]", "分組" }
+                    for group in Grouping::iter() {
+                        PillAction {
+                            key: "{group:?}",
+                            label: group.to_string(),
+                            selected: settings.group == group,
+                            onclick: move |()| error.set(Some(update_settings(|x| x.group = group)))
+                        }
+                    }
+                }
+            } else {
+                div {
+                    class: row,
+                    Text { css: "text-white text-[15px] w-[80px]", "顯示大小" }
+                    for density in Density::iter() {
+                        PillAction {
+                            key: "{density:?}",
+                            label: density.to_string(),
+                            selected: settings.density == density,
+                            onclick: move |()| error.set(Some(update_settings(|x| x.density = density)))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A grid entry that can be dragged onto a folder, right-click opens the folder menu.
+#[component]
+fn CollectionTile(
+    collection_id: ReadOnlySignal<CollectionId>,
+    mut dragging: Signal<Option<CollectionId>>,
+    tile_class: &'static str,
+    found: Option<CollectionMatch>,
+) -> Element {
+    let mut menu = use_signal(|| false);
+    let highlight = found
+        .as_ref()
+        .map(|x| x.name_indices.clone())
+        .unwrap_or_default();
+    let detail = found.and_then(|x| x.detail);
+    rsx! {
+        div {
+            class: "relative",
+            draggable: "true",
+            ondragstart: move |_| dragging.set(Some(collection_id())),
+            ondragend: move |_| dragging.set(None),
+            oncontextmenu: move |evt| {
+                evt.prevent_default();
+                menu.toggle();
+            },
+            onmouseleave: move |_| menu.set(false),
+            CollectionBlock {
+                collection_id,
+                extended_class: "rounded-[20px] {tile_class}",
+                highlight
+            }
+            if let Some((field, text, indices)) = detail {
+                div {
+                    class: "absolute top-[15px] left-[15px] rounded-full bg-deep-background px-[15px] py-[5px] text-[13px] text-hint font-english",
+                    "{field}："
+                    Highlighted { text, indices }
+                }
+            }
+            if menu() {
+                FolderMenu { collection_id }
+            }
+        }
+    }
+}
+
+#[component]
+fn FolderMenu(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut error = use_error_handler();
+    rsx! {
+        div {
+            class: "absolute top-[15px] left-[15px] z-10 flex flex-col gap-[3px] min-w-[200px]",
+            if FOLDERS.read().is_empty() {
+                Button {
+                    roundness: Roundness::Squircle,
+                    clickable: false,
+                    extended_css_class: "bg-deep-background px-[20px] py-[15px]",
+                    string_placements: vec![ContentType::hint("還沒有分類夾").align_left()]
+                }
+            }
+            for x in FOLDERS() {
+                Button {
+                    key: "{x.id}",
+                    roundness: Roundness::Squircle,
+                    extended_css_class: "bg-deep-background px-[20px] py-[15px]",
+                    onclick: move |()| {
+                        let result = if in_folder(x.id, &collection_id()) {
+                            remove_from_folder(x.id, &collection_id())
+                        } else {
+                            add_to_folder(x.id, &collection_id())
+                        };
+                        error.set(Some(result.map_err(Into::into)));
+                    },
+                    string_placements: vec![
+                        ContentType::custom(rsx! {
+                            FolderDot { color: x.color }
+                        })
+                        .align_left(),
+                        ContentType::text(if in_folder(x.id, &collection_id()) {
+                                format!("✓ {}", x.name)
+                            } else {
+                                x.name.clone()
+                            })
+                            .align_right(),
+                    ]
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn FolderDot(color: FolderColor) -> Element {
+    rsx! {
+        div {
+            class: "size-[15px] rounded-full",
+            background: color.hex()
+        }
+    }
+}
+
+/// Opens the folder on click and takes the collection being dragged when dropped on.
+#[component]
+fn FolderPill(
+    folder: Folder,
+    mut view: Signal<Option<u64>>,
+    dragging: Signal<Option<CollectionId>>,
+) -> Element {
+    let mut error = use_error_handler();
+    let id = folder.id;
+    rsx! {
+        div {
+            ondragover: move |evt| evt.prevent_default(),
+            ondrop: move |evt| {
+                evt.prevent_default();
+                if let Some(collection_id) = dragging() {
+                    error.set(Some(add_to_folder(id, &collection_id).map_err(Into::into)));
+                }
+            },
+            Button {
+                roundness: Roundness::Pill,
+                fill_mode: FillMode::Fit,
+                extended_css_class: if view() == Some(id) { "bg-white text-black px-[20px] py-[10px]" } else { "px-[20px] py-[10px]" },
+                onclick: move |()| view.set(Some(id)),
+                string_placements: vec![
+                    ContentType::custom(rsx! {
+                        FolderDot { color: folder.color }
+                    })
+                    .align_left(),
+                    ContentType::text(format!("{} ({})", folder.name, folder.collections.len()))
+                        .align_right(),
+                ]
+            }
+        }
+    }
+}
+
+#[component]
+fn FolderEditor(folder: Folder, mut view: Signal<Option<u64>>) -> Element {
+    let mut error = use_error_handler();
+    let id = folder.id;
+    rsx! {
+        div {
+            class: "flex items-center gap-[15px] rounded-[20px] bg-deep-background px-[20px] py-[15px]",
+            FolderDot { color: folder.color }
+            input {
+                class: "grow bg-transparent text-white text-[20px] outline-none",
+                value: folder.name.clone(),
+                oninput: move |evt| {
+                    error.set(Some(rename_folder(id, evt.value()).map_err(Into::into)));
+                }
+            }
+            Text { css: "text-hint text-[15px]", {format!("{} 個收藏", folder.collections.len())} }
+            div {
+                class: "flex gap-[8px]",
+                for color in FolderColor::iter() {
+                    div {
+                        key: "{color:?}",
+                        role: "button",
+                        class: if color == folder.color { "size-[25px] rounded-full border-2 border-white" } else { "size-[25px] rounded-full" },
+                        background: color.hex(),
+                        onclick: move |_| {
+                            error.set(Some(recolor_folder(id, color).map_err(Into::into)));
+                        }
+                    }
+                }
+            }
+            Button {
+                roundness: Roundness::Pill,
+                fill_mode: FillMode::Fit,
+                extended_css_class: "bg-background hover:bg-red px-[20px] py-[10px]",
+                onclick: move |()| {
+                    view.set(None);
+                    error.set(Some(delete_folder(id).map_err(Into::into)));
+                },
+                string_placements: vec![ContentType::text("刪除分類夾").align_center()]
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use serde::Deserialize;
+
+use crate::{
+    jar_metadata::{DependencyKind, JarMetadata},
+    launch_options::take_quick_play,
+    mod_index::{mods_dir, normalize, read_metadata},
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        organisms::modal::Modal,
+        string_placements::{Alignment, ContentType, Contents, Hint},
+    },
+    ErrorFormatted,
+};
+
+const RULES: &str = include_str!("conflict_rules.toml");
+/// Mod changes within this window of each other are checked once, after the last of them.
+const CHECK_DELAY: Duration = Duration::from_millis(500);
+
+/// The latest requested check of every collection, a scheduled check that is no longer the
+/// latest one gives way to it.
+static SCHEDULED: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// The latest check of every collection, keyed by `CollectionId::to_string`.
+pub static CONFLICTS: GlobalSignal<BTreeMap<String, Vec<ConflictIssue>>> =
+    GlobalSignal::new(BTreeMap::new);
+
+/// A launch that was held back by blocking issues, shown by [`PreLaunchModal`].
+pub static PRE_LAUNCH_BLOCK: GlobalSignal<Option<(CollectionId, Vec<ConflictIssue>)>> =
+    GlobalSignal::new(|| None);
+
+/// Set by [`PreLaunchModal`] when the user launches anyway, picked up by the collection display.
+pub static FORCE_LAUNCH: GlobalSignal<Option<CollectionId>> = GlobalSignal::new(|| None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Blocking,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictIssue {
+    pub severity: Severity,
+    /// Names of the mods involved
+    pub mods: Vec<String>,
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+struct Rules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Deserialize)]
+struct Rule {
+    mods: Vec<String>,
+    severity: Severity,
+    reason: String,
+}
+
+fn bundled_rules() -> Vec<Rule> {
+    match toml::from_str::<Rules>(RULES) {
+        Ok(x) => x.rules,
+        Err(x) => {
+            error!("bundled conflict rules are invalid: {x}");
+            Vec::new()
+        }
+    }
+}
+
+/// Checks installed jars for duplicate mod ids, declared incompatibilities and the bundled
+/// rules.
+pub fn find_conflicts(jars: &[(String, JarMetadata)]) -> Vec<ConflictIssue> {
+    let mut issues = Vec::new();
+    let mut by_id: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (file_name, metadata) in jars {
+        by_id.entry(&metadata.id).or_default().push(file_name);
+    }
+    for (id, files) in by_id.iter().filter(|x| x.1.len() > 1) {
+        issues.push(ConflictIssue {
+            severity: Severity::Blocking,
+            mods: files.iter().map(|x| (*x).to_owned()).collect(),
+            reason: format!("{id} 安裝了 {} 份", files.len()),
+        });
+    }
+    let find = |identifier: &str| {
+        let identifier = normalize(identifier);
+        jars.iter()
+            .map(|x| &x.1)
+            .find(|x| normalize(&x.id) == identifier || normalize(&x.name) == identifier)
+    };
+    let mut declared: Vec<(&str, &str)> = Vec::new();
+    for (_, metadata) in jars {
+        for dependency in &metadata.dependencies {
+            let severity = match dependency.kind {
+                DependencyKind::Incompatible => Severity::Blocking,
+                DependencyKind::Discouraged => Severity::Warning,
+                _ => continue,
+            };
+            // two mods that break each other are one issue
+            if declared.contains(&(dependency.id.as_str(), metadata.id.as_str())) {
+                continue;
+            }
+            let Some(other) = jars.iter().map(|x| &x.1).find(|x| x.id == dependency.id) else {
+                continue;
+            };
+            let (severity, reason) = match dependency.matches_version(other.version.as_deref()) {
+                Some(false) => continue,
+                Some(true) => (
+                    severity,
+                    format!("{} 宣告與 {} 不相容", metadata.name, other.name),
+                ),
+                // a range that can't be checked against the installed version only warns
+                None => (
+                    Severity::Warning,
+                    format!(
+                        "{} 宣告與 {} {} 不相容，無法確認已安裝的版本是否在內",
+                        metadata.name,
+                        other.name,
+                        dependency.versions.join(" 或 ")
+                    ),
+                ),
+            };
+            declared.push((&metadata.id, &dependency.id));
+            issues.push(ConflictIssue {
+                severity,
+                mods: vec![metadata.name.clone(), other.name.clone()],
+                reason,
+            });
+        }
+    }
+    for rule in bundled_rules() {
+        let matched = rule.mods.iter().map(|x| find(x)).collect::<Option<Vec<_>>>();
+        if let Some(matched) = matched {
+            issues.push(ConflictIssue {
+                severity: rule.severity,
+                mods: matched.into_iter().map(|x| x.name.clone()).collect(),
+                reason: rule.reason,
+            });
+        }
+    }
+    issues.sort_by(|a, b| b.severity.cmp(&a.severity));
+    issues
+}
+
+/// Runs the check on a blocking thread and publishes it to [`CONFLICTS`].
+async fn refresh_conflicts(collection_id: &CollectionId) -> Vec<ConflictIssue> {
+    let mods_dir = mods_dir(collection_id);
+    let check = move || read_metadata(&mods_dir).map(|x| find_conflicts(&x));
+    let issues = match tokio::task::spawn_blocking(check).await {
+        Ok(Ok(x)) => x,
+        Ok(Err(x)) => {
+            error!("{}", x.to_formatted());
+            Vec::new()
+        }
+        Err(x) => {
+            error!("The conflict check panicked: {x}");
+            Vec::new()
+        }
+    };
+    CONFLICTS
+        .write()
+        .insert(collection_id.to_string(), issues.clone());
+    issues
+}
+
+/// Re-checks the collection after its mods changed. Changes in quick succession, like a bulk
+/// action, are checked once.
+pub fn schedule_conflict_check(collection_id: CollectionId) {
+    let generation = {
+        let mut scheduled = SCHEDULED.lock().unwrap_or_else(PoisonError::into_inner);
+        let generation = scheduled.entry(collection_id.to_string()).or_default();
+        *generation += 1;
+        *generation
+    };
+    spawn(async move {
+        tokio::time::sleep(CHECK_DELAY).await;
+        let latest = SCHEDULED
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&collection_id.to_string())
+            .copied();
+        if latest == Some(generation) {
+            refresh_conflicts(&collection_id).await;
+        }
+    });
+}
+
+/// The blocking issues that should stop a launch, if any, checked right now.
+pub async fn blocking_conflicts(collection_id: &CollectionId) -> Option<Vec<ConflictIssue>> {
+    let blocking = refresh_conflicts(collection_id)
+        .await
+        .into_iter()
+        .filter(|x| x.severity == Severity::Blocking)
+        .collect::<Vec<_>>();
+    (!blocking.is_empty()).then_some(blocking)
+}
+
+fn issue_line(issue: &ConflictIssue) -> String {
+    format!("{}：{}", issue.mods.join("、"), issue.reason)
+}
+
+/// Warnings shown above the mod list.
+#[component]
+pub fn ConflictBanner(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let issues = CONFLICTS
+        .read()
+        .get(&collection_id().to_string())
+        .cloned()
+        .unwrap_or_default();
+    rsx! {
+        for (index , issue) in issues.into_iter().enumerate() {
+            Hint {
+                key: "{index}",
+                css: if issue.severity == Severity::Blocking {
+                    "px-[20px] py-[10px] text-[15px] font-english text-red-400"
+                } else {
+                    "px-[20px] py-[10px] text-[15px] font-english text-yellow-300"
+                },
+                {issue_line(&issue)}
+            }
+        }
+    }
+}
+
+#[component]
+pub fn PreLaunchModal() -> Element {
+    let mut active = use_signal(|| false);
+    use_effect(move || {
+        if PRE_LAUNCH_BLOCK.read().is_some() {
+            active.set(true);
+        }
+    });
+    rsx! {
+        Modal {
+            active,
+            name: "pre_launch_modal",
+            PreLaunchView {
+                active
+            }
+        }
+    }
+}
+
+#[component]
+fn PreLaunchView(active: Signal<bool>) -> Element {
+    let Some((collection_id, issues)) = PRE_LAUNCH_BLOCK() else {
+        return rsx!();
+    };
+    let cancelled = collection_id.clone();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px] min-w-[800px] max-w-[1000px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[30px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text("啟動前發現模組衝突").css("font-black text-red text-[40px]"),
+                                ContentType::hint("以下問題很可能讓遊戲無法啟動"),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                ]
+            }
+            for (index , issue) in issues.into_iter().enumerate() {
+                Button {
+                    key: "{index}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "p-[30px]",
+                    string_placements: vec![ContentType::text(issue_line(&issue)).css("font-english").align_left()]
+                }
+            }
+            div {
+                class: "flex gap-[3px]",
+                Button {
+                    roundness: Roundness::None,
+                    extended_css_class: "p-[30px] rounded-bl-[30px]",
+                    onclick: move |()| {
+                        active.set(false);
+                        *PRE_LAUNCH_BLOCK.write() = None;
+                        // a one-off quick play ends with the launch it was for
+                        take_quick_play(&cancelled);
+                    },
+                    string_placements: vec![ContentType::text("取消").align_center()]
+                }
+                Button {
+                    roundness: Roundness::None,
+                    extended_css_class: "p-[30px] rounded-br-[30px]",
+                    onclick: move |()| {
+                        active.set(false);
+                        *PRE_LAUNCH_BLOCK.write() = None;
+                        *FORCE_LAUNCH.write() = Some(collection_id.clone());
+                    },
+                    string_placements: vec![ContentType::text("仍要啟動").align_center()]
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,719 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use dioxus::prelude::*;
+use rust_lib::api::{
+    backend_exclusive::mod_management::mods::ModMetadata,
+    shared_resources::collection::CollectionId,
+};
+use snafu::prelude::*;
+
+use crate::{
+    jar_metadata::JarMetadata,
+    local_mods::import_download,
+    mod_actions,
+    mod_index::{index_collection, normalize},
+    pages::Pages,
+    BaseComponents::{
+        atoms::button::{Button, FillMode, Roundness},
+        molecules::switcher::StateSwitcher,
+        organisms::modal::Modal,
+        string_placements::{Alignment, ContentType, Contents, Hint, Text},
+    },
+    ARROW_RIGHT,
+};
+
+/// The latest crash that was detected after a game exited, shown by [`CrashReportModal`].
+pub static CRASH_REPORT: GlobalSignal<Option<(CollectionId, CrashAnalysis)>> =
+    GlobalSignal::new(|| None);
+
+/// Packages of the JVM, the game and the loaders. Their frames are in every trace, so they say
+/// nothing about which mod crashed.
+const PLATFORM_PACKAGES: [&str; 13] = [
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "com.mojang.",
+    "net.minecraft.",
+    "net.minecraftforge.",
+    "net.neoforged.",
+    "net.fabricmc.loader.",
+    "org.quiltmc.loader.",
+    "org.spongepowered.",
+    "cpw.mods.",
+    "org.lwjgl.",
+];
+
+#[derive(Snafu, Debug)]
+pub enum CrashReportError {
+    #[snafu(display("Failed to read crash report directory {}", path.display()))]
+    ReadDir {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to read crash report {}", path.display()))]
+    ReadReport {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashKind {
+    /// `crash-reports/crash-*.txt` written by Minecraft itself.
+    Minecraft,
+    /// `hs_err_pid*.log` written by the JVM when it dies natively.
+    Jvm,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuspectedMod {
+    pub name: String,
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixinFailure {
+    pub config: String,
+    pub mod_id: Option<String>,
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDependency {
+    pub required_by: String,
+    pub dependency: String,
+}
+
+/// Everything we could pull out of a single crash file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CrashReport {
+    pub exception: Option<String>,
+    pub description: Option<String>,
+    pub suspected_mods: Vec<SuspectedMod>,
+    pub mixin_failures: Vec<MixinFailure>,
+    pub missing_dependencies: Vec<MissingDependency>,
+    pub frames: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuggestedAction {
+    Disable { mod_name: String },
+    Update { mod_name: String },
+    Install { dependency: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub reason: String,
+    pub action: SuggestedAction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrashAnalysis {
+    pub kind: CrashKind,
+    pub path: PathBuf,
+    pub report: CrashReport,
+    /// Installed mods that showed up in the stack trace.
+    pub blamed_mods: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// An installed mod as a crash report can name it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledMod {
+    /// What the collection lists it as, which the suggested actions take
+    pub name: String,
+    /// The id from the jar, `None` when it has no metadata we understand
+    pub id: Option<String>,
+}
+
+/// The collection's mods with the ids their jars declare.
+pub fn installed_mods(game_directory: &Path, mods: &[ModMetadata]) -> Vec<InstalledMod> {
+    let jars = std::fs::read_dir(game_directory.join("mods"))
+        .into_iter()
+        .flatten()
+        .filter_map(|x| x.ok().map(|x| x.path()))
+        .filter(|x| x.extension().is_some_and(|x| x == "jar"))
+        .filter_map(|x| JarMetadata::read(&x).ok())
+        .collect::<Vec<_>>();
+    let mut installed = jars
+        .into_iter()
+        .map(|jar| {
+            let listed = mods.iter().find(|x| {
+                let name = normalize(&x.name);
+                name == normalize(&jar.name) || name == normalize(&jar.id)
+            });
+            InstalledMod {
+                name: listed.map_or(jar.name, |x| x.name.clone()),
+                id: Some(jar.id),
+            }
+        })
+        .collect::<Vec<_>>();
+    for x in mods {
+        if !installed.iter().any(|installed| installed.name == x.name) {
+            installed.push(InstalledMod {
+                name: x.name.clone(),
+                id: None,
+            });
+        }
+    }
+    installed
+}
+
+/// Looks for crash files that were written after `since`, newest first.
+pub fn find_new_crash_files(
+    game_directory: &Path,
+    since: SystemTime,
+) -> Result<Vec<(CrashKind, PathBuf)>, CrashReportError> {
+    let mut found = Vec::new();
+    let crash_reports = game_directory.join("crash-reports");
+    if crash_reports.is_dir() {
+        for entry in std::fs::read_dir(&crash_reports).context(ReadDirSnafu {
+            path: &crash_reports,
+        })? {
+            let path = entry.context(ReadDirSnafu { path: &crash_reports })?.path();
+            if path.extension().is_some_and(|x| x == "txt") {
+                found.push((CrashKind::Minecraft, path));
+            }
+        }
+    }
+    for entry in std::fs::read_dir(game_directory).context(ReadDirSnafu {
+        path: game_directory,
+    })? {
+        let path = entry.context(ReadDirSnafu {
+            path: game_directory,
+        })?.path();
+        let is_hs_err = path
+            .file_name()
+            .and_then(|x| x.to_str())
+            .is_some_and(|x| x.starts_with("hs_err_pid") && x.ends_with(".log"));
+        if is_hs_err {
+            found.push((CrashKind::Jvm, path));
+        }
+    }
+    let mut found = found
+        .into_iter()
+        .filter_map(|(kind, path)| {
+            let modified = path.metadata().and_then(|x| x.modified()).ok()?;
+            (modified >= since).then_some((modified, kind, path))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by_key(|x| std::cmp::Reverse(x.0));
+    Ok(found.into_iter().map(|(_, kind, path)| (kind, path)).collect())
+}
+
+/// Finds the newest crash written since the game was started and analyses it against the
+/// collection's installed mods. A game that exited cleanly only counts as crashed when it wrote
+/// a crash report, a JVM that exited cleanly did not crash whatever files lie around.
+pub fn detect_crash(
+    game_directory: &Path,
+    since: SystemTime,
+    exited_cleanly: bool,
+    mods: &[ModMetadata],
+) -> Result<Option<CrashAnalysis>, CrashReportError> {
+    let Some((kind, path)) = find_new_crash_files(game_directory, since)?
+        .into_iter()
+        .find(|(kind, _)| !exited_cleanly || *kind == CrashKind::Minecraft)
+    else {
+        return Ok(None);
+    };
+    let mods = installed_mods(game_directory, mods);
+    let text = std::fs::read_to_string(&path).context(ReadReportSnafu { path: &path })?;
+    let report = match kind {
+        CrashKind::Minecraft => CrashReport::parse_minecraft(&text),
+        CrashKind::Jvm => CrashReport::parse_hs_err(&text),
+    };
+    Ok(Some(CrashAnalysis::new(kind, path, report, &mods)))
+}
+
+impl CrashReport {
+    pub fn parse_minecraft(text: &str) -> Self {
+        let mut report = Self::default();
+        let mut lines = text.lines().peekable();
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim();
+            if let Some(description) = trimmed.strip_prefix("Description:") {
+                report.description.get_or_insert(description.trim().to_owned());
+                // the exception is the first non-empty line after the description
+                while let Some(next) = lines.peek() {
+                    if next.trim().is_empty() {
+                        lines.next();
+                    } else {
+                        break;
+                    }
+                }
+                if let Some(exception) = lines.next() {
+                    report.exception = Some(exception.trim().to_owned());
+                }
+            } else if let Some(rest) = trimmed
+                .strip_prefix("Suspected Mods:")
+                .or_else(|| trimmed.strip_prefix("Suspected Mod:"))
+            {
+                let rest = rest.trim();
+                if !rest.is_empty() && rest != "NONE" && rest != "None" {
+                    report.suspected_mods.extend(parse_suspected_mod(rest));
+                }
+                while let Some(next) = lines.peek() {
+                    let next_trimmed = next.trim();
+                    if !next.starts_with('\t') || next_trimmed.is_empty() {
+                        break;
+                    }
+                    if !next_trimmed.starts_with("at ") && !next_trimmed.contains("URL:") {
+                        report
+                            .suspected_mods
+                            .extend(parse_suspected_mod(next_trimmed));
+                    }
+                    lines.next();
+                }
+            } else if let Some(frame) = trimmed.strip_prefix("at ") {
+                report.frames.push(frame.to_owned());
+            } else if let Some(failure) = parse_mixin_failure(trimmed) {
+                if !report.mixin_failures.contains(&failure) {
+                    report.mixin_failures.push(failure);
+                }
+            } else if let Some(missing) = parse_missing_dependency(trimmed) {
+                if !report.missing_dependencies.contains(&missing) {
+                    report.missing_dependencies.push(missing);
+                }
+            }
+        }
+        report
+    }
+
+    pub fn parse_hs_err(text: &str) -> Self {
+        let mut report = Self::default();
+        let mut lines = text.lines();
+        let mut in_java_frames = false;
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim_start_matches('#').trim();
+            if report.exception.is_none()
+                && (trimmed.starts_with("SIG") || trimmed.starts_with("EXCEPTION_"))
+            {
+                report.exception = Some(trimmed.to_owned());
+            } else if trimmed.starts_with("Problematic frame:") {
+                if let Some(frame) = lines.next() {
+                    let frame = frame.trim_start_matches('#').trim().to_owned();
+                    report.description = Some(frame.clone());
+                    report.frames.push(frame);
+                }
+            } else if trimmed.starts_with("Java frames:") {
+                in_java_frames = true;
+            } else if in_java_frames {
+                if trimmed.is_empty() {
+                    in_java_frames = false;
+                } else if let Some(frame) = trimmed.strip_prefix("J ").or(trimmed.strip_prefix("j ")) {
+                    report.frames.push(frame.to_owned());
+                }
+            }
+        }
+        report
+    }
+}
+
+/// Parses `Sodium (sodium), Version: 0.5.3` like entries.
+fn parse_suspected_mod(entry: &str) -> Vec<SuspectedMod> {
+    entry
+        .split(", ")
+        .filter(|x| !x.starts_with("Version"))
+        .filter_map(|x| {
+            let x = x.trim();
+            if x.is_empty() {
+                return None;
+            }
+            if let Some((name, id)) = x.split_once(" (") {
+                Some(SuspectedMod {
+                    name: name.trim().to_owned(),
+                    id: Some(id.trim_end_matches(')').trim().to_owned()),
+                })
+            } else {
+                Some(SuspectedMod {
+                    name: x.to_owned(),
+                    id: None,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Parses Mixin's `Mixin [sodium.mixins.json:core.Foo] from phase [DEFAULT] in config
+/// [sodium.mixins.json] FAILED during APPLY` and Fabric's `Mixin apply for mod sodium failed
+/// sodium.mixins.json:core.Foo from mod sodium -> net.minecraft.class_310` lines.
+fn parse_mixin_failure(line: &str) -> Option<MixinFailure> {
+    if let Some(rest) = line.strip_prefix("Mixin apply for mod ") {
+        let (mod_id, rest) = rest.split_once(" failed ")?;
+        let (config, target) = rest
+            .split_once(" from mod ")
+            .map_or((rest, None), |(config, rest)| {
+                (config, rest.split_once(" -> ").map(|x| x.1))
+            });
+        return Some(MixinFailure {
+            config: config.split(':').next().unwrap_or(config).to_owned(),
+            mod_id: Some(mod_id.to_owned()),
+            target: target.map(|x| x.split(':').next().unwrap_or(x).trim().to_owned()),
+        });
+    }
+    if line.starts_with("Mixin [") && line.contains("FAILED") {
+        let config = line.split_once("in config [")?.1.split_once(']')?.0;
+        return Some(MixinFailure {
+            config: config.to_owned(),
+            mod_id: config.split('.').next().map(ToOwned::to_owned),
+            target: None,
+        });
+    }
+    None
+}
+
+/// Parses Fabric's `Mod 'Indium' (indium) 1.0.0 requires any version of fabric-api, which is
+/// missing!` and Forge's `Mod ID: 'architectury', Requested by: 'foo', ...` lines.
+fn parse_missing_dependency(line: &str) -> Option<MissingDependency> {
+    let line = line.trim_start_matches("- ");
+    if line.ends_with("which is missing!") {
+        let rest = line.strip_prefix("Mod '")?;
+        let (required_by, rest) = rest.split_once('\'')?;
+        let dependency = rest
+            .split_once(" of ")?
+            .1
+            .trim_end_matches(", which is missing!")
+            .trim();
+        return Some(MissingDependency {
+            required_by: required_by.to_owned(),
+            dependency: dependency.to_owned(),
+        });
+    }
+    if let Some(rest) = line.strip_prefix("Mod ID: '") {
+        let (dependency, rest) = rest.split_once('\'')?;
+        let required_by = rest
+            .split_once("Requested by: '")?
+            .1
+            .split_once('\'')?
+            .0;
+        return Some(MissingDependency {
+            required_by: required_by.to_owned(),
+            dependency: dependency.to_owned(),
+        });
+    }
+    None
+}
+
+/// Returns the installed mod that matches a crash report identifier (a mod id, display name or
+/// mixin config prefix).
+fn find_installed<'a>(mods: &'a [InstalledMod], identifier: &str) -> Option<&'a InstalledMod> {
+    let identifier = normalize(identifier);
+    if identifier.is_empty() {
+        return None;
+    }
+    mods.iter().find(|x| {
+        normalize(&x.name) == identifier
+            || x.id
+                .as_deref()
+                .is_some_and(|id| normalize(id) == identifier)
+    })
+}
+
+/// The installed mods a stack frame points at: a package segment that is a mod's id, as in
+/// `me.jellysquid.mods.sodium.client.Foo`, or the id Mixin puts into the handlers it merges into
+/// the game's classes, as in `handler$zza000$sodium$onRender`. Names are never matched, a mod
+/// called `Core` would otherwise be blamed for every `net.minecraft.core` frame.
+fn frame_mods<'a>(mods: &'a [InstalledMod], frame: &str) -> Vec<&'a InstalledMod> {
+    let method = frame.split('(').next().unwrap_or(frame);
+    let (class, method) = method.rsplit_once('.').unwrap_or(("", method));
+    let packages = if PLATFORM_PACKAGES.iter().any(|x| class.starts_with(x)) {
+        Vec::new()
+    } else {
+        // the last segment is the class itself
+        let mut segments = class.split('.').collect::<Vec<_>>();
+        segments.pop();
+        segments
+    };
+    let handlers = method.split('$').skip(1);
+    let tokens = packages.into_iter().chain(handlers).collect::<Vec<_>>();
+    mods.iter()
+        .filter(|x| {
+            x.id.as_deref()
+                .is_some_and(|id| tokens.iter().any(|token| token.eq_ignore_ascii_case(id)))
+        })
+        .collect()
+}
+
+impl CrashAnalysis {
+    pub fn new(kind: CrashKind, path: PathBuf, report: CrashReport, mods: &[InstalledMod]) -> Self {
+        let blamed_mods = report
+            .frames
+            .iter()
+            .flat_map(|frame| frame_mods(mods, frame))
+            .map(|x| x.name.clone())
+            .fold(Vec::new(), |mut acc, x| {
+                if !acc.contains(&x) {
+                    acc.push(x);
+                }
+                acc
+            });
+
+        let mut suggestions = Vec::new();
+        let mut push = |suggestion: Suggestion| {
+            if !suggestions
+                .iter()
+                .any(|x: &Suggestion| x.action == suggestion.action)
+            {
+                suggestions.push(suggestion);
+            }
+        };
+        for missing in &report.missing_dependencies {
+            push(Suggestion {
+                reason: format!("{} 需要 {}，但它並未安裝", missing.required_by, missing.dependency),
+                action: SuggestedAction::Install {
+                    dependency: missing.dependency.clone(),
+                },
+            });
+        }
+        for failure in &report.mixin_failures {
+            let installed = failure
+                .mod_id
+                .as_deref()
+                .and_then(|x| find_installed(mods, x));
+            if let Some(installed) = installed {
+                push(Suggestion {
+                    reason: format!("{} 的 Mixin ({}) 套用失敗", installed.name, failure.config),
+                    action: SuggestedAction::Disable {
+                        mod_name: installed.name.clone(),
+                    },
+                });
+            }
+        }
+        for suspected in &report.suspected_mods {
+            let installed = find_installed(mods, &suspected.name).or_else(|| {
+                suspected
+                    .id
+                    .as_deref()
+                    .and_then(|x| find_installed(mods, x))
+            });
+            if let Some(installed) = installed {
+                let reason = format!("崩潰報告將 {} 列為可疑模組", installed.name);
+                push(Suggestion {
+                    reason: reason.clone(),
+                    action: SuggestedAction::Disable {
+                        mod_name: installed.name.clone(),
+                    },
+                });
+                push(Suggestion {
+                    reason,
+                    action: SuggestedAction::Update {
+                        mod_name: installed.name.clone(),
+                    },
+                });
+            }
+        }
+        for mod_name in &blamed_mods {
+            let reason = format!("{mod_name} 出現在錯誤堆疊中，嘗試停用或更新它");
+            push(Suggestion {
+                reason: reason.clone(),
+                action: SuggestedAction::Disable {
+                    mod_name: mod_name.clone(),
+                },
+            });
+            push(Suggestion {
+                reason,
+                action: SuggestedAction::Update {
+                    mod_name: mod_name.clone(),
+                },
+            });
+        }
+
+        Self {
+            kind,
+            path,
+            report,
+            blamed_mods,
+            suggestions,
+        }
+    }
+}
+
+/// Updates an installed mod to the newest version Modrinth has for the collection, the way the
+/// mod list's update does. Modrinth mods go through `update_modrinth_mod`, dropped jars are
+/// replaced by the new file.
+async fn update_to_latest(collection_id: &CollectionId, mod_name: &str) -> anyhow::Result<()> {
+    let index = index_collection(collection_id).await?;
+    let jar = index
+        .find(mod_name)
+        .ok_or_else(|| anyhow::anyhow!("Could not find the jar of {mod_name}"))?;
+    let version = index
+        .updates
+        .get(&jar.file_name)
+        .ok_or_else(|| anyhow::anyhow!("{mod_name} has no newer version on Modrinth"))?;
+    let managed = collection_id
+        .get_collection_owned()
+        .mod_controller()
+        .is_some_and(|x| x.manager.mods.iter().any(|x| x.name == mod_name));
+    if managed {
+        mod_actions::update_modrinth_mod(collection_id, mod_name, Some(&jar.file_name), version)
+            .await
+    } else {
+        let file = version
+            .primary_file()
+            .ok_or_else(|| anyhow::anyhow!("{mod_name} has no file to download"))?;
+        import_download(collection_id, file).await?;
+        Ok(())
+    }
+}
+
+#[component]
+pub fn CrashReportModal() -> Element {
+    let mut active = use_signal(|| false);
+    use_effect(move || {
+        if CRASH_REPORT.read().is_some() {
+            active.set(true);
+        }
+    });
+    rsx! {
+        Modal {
+            active,
+            name: "crash_report_modal",
+            CrashReportView {
+                active
+            }
+        }
+    }
+}
+
+#[component]
+fn CrashReportView(active: Signal<bool>) -> Element {
+    let Some((collection_id, analysis)) = CRASH_REPORT() else {
+        return rsx!();
+    };
+    let title = match analysis.kind {
+        CrashKind::Minecraft => "遊戲崩潰了",
+        CrashKind::Jvm => "Java 執行環境崩潰了",
+    };
+    let exception = analysis
+        .report
+        .exception
+        .clone()
+        .or(analysis.report.description.clone())
+        .unwrap_or_else(|| String::from("無法判斷錯誤原因"));
+    let path = analysis.path.to_string_lossy().to_string();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px] min-w-[800px] max-w-[1000px]",
+            Button {
+                roundness: Roundness::Top,
+                clickable: false,
+                extended_css_class: "p-[30px]",
+                string_placements: vec![
+                    Contents::new(
+                            vec![
+                                ContentType::text(title).css("font-black text-red text-[40px]"),
+                                ContentType::hint(path),
+                            ],
+                            Alignment::Left,
+                        )
+                        .css("flex flex-col gap-[15px]"),
+                ]
+            }
+            Button {
+                roundness: Roundness::None,
+                clickable: false,
+                extended_css_class: "p-[30px]",
+                string_placements: rsx! {
+                    pre { class: "font-english text-[15px] text-white text-wrap", "{exception}" }
+                }
+            }
+            for suggestion in analysis.suggestions.clone() {
+                SuggestionRow {
+                    collection_id: collection_id.clone(),
+                    suggestion
+                }
+            }
+            Button {
+                roundness: Roundness::Bottom,
+                extended_css_class: "p-[30px]",
+                onclick: move |()| {
+                    active.set(false);
+                    *CRASH_REPORT.write() = None;
+                },
+                string_placements: vec![
+                    ContentType::text("關閉").align_center(),
+                ]
+            }
+        }
+    }
+}
+
+#[component]
+fn SuggestionRow(collection_id: ReadOnlySignal<CollectionId>, suggestion: Suggestion) -> Element {
+    let mut done = use_signal(|| false);
+    let mut failed = use_signal(|| None);
+    let action = suggestion.action.clone();
+    let (label, hint) = match &suggestion.action {
+        SuggestedAction::Disable { mod_name } => ("停用", mod_name.clone()),
+        SuggestedAction::Update { mod_name } => ("更新", mod_name.clone()),
+        SuggestedAction::Install { dependency } => ("安裝", dependency.clone()),
+    };
+    rsx! {
+        Button {
+            roundness: Roundness::None,
+            clickable: false,
+            extended_css_class: "p-[20px] gap-[20px]",
+            string_placements: vec![
+                ContentType::custom(rsx! {
+                    div {
+                        class: "flex flex-col gap-[10px]",
+                        Text { css: "text-xl", {suggestion.reason.clone()} }
+                        if let Some(x) = failed() {
+                            Hint { css: "text-[13px] text-red", {x} }
+                        } else {
+                            Hint { css: "text-[13px]", {hint} }
+                        }
+                    }
+                }).align_left(),
+                ContentType::custom(rsx! {
+                    div {
+                        class: "flex gap-[10px]",
+                        Button {
+                            roundness: Roundness::Pill,
+                            fill_mode: FillMode::Fit,
+                            extended_css_class: "px-[25px] aria-selected:bg-green",
+                            focus_color_change: true,
+                            onclick: move |()| {
+                                let action = action.clone();
+                                spawn(async move {
+                                    let result = match action {
+                                        SuggestedAction::Disable { mod_name } => {
+                                            mod_actions::set_mod_enabled(&collection_id(), &mod_name, false)
+                                        }
+                                        SuggestedAction::Update { mod_name } => {
+                                            update_to_latest(&collection_id(), &mod_name).await
+                                        }
+                                        SuggestedAction::Install { dependency } => {
+                                            mod_actions::install_modrinth_mod(&collection_id(), &dependency).await
+                                        }
+                                    };
+                                    match result {
+                                        Ok(()) => done.set(true),
+                                        Err(x) => failed.set(Some(x.to_string())),
+                                    }
+                                });
+                            },
+                            string_placements: vec![
+                                ContentType::text(if done() { "完成" } else { label }).align_center(),
+                            ]
+                        }
+                        Button {
+                            roundness: Roundness::Pill,
+                            fill_mode: FillMode::Fit,
+                            extended_css_class: "px-[20px]",
+                            onclick: move |()| {
+                                Pages::collection_display(collection_id()).switch_active_to_self();
+                            },
+                            string_placements: vec![
+                                ContentType::svg(ARROW_RIGHT).css("svg-[25px]").align_center(),
+                            ]
+                        }
+                    }
+                }).align_right(),
+            ]
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::{
+    jar_metadata::{DependencyKind, JarMetadata, ModDependency},
+    local_mods::{local_mods_of, LOCAL_MODS},
+    mod_index::{mods_dir, read_metadata},
+    mod_query::ModSource,
+    BaseComponents::{
+        atoms::button::{Button, FillMode, Roundness},
+        string_placements::{ContentType, Hint, Text},
+    },
+    ThrowResource,
+};
+
+/// Ids provided by the game and the loaders themselves, never shown as missing.
+const PLATFORM_IDS: &[&str] = &[
+    "minecraft",
+    "java",
+    "fabricloader",
+    "fabric-loader",
+    "quilt_loader",
+    "quilt_base",
+    "forge",
+    "neoforge",
+    "javafml",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyNode {
+    pub id: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub file_name: String,
+    pub source: ModSource,
+    pub dependencies: Vec<ModDependency>,
+    /// Other ids the mod answers to
+    pub provides: Vec<String>,
+}
+
+/// The installed mods keyed by mod id, with the edges their jars declare.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DependencyGraph {
+    pub nodes: BTreeMap<String, DependencyNode>,
+    /// Ids that are satisfied without being a node: the platform and jar-in-jar libraries
+    pub provided: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GraphIssues {
+    /// Library mods that no installed mod depends on any more
+    pub orphaned_libraries: Vec<String>,
+    /// `(required_by, dependency)`
+    pub missing: Vec<(String, String)>,
+    /// `(declared_by, incompatible_with)`, both installed
+    pub incompatible: Vec<(String, String)>,
+}
+
+/// Id segments that mark a library, `cloth-config`, `fabric-api` or `kotlin_lib`.
+const LIBRARY_SEGMENTS: &[&str] = &["lib", "library", "api", "config", "core"];
+
+impl DependencyNode {
+    /// A heuristic, jars don't say whether they are a library: an id with a segment in
+    /// `LIBRARY_SEGMENTS`, `architectury`, or a description calling itself one. Only whole
+    /// segments count, so `capitalism` or `scoreboard` are not taken for one.
+    pub fn looks_like_library(&self) -> bool {
+        let id = self.id.to_lowercase();
+        id.split(['-', '_', '.'])
+            .any(|x| LIBRARY_SEGMENTS.contains(&x))
+            || id == "architectury"
+            || self
+                .description
+                .as_ref()
+                .is_some_and(|x| x.to_lowercase().contains("library"))
+    }
+}
+
+impl DependencyGraph {
+    /// `jars` are `(file_name, metadata)` as [`read_metadata`] lists them.
+    pub fn build(jars: &[(String, JarMetadata)], local_files: &BTreeSet<String>) -> Self {
+        let mut graph = Self {
+            provided: PLATFORM_IDS.iter().map(|x| (*x).to_owned()).collect(),
+            ..Default::default()
+        };
+        for (file_name, metadata) in jars {
+            graph.provided.extend(
+                metadata
+                    .dependencies
+                    .iter()
+                    .filter(|x| x.kind == DependencyKind::Embedded)
+                    .map(|x| x.id.clone())
+                    .chain(metadata.provides.iter().cloned()),
+            );
+            graph.nodes.insert(
+                metadata.id.clone(),
+                DependencyNode {
+                    id: metadata.id.clone(),
+                    name: metadata.name.clone(),
+                    version: metadata.version.clone(),
+                    description: metadata.description.clone(),
+                    file_name: file_name.clone(),
+                    source: if local_files.contains(file_name) {
+                        ModSource::Local
+                    } else {
+                        ModSource::Modrinth
+                    },
+                    dependencies: metadata.dependencies.clone(),
+                    provides: metadata.provides.clone(),
+                },
+            );
+        }
+        graph
+    }
+
+    pub fn is_satisfied(&self, id: &str) -> bool {
+        self.nodes.contains_key(id) || self.provided.contains(id)
+    }
+
+    /// The installed mod behind `id`, by its own id or one it provides.
+    pub fn node(&self, id: &str) -> Option<&DependencyNode> {
+        self.nodes.get(id).or_else(|| {
+            self.nodes
+                .values()
+                .find(|x| x.provides.iter().any(|x| x == id))
+        })
+    }
+
+    /// Installed mods that actually depend on the mod `id`, directly or through an id it
+    /// provides, rather than warning against it.
+    pub fn dependents(&self, id: &str) -> Vec<&DependencyNode> {
+        self.nodes
+            .values()
+            .filter(|x| {
+                x.dependencies.iter().any(|x| {
+                    self.node(&x.id).is_some_and(|x| x.id == id)
+                        && !matches!(
+                            x.kind,
+                            DependencyKind::Incompatible | DependencyKind::Discouraged
+                        )
+                })
+            })
+            .collect()
+    }
+
+    /// Mods nothing else depends on, the top level of the tree. Mods that only depend on each
+    /// other in a cycle have no such mod above them, the first of each cycle stands in.
+    pub fn roots(&self) -> Vec<&DependencyNode> {
+        let mut roots = self
+            .nodes
+            .values()
+            .filter(|x| self.dependents(&x.id).is_empty())
+            .collect::<Vec<_>>();
+        let mut reached = BTreeSet::new();
+        for &root in &roots {
+            self.reach(&root.id, &mut reached);
+        }
+        for node in self.nodes.values() {
+            if !reached.contains(node.id.as_str()) {
+                self.reach(&node.id, &mut reached);
+                roots.push(node);
+            }
+        }
+        roots
+    }
+
+    /// Adds the mod behind `id` and every installed mod it depends on to `reached`.
+    fn reach<'a>(&'a self, id: &str, reached: &mut BTreeSet<&'a str>) {
+        let Some(node) = self.node(id) else {
+            return;
+        };
+        if !reached.insert(&node.id) {
+            return;
+        }
+        for dependency in &node.dependencies {
+            if !matches!(
+                dependency.kind,
+                DependencyKind::Incompatible | DependencyKind::Discouraged
+            ) {
+                self.reach(&dependency.id, reached);
+            }
+        }
+    }
+
+    pub fn issues(&self) -> GraphIssues {
+        let mut issues = GraphIssues::default();
+        for node in self.nodes.values() {
+            if node.looks_like_library() && self.dependents(&node.id).is_empty() {
+                issues.orphaned_libraries.push(node.id.clone());
+            }
+            for dependency in &node.dependencies {
+                match dependency.kind {
+                    DependencyKind::Required if !self.is_satisfied(&dependency.id) => {
+                        issues
+                            .missing
+                            .push((node.id.clone(), dependency.id.clone()));
+                    }
+                    DependencyKind::Incompatible
+                        if self.nodes.get(&dependency.id).is_some_and(|x| {
+                            dependency.matches_version(x.version.as_deref()) == Some(true)
+                        }) =>
+                    {
+                        // two mods that declare each other incompatible are listed once
+                        let mutual = issues
+                            .incompatible
+                            .iter()
+                            .any(|(by, id)| *by == dependency.id && *id == node.id);
+                        if !mutual {
+                            issues
+                                .incompatible
+                                .push((node.id.clone(), dependency.id.clone()));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        issues
+    }
+
+    /// Depth-first rows of the indented tree, starting from every root.
+    pub fn tree_rows(&self, include_optional: bool) -> Vec<TreeRow> {
+        let mut rows = Vec::new();
+        let mut path = Vec::new();
+        for root in self.roots() {
+            self.push_rows(&root.id, None, 0, include_optional, &mut path, &mut rows);
+        }
+        rows
+    }
+
+    fn push_rows(
+        &self,
+        id: &str,
+        kind: Option<DependencyKind>,
+        depth: usize,
+        include_optional: bool,
+        path: &mut Vec<String>,
+        rows: &mut Vec<TreeRow>,
+    ) {
+        let node = self.node(id);
+        // the path holds mod ids, a dependency may name one of them by an alias
+        let cycle = path.iter().any(|x| x == node.map_or(id, |x| x.id.as_str()));
+        rows.push(TreeRow {
+            id: id.to_owned(),
+            name: node.map(|x| x.name.clone()),
+            kind,
+            depth,
+            missing: kind == Some(DependencyKind::Required) && !self.is_satisfied(id),
+            cycle,
+        });
+        let Some(node) = node.filter(|_| !cycle) else {
+            return;
+        };
+        path.push(node.id.clone());
+        for dependency in &node.dependencies {
+            let shown = match dependency.kind {
+                DependencyKind::Required => !self.provided.contains(&dependency.id)
+                    || self.nodes.contains_key(&dependency.id),
+                DependencyKind::Optional => {
+                    include_optional && self.node(&dependency.id).is_some()
+                }
+                DependencyKind::Incompatible | DependencyKind::Discouraged => {
+                    self.node(&dependency.id).is_some()
+                }
+                DependencyKind::Embedded => include_optional,
+            };
+            if shown {
+                self.push_rows(
+                    &dependency.id,
+                    Some(dependency.kind),
+                    depth + 1,
+                    include_optional,
+                    path,
+                    rows,
+                );
+            }
+        }
+        path.pop();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeRow {
+    pub id: String,
+    /// `None` when the mod is not installed
+    pub name: Option<String>,
+    /// How the parent depends on this mod, `None` for roots
+    pub kind: Option<DependencyKind>,
+    pub depth: usize,
+    pub missing: bool,
+    /// Already on the path from the root, its children are not repeated
+    pub cycle: bool,
+}
+
+/// Builds the graph from the jars' metadata on a blocking thread, the graph needs no hashes.
+pub async fn dependency_graph(collection_id: &CollectionId) -> anyhow::Result<DependencyGraph> {
+    let local_files = local_mods_of(collection_id)
+        .into_iter()
+        .map(|x| x.file_name)
+        .collect::<BTreeSet<_>>();
+    let dir = mods_dir(collection_id);
+    tokio::task::spawn_blocking(move || {
+        let jars = read_metadata(&dir)?;
+        Ok(DependencyGraph::build(&jars, &local_files))
+    })
+    .await?
+}
+
+fn kind_label(kind: Option<DependencyKind>) -> &'static str {
+    match kind {
+        None => "",
+        Some(DependencyKind::Required) => "必要",
+        Some(DependencyKind::Optional) => "選用",
+        Some(DependencyKind::Incompatible) => "不相容",
+        Some(DependencyKind::Discouraged) => "不建議同時使用",
+        Some(DependencyKind::Embedded) => "內嵌",
+    }
+}
+
+#[component]
+pub fn DependencyTree(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let mut include_optional = use_signal(|| false);
+    let graph = use_resource(move || async move {
+        // rebuild when mods are installed, updated, dropped in or removed
+        collection_id().get_collection().read();
+        LOCAL_MODS.read();
+        dependency_graph(&collection_id()).await
+    });
+    let graph = graph.throw()?.unwrap_or_default();
+    let issues = graph.issues();
+    let orphaned = issues.orphaned_libraries.iter().cloned().collect::<BTreeSet<_>>();
+    let incompatible = issues
+        .incompatible
+        .iter()
+        .flat_map(|(a, b)| [a.clone(), b.clone()])
+        .collect::<BTreeSet<_>>();
+    let summary = [
+        (!issues.missing.is_empty()).then(|| {
+            format!(
+                "缺少必要依賴：{}",
+                issues
+                    .missing
+                    .iter()
+                    .map(|(by, id)| format!("{by} → {id}"))
+                    .collect::<Vec<_>>()
+                    .join("、")
+            )
+        }),
+        (!issues.incompatible.is_empty()).then(|| {
+            format!(
+                "宣告不相容：{}",
+                issues
+                    .incompatible
+                    .iter()
+                    .map(|(by, id)| format!("{by} ✕ {id}"))
+                    .collect::<Vec<_>>()
+                    .join("、")
+            )
+        }),
+        (!issues.orphaned_libraries.is_empty()).then(|| {
+            format!(
+                "沒有模組依賴的函式庫：{}",
+                issues.orphaned_libraries.join("、")
+            )
+        }),
+    ];
+    let rows = graph.tree_rows(include_optional());
+    rsx! {
+        div {
+            class: "bg-background flex flex-col gap-[10px] rounded-t-[30px] p-[30px] h-full overflow-x-hidden",
+            div {
+                class: "flex items-center justify-between",
+                Text { css: "text-white text-lg", "依賴關係" }
+                Button {
+                    roundness: Roundness::Pill,
+                    fill_mode: FillMode::Fit,
+                    extended_css_class: "bg-deep-background px-[25px] py-[10px]",
+                    onclick: move |()| include_optional.toggle(),
+                    string_placements: vec![
+                        ContentType::text(if include_optional() { "隱藏選用與內嵌" } else { "顯示選用與內嵌" })
+                            .align_center(),
+                    ]
+                }
+            }
+            for line in summary.into_iter().flatten() {
+                Hint { css: "text-[15px] font-english text-red-400", {line} }
+            }
+            if rows.is_empty() {
+                Hint { css: "text-[15px]", "沒有可讀取的模組資訊" }
+            }
+            for (index , row) in rows.into_iter().enumerate() {
+                div {
+                    key: "{index}",
+                    class: "flex items-center gap-[10px] rounded-[15px] bg-deep-background py-[10px] pr-[20px]",
+                    padding_left: format!("{}px", 20 + row.depth * 30),
+                    Text {
+                        css: if row.missing || (incompatible.contains(&row.id) && row.kind == Some(DependencyKind::Incompatible)) {
+                            "text-red-400 text-[18px] font-english"
+                        } else if orphaned.contains(&row.id) && row.depth == 0 {
+                            "text-yellow-300 text-[18px] font-english"
+                        } else {
+                            "text-white text-[18px] font-english"
+                        },
+                        {row.name.clone().unwrap_or_else(|| row.id.clone())}
+                    }
+                    Hint {
+                        css: "text-[13px] font-english",
+                        {
+                            [
+                                Some(kind_label(row.kind).to_owned()).filter(|x| !x.is_empty()),
+                                row.missing.then(|| String::from("未安裝")),
+                                row.cycle.then(|| String::from("循環依賴")),
+                                (orphaned.contains(&row.id) && row.depth == 0)
+                                    .then(|| String::from("未被使用的函式庫")),
+                            ]
+                                .into_iter()
+                                .flatten()
+                                .collect::<Vec<_>>()
+                                .join("•")
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
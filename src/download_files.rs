@@ -0,0 +1,184 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    download_history::Outcome,
+    download_queue::{job_state, JobKind, JobState, DOWNLOAD_QUEUE},
+    mod_index::normalize,
+};
+
+/// What each queued job is fetching, keyed by job id. `rust_lib` only reports a collection's
+/// total, so libraries and assets are one entry each and mod files are picked up as they land in
+/// the collection's `mods` directory. Entries nothing reported on are marked `estimated`, their
+/// state is inferred from the job as a whole.
+pub static JOB_FILES: GlobalSignal<BTreeMap<u64, Vec<FileEntry>>> =
+    GlobalSignal::new(BTreeMap::new);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileCategory {
+    Libraries,
+    Assets,
+    Mod,
+}
+
+impl std::fmt::Display for FileCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Libraries => "函式庫",
+            Self::Assets => "資源",
+            Self::Mod => "模組",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileState {
+    Pending,
+    Downloading,
+    Done,
+    Failed,
+}
+
+impl std::fmt::Display for FileState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Pending => "等待中",
+            Self::Downloading => "下載中",
+            Self::Done => "已完成",
+            Self::Failed => "失敗",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub category: FileCategory,
+    /// The file name for mods, known once the file is written for mods added by slug
+    pub name: String,
+    pub state: FileState,
+    pub retries: u32,
+    /// The error of the last failed attempt
+    pub reason: Option<String>,
+    /// The state follows the job rather than anything seen of this file
+    #[serde(default)]
+    pub estimated: bool,
+}
+
+impl FileEntry {
+    fn new(category: FileCategory, name: impl Into<String>) -> Self {
+        Self {
+            category,
+            name: name.into(),
+            state: FileState::Pending,
+            retries: 0,
+            reason: None,
+            estimated: true,
+        }
+    }
+}
+
+/// The entries a job starts with, an upgrade only learns which mods it fetches as they arrive.
+fn plan(kind: &JobKind) -> Vec<FileEntry> {
+    let mod_file = match kind {
+        // the job is this one download, so its outcome is the file's
+        JobKind::Import { file } => {
+            return vec![FileEntry {
+                estimated: false,
+                ..FileEntry::new(FileCategory::Mod, &file.filename)
+            }]
+        }
+        JobKind::Install { slug } => Some(slug.clone()),
+        JobKind::Update { version, .. } => version.primary_file().map(|x| x.filename.clone()),
+        JobKind::Upgrade { .. } => None,
+    };
+    let mut entries = vec![
+        FileEntry::new(FileCategory::Libraries, "遊戲函式庫"),
+        FileEntry::new(FileCategory::Assets, "遊戲資源"),
+    ];
+    entries.extend(mod_file.map(|x| FileEntry::new(FileCategory::Mod, x)));
+    entries
+}
+
+pub(crate) fn start(id: u64, kind: &JobKind) {
+    JOB_FILES.write().insert(id, plan(kind));
+}
+
+/// Updates a running job's entries with the files that appeared in `mods` since it started.
+pub(crate) fn observe(id: u64, added: &BTreeSet<String>) {
+    let Some(mut entries) = JOB_FILES.peek().get(&id).cloned() else {
+        return;
+    };
+    for entry in entries
+        .iter_mut()
+        .filter(|x| x.category != FileCategory::Mod && x.state == FileState::Pending)
+    {
+        entry.state = FileState::Downloading;
+    }
+    for file in added {
+        let key = normalize(file);
+        let planned = entries.iter_mut().find(|x| {
+            x.category == FileCategory::Mod
+                && (x.name == *file
+                    || x.state != FileState::Done && key.contains(&normalize(&x.name)))
+        });
+        match planned {
+            Some(entry) => {
+                entry.name.clone_from(file);
+                entry.state = FileState::Done;
+                entry.estimated = false;
+            }
+            None => entries.push(FileEntry {
+                state: FileState::Done,
+                estimated: false,
+                ..FileEntry::new(FileCategory::Mod, file)
+            }),
+        }
+    }
+    if let Some(entry) = entries
+        .iter_mut()
+        .find(|x| x.category == FileCategory::Mod && x.state == FileState::Pending)
+    {
+        entry.state = FileState::Downloading;
+    }
+    // every poll of the job lands here, only changes should rerender the list
+    if JOB_FILES.peek().get(&id) != Some(&entries) {
+        JOB_FILES.write().insert(id, entries);
+    }
+}
+
+/// Takes the job's entries once it ended, settling the unfinished ones by its outcome.
+pub(crate) fn finish(id: u64, outcome: &Outcome) -> Vec<FileEntry> {
+    let mut entries = JOB_FILES.write().remove(&id).unwrap_or_default();
+    for entry in entries.iter_mut().filter(|x| x.state != FileState::Done) {
+        match outcome {
+            Outcome::Completed => entry.state = FileState::Done,
+            Outcome::Failed { reason } => {
+                entry.state = FileState::Failed;
+                entry.reason = Some(reason.clone());
+            }
+            Outcome::Cancelled => {}
+        }
+    }
+    entries
+}
+
+/// Counts another attempt at `file_name` for the collection's running job.
+pub fn record_retry(collection_id: &CollectionId, file_name: &str, reason: String) {
+    let queue = DOWNLOAD_QUEUE.peek();
+    let Some(job) = queue.iter().find(|x| {
+        &x.collection_id == collection_id && job_state(&queue, x.id) == Some(JobState::Running)
+    }) else {
+        return;
+    };
+    let mut jobs = JOB_FILES.write();
+    let entry = jobs
+        .get_mut(&job.id)
+        .and_then(|x| x.iter_mut().find(|x| x.name == file_name));
+    if let Some(entry) = entry {
+        entry.retries += 1;
+        entry.reason = Some(reason);
+    }
+}
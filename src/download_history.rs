@@ -0,0 +1,103 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::{collection::CollectionId, entry::STORAGE};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    download_files::FileEntry,
+    download_queue::JobKind,
+    local_mods::import_download,
+    mod_actions::{install_modrinth_mod, update_modrinth_mod},
+    persistence::{self, PersistenceError},
+    upgrade::apply_in_place,
+};
+
+const DOWNLOAD_HISTORY_FILE: &str = "download_history";
+/// Older entries are dropped once the history grows past this.
+const MAX_HISTORY: usize = 200;
+
+/// Jobs that left the download queue, newest first.
+pub static DOWNLOAD_HISTORY: GlobalSignal<Vec<FinishedJob>> =
+    GlobalSignal::new(|| persistence::load(DOWNLOAD_HISTORY_FILE));
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinishedJob {
+    /// `CollectionId::to_string` of the collection the job downloaded into
+    pub collection_id: String,
+    /// The collection's name when the job ended, kept for collections that are gone since
+    pub collection_name: String,
+    pub kind: JobKind,
+    /// Unix timestamp in seconds
+    pub finished: u64,
+    /// Time spent downloading, without the time spent waiting or paused
+    pub duration_ms: u64,
+    /// `None` when nothing reported a size for the job
+    pub total_size: Option<f64>,
+    pub outcome: Outcome,
+    /// What the job fetched, as the download page listed it
+    #[serde(default)]
+    pub files: Vec<FileEntry>,
+}
+
+impl FinishedJob {
+    pub fn average_speed(&self) -> Option<f64> {
+        let size = self.total_size?;
+        (self.duration_ms > 0).then(|| size / (self.duration_ms as f64 / 1000.))
+    }
+
+    pub fn collection(&self) -> Option<CollectionId> {
+        (STORAGE.collections)
+            .read()
+            .keys()
+            .find(|x| x.to_string() == self.collection_id)
+            .cloned()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Completed,
+    Failed { reason: String },
+    Cancelled,
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Completed => "已完成",
+            Self::Failed { .. } => "失敗",
+            Self::Cancelled => "已取消",
+        })
+    }
+}
+
+pub fn record(job: FinishedJob) -> Result<(), PersistenceError> {
+    let mut history = DOWNLOAD_HISTORY.write();
+    history.insert(0, job);
+    history.truncate(MAX_HISTORY);
+    persistence::save(DOWNLOAD_HISTORY_FILE, &*history)
+}
+
+pub fn clear_history() -> Result<(), PersistenceError> {
+    DOWNLOAD_HISTORY.write().clear();
+    persistence::save(DOWNLOAD_HISTORY_FILE, &*DOWNLOAD_HISTORY.read())
+}
+
+/// Starts the job again from the beginning, through the queue like the first time.
+pub async fn retry(job: &FinishedJob) -> anyhow::Result<()> {
+    let collection_id = job
+        .collection()
+        .ok_or_else(|| anyhow::anyhow!("{} no longer exists", job.collection_name))?;
+    match &job.kind {
+        JobKind::Install { slug } => install_modrinth_mod(&collection_id, slug).await?,
+        JobKind::Update {
+            mod_name,
+            file_name,
+            version,
+        } => update_modrinth_mod(&collection_id, mod_name, file_name.as_deref(), version).await?,
+        JobKind::Upgrade { created } => apply_in_place(&collection_id, *created).await?,
+        JobKind::Import { file } => {
+            import_download(&collection_id, file).await?;
+        }
+    }
+    Ok(())
+}
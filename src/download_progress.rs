@@ -1,12 +1,23 @@
+use std::time::Duration;
+
 use dioxus::prelude::*;
 
 use rust_lib::api::backend_exclusive::download::Progress;
 use rust_lib::api::shared_resources::collection::CollectionId;
 
+use crate::collection_edit::advanced::PillAction;
+use crate::collection_stats::format_relative;
+use crate::download_files::{FileEntry, FileState, JOB_FILES};
+use crate::download_history::{clear_history, retry, FinishedJob, Outcome, DOWNLOAD_HISTORY};
+use crate::download_queue::{
+    cancel, job_state, move_job, queue_summary, set_pause_all, set_paused, JobState, QueuedJob,
+    DOWNLOAD_QUEUE, PAUSE_ALL,
+};
 use crate::impl_context_switcher;
+use crate::use_error_handler;
 use crate::BaseComponents::{
     atoms::button::{Button, FillMode, Roundness},
-    molecules::switcher::{StateSwitcherSelectionBar, ToClass},
+    molecules::switcher::{Comparison, StateSwitcherSelectionBar, ToClass},
     string_placements::{ContentType, Hint, Image, StringPlacements, Text},
 };
 use crate::DRAG_INDICATOR;
@@ -14,49 +25,201 @@ use rust_lib::api::shared_resources::entry::DOWNLOAD_PROGRESS;
 use strum::EnumIter;
 
 #[component]
-fn ListItem(collection_id: ReadOnlySignal<CollectionId>, progress: Progress) -> Element {
+fn ListItem(
+    collection_id: ReadOnlySignal<CollectionId>,
+    progress: Option<Progress>,
+    job: Option<QueuedJob>,
+    mut dragging: Signal<Option<u64>>,
+) -> Element {
     let collection = collection_id().get_collection();
+    let state = job
+        .as_ref()
+        .and_then(|x| job_state(&DOWNLOAD_QUEUE.read(), x.id));
+    let sizes = progress.as_ref().map_or_else(
+        || String::from("尚未開始 |"),
+        |x| {
+            format!(
+                "{} / {} |",
+                x.current_size
+                    .unwrap_or_default()
+                    .display_size_from_megabytes(),
+                x.total_size
+                    .unwrap_or_default()
+                    .display_size_from_megabytes()
+            )
+        },
+    );
+    let speed = match (state, &progress) {
+        (Some(state @ (JobState::Paused | JobState::Waiting)), _) => state.to_string(),
+        (_, Some(x)) => x.speed.unwrap_or_default().display_size_from_megabytes(),
+        (_, None) => String::new(),
+    };
+    let percentages = progress.as_ref().map_or(0., |x| x.percentages);
+    let id = job.as_ref().map(|x| x.id);
+    let paused = job.as_ref().is_some_and(|x| x.paused);
+    let mut expanded = use_signal(|| false);
+    let files = id
+        .and_then(|x| JOB_FILES.read().get(&x).cloned())
+        .unwrap_or_default();
     rsx! {
-        Button {
-            roundness: Roundness::Pill,
-            string_placements: rsx! {
-                div { class: "justify-self-center w-full flex gap-[15px]",
-                    {ContentType::svg(DRAG_INDICATOR).css("self-center svg-[30px]")}
-                    div { class: "w-full flex gap-[20px]",
-                        Image { css: "bg-cover bg-white w-[80px] h-[80px] rounded-[10px]",
-                            {collection.read().picture_path().to_string_lossy().to_string()}
-                        }
-                        div { class: "w-full flex flex-col justify-start gap-[10px]",
-                            Text { css: "text-[25px] fond-bold", {collection.read().display_name().clone()} }
-                            div { class: "flex gap-[4px]",
-                                Hint { css: "text-base font-semibold",
-                                    {format!("{} / {} |", progress.current_size.unwrap_or_default().display_size_from_megabytes(), progress.total_size.unwrap_or_default().display_size_from_megabytes())}
+        div {
+            draggable: id.is_some().to_string(),
+            ondragstart: move |_| dragging.set(id),
+            ondragend: move |_| dragging.set(None),
+            ondragover: move |evt| evt.prevent_default(),
+            ondrop: move |evt| {
+                evt.prevent_default();
+                if let (Some(dragged), Some(id)) = (dragging(), id) {
+                    move_job(dragged, id);
+                }
+            },
+            Button {
+                roundness: Roundness::Pill,
+                string_placements: rsx! {
+                    div { class: "justify-self-center w-full flex gap-[15px]",
+                        {ContentType::svg(DRAG_INDICATOR).css(if id.is_some() { "self-center svg-[30px] cursor-grab" } else { "self-center svg-[30px] opacity-30" })}
+                        div { class: "w-full flex gap-[20px]",
+                            Image { css: "bg-cover bg-white w-[80px] h-[80px] rounded-[10px]",
+                                {collection.read().picture_path().to_string_lossy().to_string()}
+                            }
+                            div { class: "w-full flex flex-col justify-start gap-[10px]",
+                                Text { css: "text-[25px] fond-bold", {collection.read().display_name().clone()} }
+                                div { class: "flex gap-[4px]",
+                                    Hint { css: "text-base font-semibold", {sizes} }
+                                    Text { css: "text-base font-semibold", {speed} }
                                 }
-                                Text { css: "text-base font-semibold",
-                                    "{progress.speed.unwrap_or_default().display_size_from_megabytes()}"
+                                div { class: "w-full h-full flex items-end",
+                                    div { class: "rounded-[50px] w-full h-[7px] bg-zinc-800",
+                                        div {
+                                            class: "transition-all rounded-[50px] bg-white h-[7px]",
+                                            width: format!("{}%", percentages)
+                                        }
+                                    }
                                 }
                             }
-                            div { class: "w-full h-full flex items-end",
-                                div { class: "rounded-[50px] w-full h-[7px] bg-zinc-800",
-                                    div {
-                                        class: "transition-all rounded-[50px] bg-white h-[7px]",
-                                        width: format!("{}%", progress.percentages)
+                            if let Some(id) = id {
+                                div { class: "self-center flex gap-[10px]",
+                                    PillAction {
+                                        label: if expanded() { "收起" } else { "明細" },
+                                        selected: expanded(),
+                                        onclick: move |()| expanded.toggle()
+                                    }
+                                    PillAction {
+                                        label: if paused { "繼續" } else { "暫停" },
+                                        onclick: move |()| set_paused(id, !paused)
+                                    }
+                                    PillAction {
+                                        label: "取消",
+                                        onclick: move |()| cancel(id)
                                     }
                                 }
                             }
                         }
                     }
+                },
+                extended_css_class: "rounded-[5px]",
+                fill_mode: FillMode::Fill
+            }
+            if expanded() {
+                FileBreakdown { files }
+            }
+        }
+    }
+}
+
+/// One row per library set, asset set and mod file of a job.
+#[component]
+fn FileBreakdown(files: Vec<FileEntry>) -> Element {
+    rsx! {
+        div { class: "flex flex-col gap-[3px] pt-[3px] pl-[45px]",
+            if files.is_empty() {
+                Hint { css: "text-[15px] px-[25px] py-[10px]", "尚未開始下載" }
+            } else if files.iter().any(|x| x.estimated) {
+                Hint { css: "text-[15px] px-[25px] py-[10px]",
+                    "函式庫、資源與尚未寫入的模組沒有個別進度，標示估計的狀態是依整體下載推算"
                 }
-            },
-            extended_css_class: "rounded-[5px]",
-            fill_mode: FillMode::Fill
+            }
+            for file in files {
+                div { class: "flex items-center gap-[15px] rounded-[5px] bg-deep-background px-[25px] py-[10px]",
+                    Hint { css: "text-[15px] w-[60px] shrink-0", {file.category.to_string()} }
+                    div { class: "w-full flex flex-col gap-[5px] min-w-0",
+                        Text { css: "text-[18px] font-english truncate", {file.name} }
+                        if let Some(reason) = file.reason {
+                            Hint { css: "text-[15px] text-red", {reason} }
+                        }
+                    }
+                    if file.retries > 0 {
+                        Hint { css: "text-[15px] shrink-0", {format!("重試 {} 次", file.retries)} }
+                    }
+                    Text {
+                        css: if file.state == FileState::Failed { "text-[15px] shrink-0 text-red" } else { "text-[15px] shrink-0" },
+                        {if file.estimated { format!("{} (估計)", file.state) } else { file.state.to_string() }}
+                    }
+                }
+            }
         }
     }
 }
 
+/// How many one second speed samples the sparkline keeps.
+const SPEED_SAMPLES: usize = 40;
+/// Weight of the newest sample in the smoothed speed the ETA is based on.
+const SMOOTHING: f64 = 0.2;
+
+/// `45 秒`, `3 分 5 秒`
+fn format_elapsed(ms: u64) -> String {
+    match ms / 1000 {
+        0 => String::from("不到 1 秒"),
+        s if s < 60 => format!("{s} 秒"),
+        s => format!("{} 分 {} 秒", s / 60, s % 60),
+    }
+}
+
 #[component]
 fn FirstProgressView(collection_id: ReadOnlySignal<CollectionId>, progress: Progress) -> Element {
     let collection = collection_id().get_collection();
+    let mut samples = use_signal(Vec::<f64>::new);
+    let mut smoothed = use_signal(|| None::<f64>);
+    use_future(move || async move {
+        let mut tracked = None;
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let collection_id = collection_id.peek().clone();
+            if tracked.as_ref() != Some(&collection_id) {
+                samples.write().clear();
+                smoothed.set(None);
+                tracked = Some(collection_id.clone());
+            }
+            let speed = DOWNLOAD_PROGRESS
+                .peek()
+                .0
+                .iter()
+                .find(|(id, _)| id.collection_id == collection_id)
+                .and_then(|(_, x)| x.speed)
+                .unwrap_or_default();
+            {
+                let mut samples = samples.write();
+                samples.push(speed);
+                let excess = samples.len().saturating_sub(SPEED_SAMPLES);
+                samples.drain(..excess);
+            }
+            let next = smoothed
+                .peek()
+                .map_or(speed, |x| x + SMOOTHING * (speed - x));
+            smoothed.set(Some(next));
+        }
+    });
+    let remaining = (progress.total_size.unwrap_or_default()
+        - progress.current_size.unwrap_or_default())
+    .max(0.);
+    let eta = match smoothed() {
+        Some(speed) if speed > 0. => format!(
+            "預計剩餘 {}",
+            format_elapsed((remaining / speed * 1000.) as u64)
+        ),
+        _ => String::from("正在估算剩餘時間"),
+    };
+    let peak = samples.read().iter().copied().fold(0., f64::max);
     rsx! {
         div {
             class: "w-full h-[350px] p-[30px] rounded-[20px]",
@@ -81,16 +244,29 @@ fn FirstProgressView(collection_id: ReadOnlySignal<CollectionId>, progress: Prog
                             )
                         }
                     }
+                    Hint { css: "font-medium", {eta} }
                 }
                 div {
-                    class: "justify-self-end flex",
-                    Text {
-                        css: "text-[50px] font-bold text-white",
-                        "{progress.speed.unwrap_or_default().display_size_from_megabytes()}"
+                    class: "justify-self-end flex flex-col items-end gap-[20px]",
+                    div {
+                        class: "flex",
+                        Text {
+                            css: "text-[50px] font-bold text-white",
+                            "{progress.speed.unwrap_or_default().display_size_from_megabytes()}"
+                        }
+                        Hint {
+                            css: "text-[50px] font-bold",
+                            "/s"
+                        }
                     }
-                    Hint {
-                        css: "text-[50px] font-bold",
-                        "/s"
+                    div {
+                        class: "flex items-end gap-[2px] h-[50px]",
+                        for speed in samples() {
+                            div {
+                                class: "w-[5px] rounded-t-[2px] bg-white",
+                                height: format!("{}%", if peak > 0. { (speed / peak * 100.).max(4.) } else { 4. })
+                            }
+                        }
                     }
                 }
             }
@@ -125,16 +301,161 @@ impl ToClass for ProgressState {}
 impl_context_switcher!(ProgressState);
 
 #[component]
-fn ProgressStateBar() -> Element {
+fn ProgressStateBar(state: Signal<Comparison<ProgressState>>) -> Element {
     rsx! {
         div {
             class: "w-full grid grid-flow-col justify-stretch",
             StateSwitcherSelectionBar {
+                signal: state,
                 default_state: ProgressState::Running
             }
             div {
-                class: "justify-self-end",
-                "go fuck yourself"
+                class: "justify-self-end self-center",
+                match state().0 {
+                    ProgressState::Running => rsx! {
+                        QueueControls {}
+                    },
+                    ProgressState::Finished => rsx! {
+                        HistoryControls {}
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// The queue's summary with the switch that holds every job.
+#[component]
+fn QueueControls() -> Element {
+    let paused = PAUSE_ALL();
+    rsx! {
+        div {
+            class: "flex items-center gap-[15px]",
+            Hint { css: "font-medium", {queue_summary()} }
+            if !DOWNLOAD_QUEUE.read().is_empty() {
+                PillAction {
+                    label: if paused { "全部繼續" } else { "全部暫停" },
+                    selected: paused,
+                    onclick: move |()| set_pause_all(!paused)
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn HistoryControls() -> Element {
+    let mut error = use_error_handler();
+    let history = DOWNLOAD_HISTORY.read();
+    let failed = history
+        .iter()
+        .filter(|x| matches!(x.outcome, Outcome::Failed { .. }))
+        .count();
+    let summary = match (history.len(), failed) {
+        (0, _) => String::from("沒有下載紀錄"),
+        (total, 0) => format!("{total} 筆紀錄"),
+        (total, failed) => format!("{total} 筆紀錄•{failed} 筆失敗"),
+    };
+    rsx! {
+        div {
+            class: "flex items-center gap-[15px]",
+            Hint { css: "font-medium", {summary} }
+            if !history.is_empty() {
+                PillAction {
+                    label: "清除紀錄",
+                    onclick: move |()| error.set(Some(clear_history().map_err(Into::into)))
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn HistoryItem(job: FinishedJob) -> Element {
+    let mut error = use_error_handler();
+    let mut retrying = use_signal(|| false);
+    let picture = job.collection().map(|x| {
+        x.get_collection_owned()
+            .picture_path()
+            .to_string_lossy()
+            .to_string()
+    });
+    let stats = format!(
+        "{} | {} | {}/s",
+        job.total_size.map_or_else(
+            || String::from("大小不明"),
+            |x| x.display_size_from_megabytes()
+        ),
+        format_elapsed(job.duration_ms),
+        job.average_speed()
+            .unwrap_or_default()
+            .display_size_from_megabytes(),
+    );
+    let reason = match &job.outcome {
+        Outcome::Failed { reason } => Some(reason.clone()),
+        _ => None,
+    };
+    let mut expanded = use_signal(|| false);
+    let files = job.files.clone();
+    rsx! {
+        div {
+            Button {
+                roundness: Roundness::Pill,
+                clickable: false,
+                string_placements: rsx! {
+                    div { class: "w-full flex gap-[20px]",
+                        Image { css: "bg-cover bg-white w-[80px] h-[80px] rounded-[10px]",
+                            {picture.unwrap_or_default()}
+                        }
+                        div { class: "w-full flex flex-col justify-start gap-[10px]",
+                            div { class: "flex items-baseline gap-[15px]",
+                                Text { css: "text-[25px] fond-bold", {job.collection_name.clone()} }
+                                Hint { css: "text-base", {job.kind.to_string()} }
+                            }
+                            div { class: "flex gap-[4px]",
+                                Hint { css: "text-base font-semibold", {stats} }
+                                Text { css: "text-base font-semibold",
+                                    {format!("{}•{}", job.outcome, format_relative(job.finished))}
+                                }
+                            }
+                            if let Some(reason) = reason {
+                                Hint { css: "text-[15px] text-red", {reason} }
+                            }
+                        }
+                        div { class: "self-center flex gap-[10px]",
+                            if !files.is_empty() {
+                                PillAction {
+                                    label: if expanded() { "收起" } else { "明細" },
+                                    selected: expanded(),
+                                    onclick: move |()| expanded.toggle()
+                                }
+                            }
+                            if matches!(job.outcome, Outcome::Failed { .. }) {
+                                PillAction {
+                                    label: if retrying() { "重試中" } else { "重試" },
+                                    selected: retrying(),
+                                    onclick: move |()| {
+                                        if retrying() {
+                                            return;
+                                        }
+                                        retrying.set(true);
+                                        let job = job.clone();
+                                        spawn(async move {
+                                            let result = retry(&job).await;
+                                            retrying.set(false);
+                                            error.set(Some(result));
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                extended_css_class: "rounded-[5px]",
+                fill_mode: FillMode::Fill
+            }
+            if expanded() {
+                FileBreakdown { files }
             }
         }
     }
@@ -142,30 +463,79 @@ fn ProgressStateBar() -> Element {
 
 #[component]
 pub fn DownloadProgress() -> Element {
+    let dragging = use_signal(|| None);
+    let state = use_signal(|| (ProgressState::Running, None));
     let progress = DOWNLOAD_PROGRESS()
         .0
         .into_iter()
         .filter(|(_, x)| x.percentages < 100.)
         .map(|(id, progress)| (id.collection_id, progress))
-        .enumerate();
+        .collect::<Vec<_>>();
+    let queue = DOWNLOAD_QUEUE();
+    let find = |collection_id: &CollectionId| {
+        progress
+            .iter()
+            .find(|x| &x.0 == collection_id)
+            .map(|x| x.1.clone())
+    };
+    // a collection's progress belongs to its running job, downloads started outside the queue
+    // are listed after the queue without controls
+    let running = queue
+        .iter()
+        .filter(|x| job_state(&queue, x.id) == Some(JobState::Running))
+        .map(|x| x.collection_id.clone())
+        .collect::<Vec<_>>();
+    let rows =
+        queue
+            .iter()
+            .map(|job| {
+                let progress = match job_state(&queue, job.id) {
+                    Some(JobState::Running) => find(&job.collection_id),
+                    _ => None,
+                };
+                (job.collection_id.clone(), progress, Some(job.clone()))
+            })
+            .chain(progress.iter().filter(|x| !running.contains(&x.0)).map(
+                |(collection_id, progress)| (collection_id.clone(), Some(progress.clone()), None),
+            ))
+            .collect::<Vec<_>>();
+    let first = rows
+        .iter()
+        .find_map(|(collection_id, progress, _)| Some((collection_id.clone(), progress.clone()?)));
+    let history = DOWNLOAD_HISTORY();
     rsx! {
         div {
             class: "flex flex-col gap-[20px]",
-            for (u, (collection_id , progress) ) in progress {
-                if u == 0 {
-                    FirstProgressView {
-                        collection_id: collection_id.clone(),
-                        progress: progress.clone()
-                    }
-                    ProgressStateBar {
-
-                    }
-                }
-                ListItem {
+            if let Some((collection_id, progress)) = first {
+                FirstProgressView {
                     collection_id,
                     progress
                 }
             }
+            if !rows.is_empty() || !history.is_empty() {
+                ProgressStateBar {
+                    state
+                }
+            }
+            match state().0 {
+                ProgressState::Running => rsx! {
+                    for (collection_id , progress , job) in rows {
+                        ListItem {
+                            collection_id,
+                            progress,
+                            job,
+                            dragging
+                        }
+                    }
+                },
+                ProgressState::Finished => rsx! {
+                    for job in history {
+                        HistoryItem {
+                            job
+                        }
+                    }
+                },
+            }
         }
     }
 }
@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
+
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use rust_lib::api::shared_resources::{collection::CollectionId, entry::DOWNLOAD_PROGRESS};
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    collection_stats::unix_now,
+    download_files,
+    download_history::{self, FinishedJob, Outcome},
+    local_mods::local_mods_of,
+    modrinth::{ModrinthFile, ModrinthVersion},
+    ErrorFormatted,
+};
+
+/// How many jobs download at once, the rest wait in queue order.
+const CONCURRENT_JOBS: usize = 1;
+/// How often a running job looks for new files in `mods`, polls come with every progress update.
+const OBSERVE_INTERVAL: Duration = Duration::from_millis(500);
+/// How long a cancelled job's files are looked for again, for writes `rust_lib` had in flight.
+const LATE_WRITES: Duration = Duration::from_secs(3);
+
+/// Downloads started through [`queued`], in priority order.
+pub static DOWNLOAD_QUEUE: GlobalSignal<Vec<QueuedJob>> = GlobalSignal::new(Vec::new);
+/// Holds every job without touching their own pause state.
+pub static PAUSE_ALL: GlobalSignal<bool> = GlobalSignal::new(|| false);
+
+static NEXT_JOB: AtomicU64 = AtomicU64::new(0);
+/// Jobs waiting for their turn or a resume, woken whenever the queue changes.
+static WAKERS: Mutex<BTreeMap<u64, Waker>> = Mutex::new(BTreeMap::new());
+
+#[derive(Snafu, Debug)]
+pub enum QueueError {
+    #[snafu(display("The download for {name} was cancelled"))]
+    Cancelled { name: String },
+}
+
+/// What a job downloads, kept so a failed job can be started again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobKind {
+    Install {
+        slug: String,
+    },
+    Update {
+        mod_name: String,
+        file_name: Option<String>,
+        version: ModrinthVersion,
+    },
+    /// Moving a collection to the version of the upgrade report created at `created`
+    Upgrade {
+        created: u64,
+    },
+    Import {
+        file: ModrinthFile,
+    },
+}
+
+impl std::fmt::Display for JobKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Install { slug } => write!(f, "安裝 {slug}"),
+            Self::Update {
+                mod_name, version, ..
+            } => write!(f, "更新 {mod_name} 至 {}", version.version_number),
+            Self::Upgrade { .. } => f.write_str("升級版本"),
+            Self::Import { file } => write!(f, "下載 {}", file.filename),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedJob {
+    pub id: u64,
+    pub collection_id: CollectionId,
+    pub kind: JobKind,
+    pub paused: bool,
+    cancelled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Running,
+    Waiting,
+    Paused,
+}
+
+impl std::fmt::Display for JobState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Running => "下載中",
+            Self::Waiting => "等待中",
+            Self::Paused => "已暫停",
+        })
+    }
+}
+
+fn wake_all() {
+    let wakers = std::mem::take(&mut *WAKERS.lock().unwrap_or_else(PoisonError::into_inner));
+    for waker in wakers.into_values() {
+        waker.wake();
+    }
+}
+
+fn update_queue(f: impl FnOnce(&mut Vec<QueuedJob>)) {
+    f(&mut DOWNLOAD_QUEUE.write());
+    wake_all();
+}
+
+/// The first unpaused jobs in the queue run, so moving a job up can hold back the one it passes.
+pub fn job_state(queue: &[QueuedJob], id: u64) -> Option<JobState> {
+    let job = queue.iter().find(|x| x.id == id)?;
+    if job.paused || PAUSE_ALL() {
+        return Some(JobState::Paused);
+    }
+    let running = queue
+        .iter()
+        .filter(|x| !x.paused && !x.cancelled)
+        .take(CONCURRENT_JOBS)
+        .any(|x| x.id == id);
+    Some(if running {
+        JobState::Running
+    } else {
+        JobState::Waiting
+    })
+}
+
+/// Holds a job by no longer polling its download. Nothing is torn down, so connections it had
+/// open sit idle and the server may close them, in which case the download fails once resumed
+/// and can be retried from the history.
+pub fn set_paused(id: u64, paused: bool) {
+    update_queue(|queue| {
+        if let Some(job) = queue.iter_mut().find(|x| x.id == id) {
+            job.paused = paused;
+        }
+    });
+}
+
+/// [`set_paused`] for every job at once.
+pub fn set_pause_all(paused: bool) {
+    *PAUSE_ALL.write() = paused;
+    wake_all();
+}
+
+pub fn cancel(id: u64) {
+    update_queue(|queue| {
+        if let Some(job) = queue.iter_mut().find(|x| x.id == id) {
+            job.cancelled = true;
+        }
+    });
+}
+
+/// Moves job `id` to where `before` is, pushing `before` and the rest down.
+pub fn move_job(id: u64, before: u64) {
+    update_queue(|queue| {
+        let Some(from) = queue.iter().position(|x| x.id == id) else {
+            return;
+        };
+        let job = queue.remove(from);
+        let to = queue
+            .iter()
+            .position(|x| x.id == before)
+            .unwrap_or(queue.len());
+        queue.insert(to, job);
+    });
+}
+
+/// What the sidebar and the download page say about the queue.
+pub fn queue_summary() -> String {
+    let queue = DOWNLOAD_QUEUE.read();
+    let paused = queue
+        .iter()
+        .filter(|x| job_state(&queue, x.id) == Some(JobState::Paused))
+        .count();
+    match (queue.len(), paused) {
+        (0, _) => String::from("無下載佇列"),
+        (total, paused) if paused == total => format!("{total} 個下載已暫停"),
+        (total, 0) => format!("{total} 個下載"),
+        (total, paused) => format!("{total} 個下載•{paused} 個已暫停"),
+    }
+}
+
+fn mod_files(game_directory: &Path) -> BTreeSet<String> {
+    std::fs::read_dir(game_directory.join("mods"))
+        .map(|x| {
+            x.filter_map(Result::ok)
+                .map(|x| x.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Removes the queue entry when the job ends, including when its caller drops it.
+struct Entry(u64);
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        let id = self.0;
+        WAKERS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id);
+        update_queue(|queue| queue.retain(|x| x.id != id));
+    }
+}
+
+/// The size `rust_lib` reports for the collection's download, which only covers its own jobs.
+fn reported_size(collection_id: &CollectionId) -> Option<f64> {
+    DOWNLOAD_PROGRESS
+        .read()
+        .0
+        .iter()
+        .find(|(id, _)| &id.collection_id == collection_id)
+        .and_then(|(_, x)| x.total_size)
+}
+
+/// Runs a download for `collection_id` as a queue entry. The future is only polled while it is
+/// the job's turn and nothing pauses it, see [`set_paused`] for what that means for its
+/// connections. A cancelled job is dropped and the files that appeared in the collection's
+/// `mods` directory while it ran are removed, since the collection never records them. Files that
+/// showed up while it waited or was paused, and jars dropped in meanwhile, are left alone. Every
+/// job ends up in the download history with its outcome.
+///
+/// Pausing and cancelling only reach the future itself. Should `rust_lib` hand parts of a
+/// download to tasks of its own, those keep running through a pause and past a cancel, there is
+/// no way to stop them from here. A cancelled job therefore looks at `mods` once more after
+/// [`LATE_WRITES`] and removes what was written since, unless another job of the collection is
+/// queued and the files may be its own. Writes later than that are not caught.
+pub async fn queued<F, T, E>(
+    collection_id: &CollectionId,
+    kind: JobKind,
+    future: F,
+) -> Result<Result<T, E>, QueueError>
+where
+    F: Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    let id = NEXT_JOB.fetch_add(1, Ordering::Relaxed);
+    update_queue(|queue| {
+        queue.push(QueuedJob {
+            id,
+            collection_id: collection_id.clone(),
+            kind: kind.clone(),
+            paused: false,
+            cancelled: false,
+        });
+    });
+    download_files::start(id, &kind);
+    let entry = Entry(id);
+    let collection = collection_id.get_collection_owned();
+    let game_directory = collection.game_directory();
+    // files in `mods` that are not this job's, taken again whenever it gets to run
+    let mut others = BTreeSet::new();
+    let mut owned = BTreeSet::new();
+    let take_new = |others: &BTreeSet<String>, owned: &mut BTreeSet<String>| {
+        let new = mod_files(&game_directory)
+            .into_iter()
+            .filter(|x| !others.contains(x))
+            .collect::<Vec<_>>();
+        owned.extend(new);
+    };
+    let mut total_size = match &kind {
+        JobKind::Import { file } => Some(file.size as f64),
+        _ => None,
+    };
+    let mut running = Duration::ZERO;
+    let mut resumed: Option<Instant> = None;
+    let mut ran = false;
+    let mut observed: Option<Instant> = None;
+    // the future is dropped at the end of the block, before a cancelled job's files are removed
+    let output = {
+        let mut future = std::pin::pin!(future);
+        std::future::poll_fn(|cx| {
+            let queue = DOWNLOAD_QUEUE.read();
+            if queue.iter().any(|x| x.id == id && x.cancelled) {
+                return Poll::Ready(None);
+            }
+            if job_state(&queue, id) == Some(JobState::Running) {
+                drop(queue);
+                if resumed.is_none() {
+                    resumed = Some(Instant::now());
+                    ran = true;
+                    others = &mod_files(&game_directory) - &owned;
+                }
+                if let Some(size) = reported_size(collection_id) {
+                    total_size = Some(size);
+                }
+                if observed.map_or(true, |x| x.elapsed() >= OBSERVE_INTERVAL) {
+                    observed = Some(Instant::now());
+                    take_new(&others, &mut owned);
+                    download_files::observe(id, &owned);
+                }
+                return future.as_mut().poll(cx).map(Some);
+            }
+            if let Some(x) = resumed.take() {
+                running += x.elapsed();
+                take_new(&others, &mut owned);
+            }
+            WAKERS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(id, cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    };
+    if let Some(x) = resumed {
+        running += x.elapsed();
+        take_new(&others, &mut owned);
+    }
+    drop(entry);
+    let outcome = match &output {
+        Some(Ok(_)) => Outcome::Completed,
+        Some(Err(x)) => Outcome::Failed {
+            reason: x.to_string(),
+        },
+        None => Outcome::Cancelled,
+    };
+    let files = download_files::finish(id, &outcome);
+    let finished = FinishedJob {
+        collection_id: collection_id.to_string(),
+        collection_name: collection.display_name().clone(),
+        kind,
+        finished: unix_now(),
+        duration_ms: running.as_millis() as u64,
+        total_size,
+        outcome,
+        files,
+    };
+    if let Err(x) = download_history::record(finished) {
+        error!("{}", x.to_formatted());
+    }
+    match output {
+        Some(output) => Ok(output),
+        None => {
+            let mods = game_directory.join("mods");
+            let remove = |files: &BTreeSet<String>| {
+                // a jar dropped into the collection while the job ran is the user's
+                let dropped = local_mods_of(collection_id)
+                    .into_iter()
+                    .filter_map(|x| {
+                        Some(x.path_in(&mods).file_name()?.to_string_lossy().into_owned())
+                    })
+                    .collect::<BTreeSet<_>>();
+                for file in files.difference(&dropped) {
+                    let path = mods.join(file);
+                    if let Err(x) = std::fs::remove_file(&path) {
+                        error!("Failed to remove {}: {x}", path.display());
+                    }
+                }
+            };
+            remove(&owned);
+            let idle = || {
+                !DOWNLOAD_QUEUE
+                    .read()
+                    .iter()
+                    .any(|x| &x.collection_id == collection_id)
+            };
+            if ran && idle() {
+                let before = mod_files(&game_directory);
+                let next_job = NEXT_JOB.load(Ordering::Relaxed);
+                tokio::time::sleep(LATE_WRITES).await;
+                if NEXT_JOB.load(Ordering::Relaxed) == next_job && idle() {
+                    remove(&(&mod_files(&game_directory) - &before));
+                }
+            }
+            CancelledSnafu {
+                name: collection.display_name().clone(),
+            }
+            .fail()
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+
+use crate::persistence::{self, PersistenceError};
+
+const FAVORITES_FILE: &str = "favorites";
+
+/// Favorite collections as `CollectionId::to_string`, in the order the user arranged them.
+pub static FAVORITES: GlobalSignal<Vec<String>> =
+    GlobalSignal::new(|| persistence::load(FAVORITES_FILE));
+
+fn save() -> Result<(), PersistenceError> {
+    persistence::save(FAVORITES_FILE, &*FAVORITES.read())
+}
+
+pub fn is_favorite(collection_id: &CollectionId) -> bool {
+    FAVORITES.read().contains(&collection_id.to_string())
+}
+
+/// Stars or unstars the collection, new favorites go to the end.
+pub fn toggle_favorite(collection_id: &CollectionId) -> Result<(), PersistenceError> {
+    let key = collection_id.to_string();
+    {
+        let mut favorites = FAVORITES.write();
+        if let Some(position) = favorites.iter().position(|x| *x == key) {
+            favorites.remove(position);
+        } else {
+            favorites.push(key);
+        }
+    }
+    save()
+}
+
+/// Moves a favorite `offset` places, clamped to the ends of the list.
+pub fn move_favorite(collection_id: &CollectionId, offset: isize) -> Result<(), PersistenceError> {
+    let key = collection_id.to_string();
+    {
+        let mut favorites = FAVORITES.write();
+        let Some(position) = favorites.iter().position(|x| *x == key) else {
+            return Ok(());
+        };
+        let target = position
+            .saturating_add_signed(offset)
+            .min(favorites.len() - 1);
+        let moved = favorites.remove(position);
+        favorites.insert(target, moved);
+    }
+    save()
+}
+
+/// The favorites among `keys` in favorite order, collections deleted since are skipped.
+pub fn favorites_of(keys: &[CollectionId]) -> Vec<CollectionId> {
+    FAVORITES
+        .read()
+        .iter()
+        .filter_map(|x| keys.iter().find(|id| id.to_string() == *x).cloned())
+        .collect()
+}
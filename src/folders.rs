@@ -0,0 +1,120 @@
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::persistence::{self, PersistenceError};
+
+const FOLDERS_FILE: &str = "collection_folders";
+
+/// User-defined folders (分類夾), in creation order.
+pub static FOLDERS: GlobalSignal<Vec<Folder>> =
+    GlobalSignal::new(|| persistence::load(FOLDERS_FILE));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter)]
+pub enum FolderColor {
+    #[default]
+    Green,
+    Red,
+    LightBlue,
+    Yellow,
+    Purple,
+    Grey,
+}
+
+impl FolderColor {
+    /// The palette of `tailwind.config.js`, applied as an inline background.
+    pub const fn hex(self) -> &'static str {
+        match self {
+            Self::Green => "#CDE347",
+            Self::Red => "#C92B45",
+            Self::LightBlue => "#7CAED3",
+            Self::Yellow => "#D3E950",
+            Self::Purple => "#9747FF",
+            Self::Grey => "#4F4F4F",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: u64,
+    pub name: String,
+    pub color: FolderColor,
+    /// Members as `CollectionId::to_string`, a collection can be in several folders
+    pub collections: Vec<String>,
+}
+
+fn save() -> Result<(), PersistenceError> {
+    persistence::save(FOLDERS_FILE, &*FOLDERS.read())
+}
+
+fn update_folder(id: u64, update: impl FnOnce(&mut Folder)) -> Result<(), PersistenceError> {
+    if let Some(folder) = FOLDERS.write().iter_mut().find(|x| x.id == id) {
+        update(folder);
+    }
+    save()
+}
+
+pub fn folder(id: u64) -> Option<Folder> {
+    FOLDERS.read().iter().find(|x| x.id == id).cloned()
+}
+
+/// Creates an empty folder named `新分類夾`, numbered when the name is taken.
+pub fn create_folder() -> Result<u64, PersistenceError> {
+    let id = {
+        let mut folders = FOLDERS.write();
+        let id = folders.iter().map(|x| x.id + 1).max().unwrap_or_default();
+        let mut name = String::from("新分類夾");
+        let mut counter = 1;
+        while folders.iter().any(|x| x.name == name) {
+            counter += 1;
+            name = format!("新分類夾 {counter}");
+        }
+        folders.push(Folder {
+            id,
+            name,
+            color: FolderColor::default(),
+            collections: Vec::new(),
+        });
+        id
+    };
+    save()?;
+    Ok(id)
+}
+
+pub fn rename_folder(id: u64, name: String) -> Result<(), PersistenceError> {
+    update_folder(id, |x| x.name = name)
+}
+
+pub fn recolor_folder(id: u64, color: FolderColor) -> Result<(), PersistenceError> {
+    update_folder(id, |x| x.color = color)
+}
+
+/// Deletes the folder, its collections are left untouched.
+pub fn delete_folder(id: u64) -> Result<(), PersistenceError> {
+    FOLDERS.write().retain(|x| x.id != id);
+    save()
+}
+
+pub fn add_to_folder(id: u64, collection_id: &CollectionId) -> Result<(), PersistenceError> {
+    let key = collection_id.to_string();
+    update_folder(id, |x| {
+        if !x.collections.contains(&key) {
+            x.collections.push(key);
+        }
+    })
+}
+
+pub fn remove_from_folder(id: u64, collection_id: &CollectionId) -> Result<(), PersistenceError> {
+    let key = collection_id.to_string();
+    update_folder(id, |x| x.collections.retain(|x| *x != key))
+}
+
+pub fn in_folder(id: u64, collection_id: &CollectionId) -> bool {
+    let key = collection_id.to_string();
+    FOLDERS
+        .read()
+        .iter()
+        .any(|x| x.id == id && x.collections.contains(&key))
+}
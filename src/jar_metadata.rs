@@ -0,0 +1,636 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+#[derive(Snafu, Debug)]
+pub enum JarMetadataError {
+    #[snafu(display("Failed to open {}", path.display()))]
+    Open {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("{} is not a valid jar", path.display()))]
+    Archive {
+        path: PathBuf,
+        source: zip::result::ZipError,
+    },
+    #[snafu(display("Failed to read {entry} in {}", path.display()))]
+    Entry {
+        path: PathBuf,
+        entry: String,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to parse {entry} in {}", path.display()))]
+    Json {
+        path: PathBuf,
+        entry: String,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Failed to parse {entry} in {}", path.display()))]
+    Toml {
+        path: PathBuf,
+        entry: String,
+        source: toml::de::Error,
+    },
+    #[snafu(display("{} has no fabric.mod.json, quilt.mod.json or mods.toml", path.display()))]
+    NotAMod { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LoaderKind {
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+}
+
+impl std::fmt::Display for LoaderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Fabric => "Fabric",
+            Self::Quilt => "Quilt",
+            Self::Forge => "Forge",
+            Self::NeoForge => "NeoForge",
+        })
+    }
+}
+
+impl LoaderKind {
+    pub const ALL: [Self; 4] = [Self::Fabric, Self::Quilt, Self::Forge, Self::NeoForge];
+
+    /// Parses the `Display` output of a collection's mod loader, or a loader name typed by the
+    /// user. A trailing build, `Fabric 0.15.11`, is ignored, and only whole names match.
+    pub fn from_loader_name(name: &str) -> Option<Self> {
+        let name = name.split_whitespace().next()?.to_lowercase();
+        match name.as_str() {
+            "fabric" => Some(Self::Fabric),
+            "quilt" => Some(Self::Quilt),
+            "forge" => Some(Self::Forge),
+            "neoforge" => Some(Self::NeoForge),
+            _ => None,
+        }
+    }
+
+    /// Whether a jar built for `self` runs on a collection using `collection`.
+    /// Quilt loads Fabric mods as well.
+    pub const fn runs_on(self, collection: Self) -> bool {
+        matches!(
+            (self, collection),
+            (Self::Fabric, Self::Fabric | Self::Quilt)
+                | (Self::Quilt, Self::Quilt)
+                | (Self::Forge, Self::Forge)
+                | (Self::NeoForge, Self::NeoForge)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DependencyKind {
+    Required,
+    Optional,
+    Incompatible,
+    /// Loads, but the author warns against combining them (`conflicts`, `discouraged`)
+    Discouraged,
+    /// Shipped inside the jar (jar-in-jar).
+    Embedded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModDependency {
+    pub id: String,
+    pub kind: DependencyKind,
+    /// Version predicates, any of which matches, `>=0.4 <0.5`. Empty for any version.
+    #[serde(default)]
+    pub versions: Vec<String>,
+}
+
+impl ModDependency {
+    /// Whether the declaration covers the other mod at `version`. `None` when the version or
+    /// a predicate can't be compared, a pre-release or `mc1.20-0.5.3` for example.
+    pub fn matches_version(&self, version: Option<&str>) -> Option<bool> {
+        if self.versions.is_empty() || self.versions.iter().any(|x| x.trim() == "*") {
+            return Some(true);
+        }
+        let version = parse_version(version?)?;
+        let mut unknown = false;
+        for predicate in &self.versions {
+            match predicate_matches(predicate, &version) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => unknown = true,
+            }
+        }
+        (!unknown).then_some(false)
+    }
+}
+
+/// What a mod jar says about itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JarMetadata {
+    pub id: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    pub loader: LoaderKind,
+    /// Path of the icon inside the jar
+    pub icon: Option<String>,
+    pub dependencies: Vec<ModDependency>,
+    /// Other ids the mod answers to, as declared by Fabric's and Quilt's `provides`
+    #[serde(default)]
+    pub provides: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct FabricModJson {
+    id: String,
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    authors: Vec<serde_json::Value>,
+    icon: Option<serde_json::Value>,
+    #[serde(default)]
+    depends: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    recommends: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    suggests: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    breaks: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    conflicts: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    jars: Vec<FabricNestedJar>,
+    #[serde(default)]
+    provides: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct FabricNestedJar {
+    file: String,
+}
+
+#[derive(Deserialize)]
+struct QuiltModJson {
+    quilt_loader: QuiltLoader,
+}
+
+#[derive(Deserialize)]
+struct QuiltLoader {
+    id: String,
+    version: Option<String>,
+    #[serde(default)]
+    metadata: QuiltMetadata,
+    #[serde(default)]
+    depends: Vec<serde_json::Value>,
+    #[serde(default)]
+    breaks: Vec<serde_json::Value>,
+    #[serde(default)]
+    jars: Vec<String>,
+    /// Ids, or `{ "id": .. }` objects
+    #[serde(default)]
+    provides: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, Default)]
+struct QuiltMetadata {
+    name: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    contributors: serde_json::Map<String, serde_json::Value>,
+    icon: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsToml {
+    #[serde(default)]
+    mods: Vec<ModsTomlMod>,
+    #[serde(default)]
+    dependencies: std::collections::BTreeMap<String, Vec<ModsTomlDependency>>,
+}
+
+/// `META-INF/jarjar/metadata.json`, Forge's jar-in-jar listing.
+#[derive(Deserialize)]
+struct JarJarMetadata {
+    #[serde(default)]
+    jars: Vec<JarJarEntry>,
+}
+
+#[derive(Deserialize)]
+struct JarJarEntry {
+    identifier: JarJarIdentifier,
+}
+
+#[derive(Deserialize)]
+struct JarJarIdentifier {
+    artifact: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlMod {
+    mod_id: String,
+    version: Option<String>,
+    display_name: Option<String>,
+    description: Option<String>,
+    authors: Option<String>,
+    logo_file: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlDependency {
+    mod_id: String,
+    /// Forge
+    mandatory: Option<bool>,
+    /// NeoForge, `required`, `optional`, `incompatible` or `discouraged`
+    r#type: Option<String>,
+    /// A Maven range, `[0.4,0.5)`
+    version_range: Option<String>,
+}
+
+/// `icon` is either a path or a `{ "size": "path" }` map, in which case the largest is used.
+fn icon_path(icon: Option<serde_json::Value>) -> Option<String> {
+    match icon? {
+        serde_json::Value::String(x) => Some(x),
+        serde_json::Value::Object(x) => x
+            .into_iter()
+            .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or_default())
+            .and_then(|(_, x)| x.as_str().map(ToOwned::to_owned)),
+        _ => None,
+    }
+}
+
+fn read_entry(
+    archive: &mut zip::ZipArchive<std::fs::File>,
+    path: &Path,
+    entry: &str,
+) -> Result<Option<Vec<u8>>, JarMetadataError> {
+    let Ok(mut file) = archive.by_name(entry) else {
+        return Ok(None);
+    };
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)
+        .context(EntrySnafu { path, entry })?;
+    Ok(Some(bytes))
+}
+
+/// Reads a single entry of the jar, used for icons.
+pub fn read_jar_entry(path: &Path, entry: &str) -> Result<Option<Vec<u8>>, JarMetadataError> {
+    let file = std::fs::File::open(path).context(OpenSnafu { path })?;
+    let mut archive = zip::ZipArchive::new(file).context(ArchiveSnafu { path })?;
+    read_entry(&mut archive, path, entry)
+}
+
+impl JarMetadata {
+    pub fn read(path: &Path) -> Result<Self, JarMetadataError> {
+        let file = std::fs::File::open(path).context(OpenSnafu { path })?;
+        let mut archive = zip::ZipArchive::new(file).context(ArchiveSnafu { path })?;
+        if let Some(bytes) = read_entry(&mut archive, path, "quilt.mod.json")? {
+            let json: QuiltModJson = serde_json::from_slice(&bytes).context(JsonSnafu {
+                path,
+                entry: "quilt.mod.json",
+            })?;
+            return Ok(Self::from_quilt(json.quilt_loader));
+        }
+        if let Some(bytes) = read_entry(&mut archive, path, "fabric.mod.json")? {
+            let json: FabricModJson = serde_json::from_slice(&bytes).context(JsonSnafu {
+                path,
+                entry: "fabric.mod.json",
+            })?;
+            return Ok(Self::from_fabric(json));
+        }
+        for (entry, loader) in [
+            ("META-INF/neoforge.mods.toml", LoaderKind::NeoForge),
+            ("META-INF/mods.toml", LoaderKind::Forge),
+        ] {
+            if let Some(bytes) = read_entry(&mut archive, path, entry)? {
+                let text = String::from_utf8_lossy(&bytes);
+                let parsed: ModsToml = toml::from_str(&text).context(TomlSnafu { path, entry })?;
+                let manifest_version = read_entry(&mut archive, path, "META-INF/MANIFEST.MF")?
+                    .and_then(|x| {
+                        String::from_utf8_lossy(&x).lines().find_map(|x| {
+                            x.strip_prefix("Implementation-Version:")
+                                .map(|x| x.trim().to_owned())
+                        })
+                    });
+                let Some(mut metadata) = Self::from_mods_toml(parsed, loader, manifest_version)
+                else {
+                    continue;
+                };
+                let jarjar = "META-INF/jarjar/metadata.json";
+                if let Some(bytes) = read_entry(&mut archive, path, jarjar)? {
+                    let json: JarJarMetadata = serde_json::from_slice(&bytes)
+                        .context(JsonSnafu { path, entry: jarjar })?;
+                    metadata
+                        .dependencies
+                        .extend(json.jars.into_iter().map(|x| ModDependency {
+                            id: x.identifier.artifact,
+                            kind: DependencyKind::Embedded,
+                            versions: Vec::new(),
+                        }));
+                }
+                return Ok(metadata);
+            }
+        }
+        NotAModSnafu { path }.fail()
+    }
+
+    fn from_fabric(json: FabricModJson) -> Self {
+        let mut dependencies = Vec::new();
+        for (map, kind) in [
+            (json.depends, DependencyKind::Required),
+            (json.recommends, DependencyKind::Optional),
+            (json.suggests, DependencyKind::Optional),
+            (json.breaks, DependencyKind::Incompatible),
+            (json.conflicts, DependencyKind::Discouraged),
+        ] {
+            dependencies.extend(map.into_iter().map(|(id, x)| ModDependency {
+                id,
+                kind,
+                versions: version_predicates(x),
+            }));
+        }
+        dependencies.extend(json.jars.into_iter().map(|x| ModDependency {
+            id: nested_jar_id(&x.file),
+            kind: DependencyKind::Embedded,
+            versions: Vec::new(),
+        }));
+        Self {
+            name: json.name.unwrap_or_else(|| json.id.clone()),
+            id: json.id,
+            version: json.version,
+            description: json.description,
+            authors: json
+                .authors
+                .into_iter()
+                .filter_map(|x| match x {
+                    serde_json::Value::String(x) => Some(x),
+                    serde_json::Value::Object(x) => {
+                        x.get("name").and_then(|x| x.as_str()).map(ToOwned::to_owned)
+                    }
+                    _ => None,
+                })
+                .collect(),
+            loader: LoaderKind::Fabric,
+            icon: icon_path(json.icon),
+            dependencies,
+            provides: json.provides,
+        }
+    }
+
+    fn from_quilt(loader: QuiltLoader) -> Self {
+        let dependency = |x: serde_json::Value, kind: DependencyKind| match x {
+            serde_json::Value::String(id) => Some(ModDependency {
+                id,
+                kind,
+                versions: Vec::new(),
+            }),
+            serde_json::Value::Object(x) => {
+                let id = x.get("id")?.as_str()?.to_owned();
+                let optional = x
+                    .get("optional")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false);
+                Some(ModDependency {
+                    id,
+                    kind: if optional && kind == DependencyKind::Required {
+                        DependencyKind::Optional
+                    } else {
+                        kind
+                    },
+                    versions: x
+                        .get("versions")
+                        .cloned()
+                        .map(version_predicates)
+                        .unwrap_or_default(),
+                })
+            }
+            _ => None,
+        };
+        let mut dependencies = loader
+            .depends
+            .into_iter()
+            .filter_map(|x| dependency(x, DependencyKind::Required))
+            .chain(
+                loader
+                    .breaks
+                    .into_iter()
+                    .filter_map(|x| dependency(x, DependencyKind::Incompatible)),
+            )
+            .collect::<Vec<_>>();
+        dependencies.extend(loader.jars.iter().map(|x| ModDependency {
+            id: nested_jar_id(x),
+            kind: DependencyKind::Embedded,
+            versions: Vec::new(),
+        }));
+        Self {
+            name: loader.metadata.name.unwrap_or_else(|| loader.id.clone()),
+            id: loader.id,
+            version: loader.version,
+            description: loader.metadata.description,
+            authors: loader.metadata.contributors.into_iter().map(|x| x.0).collect(),
+            loader: LoaderKind::Quilt,
+            icon: icon_path(loader.metadata.icon),
+            dependencies,
+            provides: loader
+                .provides
+                .into_iter()
+                .filter_map(|x| match x {
+                    serde_json::Value::String(x) => Some(x),
+                    serde_json::Value::Object(x) => {
+                        x.get("id").and_then(|x| x.as_str()).map(ToOwned::to_owned)
+                    }
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    fn from_mods_toml(
+        toml: ModsToml,
+        loader: LoaderKind,
+        manifest_version: Option<String>,
+    ) -> Option<Self> {
+        let first = toml.mods.into_iter().next()?;
+        let dependencies = toml
+            .dependencies
+            .get(&first.mod_id)
+            .into_iter()
+            .flatten()
+            .map(|x| {
+                let kind = match (x.r#type.as_deref(), x.mandatory) {
+                    (Some("incompatible"), _) => DependencyKind::Incompatible,
+                    (Some("discouraged"), _) => DependencyKind::Discouraged,
+                    (Some("required"), _) | (None, Some(true) | None) => DependencyKind::Required,
+                    _ => DependencyKind::Optional,
+                };
+                ModDependency {
+                    id: x.mod_id.clone(),
+                    kind,
+                    versions: x
+                        .version_range
+                        .as_deref()
+                        .map(maven_range_predicates)
+                        .unwrap_or_default(),
+                }
+            })
+            .collect::<Vec<_>>();
+        // NeoForge mods before 1.20.5 still ship a `mods.toml`
+        let loader = if dependencies.iter().any(|x| x.id == "neoforge") {
+            LoaderKind::NeoForge
+        } else {
+            loader
+        };
+        let version = first
+            .version
+            .filter(|x| !x.starts_with("${"))
+            .or(manifest_version);
+        Some(Self {
+            name: first.display_name.unwrap_or_else(|| first.mod_id.clone()),
+            id: first.mod_id,
+            version,
+            description: first.description,
+            authors: first
+                .authors
+                .map(|x| x.split(',').map(|x| x.trim().to_owned()).collect())
+                .unwrap_or_default(),
+            loader,
+            icon: first.logo_file,
+            dependencies,
+            provides: Vec::new(),
+        })
+    }
+}
+
+/// `META-INF/jars/fabric-api-base-0.4.31.jar` -> `fabric-api-base`
+fn nested_jar_id(path: &str) -> String {
+    let file = path.rsplit('/').next().unwrap_or(path).trim_end_matches(".jar");
+    file.split('-')
+        .take_while(|x| !x.starts_with(|c: char| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A Fabric or Quilt version requirement, a predicate or a list of them.
+fn version_predicates(value: serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::String(x) => vec![x],
+        serde_json::Value::Array(x) => x
+            .into_iter()
+            .filter_map(|x| x.as_str().map(ToOwned::to_owned))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `[0.4,0.5)` -> `>=0.4 <0.5`, one predicate per bracketed range. A bare version is only a
+/// recommendation in Maven and allows any.
+fn maven_range_predicates(range: &str) -> Vec<String> {
+    let mut predicates = Vec::new();
+    let mut rest = range;
+    while let Some(start) = rest.find(['[', '(']) {
+        let Some(end) = rest[start..].find([']', ')']).map(|x| x + start) else {
+            break;
+        };
+        let inclusive_lower = rest[start..].starts_with('[');
+        let inclusive_upper = rest[end..].starts_with(']');
+        let inner = &rest[start + 1..end];
+        let predicate = match inner.split_once(',') {
+            None => format!("={}", inner.trim()),
+            Some((lower, upper)) => {
+                let lower = lower.trim();
+                let upper = upper.trim();
+                let mut terms = Vec::new();
+                if !lower.is_empty() {
+                    terms.push(format!(
+                        "{}{lower}",
+                        if inclusive_lower { ">=" } else { ">" }
+                    ));
+                }
+                if !upper.is_empty() {
+                    terms.push(format!(
+                        "{}{upper}",
+                        if inclusive_upper { "<=" } else { "<" }
+                    ));
+                }
+                if terms.is_empty() {
+                    "*".to_owned()
+                } else {
+                    terms.join(" ")
+                }
+            }
+        };
+        predicates.push(predicate);
+        rest = &rest[end + 1..];
+    }
+    predicates
+}
+
+/// `0.5.3+mc1.20.1` -> `[0, 5, 3]`, build metadata is ignored. Pre-releases and versions that
+/// aren't dotted numbers can't be ordered reliably and give `None`.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.split('+').next()?.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    if version.contains('-') {
+        return None;
+    }
+    version.split('.').map(|x| x.parse().ok()).collect()
+}
+
+fn compare_versions(a: &[u64], b: &[u64]) -> std::cmp::Ordering {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|x| x.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// A Fabric predicate, space separated terms that all have to match: `>=1.2 <2`, `~1.2`,
+/// `^1.2`, `1.2.x` or `*`.
+fn predicate_matches(predicate: &str, version: &[u64]) -> Option<bool> {
+    use std::cmp::Ordering;
+    let mut matches = true;
+    for term in predicate.split_whitespace() {
+        if term == "*" {
+            continue;
+        }
+        let operator_len = term
+            .find(|c: char| c.is_ascii_digit() || c == 'v')
+            .unwrap_or(term.len());
+        let (operator, bound) = term.split_at(operator_len);
+        let wildcard = bound.ends_with(['x', 'X', '*']);
+        let bound = parse_version(
+            bound
+                .trim_end_matches(['x', 'X', '*'])
+                .trim_end_matches('.'),
+        )?;
+        let ordering = compare_versions(version, &bound);
+        let next = |index: usize| -> Option<Vec<u64>> {
+            let mut next = bound.get(..=index)?.to_vec();
+            next[index] += 1;
+            Some(next)
+        };
+        matches &= match operator {
+            ">=" => ordering != Ordering::Less,
+            ">" => ordering == Ordering::Greater,
+            "<=" => ordering != Ordering::Greater,
+            "<" => ordering == Ordering::Less,
+            "~" => {
+                let upper = next(1).or_else(|| next(0))?;
+                ordering != Ordering::Less && compare_versions(version, &upper).is_lt()
+            }
+            "^" => ordering != Ordering::Less && compare_versions(version, &next(0)?).is_lt(),
+            "=" | "" if wildcard => bound
+                .iter()
+                .enumerate()
+                .all(|(i, x)| version.get(i).unwrap_or(&0) == x),
+            "=" | "" => ordering == Ordering::Equal,
+            _ => return None,
+        };
+    }
+    Some(matches)
+}
@@ -0,0 +1,541 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use dioxus::prelude::*;
+use rust_lib::api::{
+    backend_exclusive::vanilla::version::VersionMetadata,
+    shared_resources::collection::CollectionId,
+};
+use snafu::prelude::*;
+
+use crate::persistence::{self, PersistenceError};
+
+const OVERRIDES_FILE: &str = "java_overrides";
+const MIRROR_FILE: &str = "java_mirror";
+const ADOPTIUM_API: &str = "https://api.adoptium.net/v3/binary/latest";
+/// Points at a directory for `LocalDirectory` to serve runtimes from instead of Adoptium, when
+/// none is set in the settings.
+const MIRROR_VARIABLE: &str = "PRAG_PORTAL_JAVA_MIRROR";
+#[cfg(windows)]
+const JAVA_BINARY: &str = "java.exe";
+#[cfg(not(windows))]
+const JAVA_BINARY: &str = "java";
+
+/// Runtime homes picked by hand, keyed by `CollectionId::to_string`.
+pub static JAVA_OVERRIDES: GlobalSignal<BTreeMap<String, PathBuf>> =
+    GlobalSignal::new(|| persistence::load(OVERRIDES_FILE));
+
+/// Directory `LocalDirectory` serves runtimes from instead of Adoptium, `None` downloads them.
+pub static JAVA_MIRROR: GlobalSignal<Option<PathBuf>> =
+    GlobalSignal::new(|| persistence::load(MIRROR_FILE));
+
+/// The runtimes of the last detection, `None` until one finished. Detecting runs `java` for
+/// runtimes without a `release` file, so it is done once and reused.
+pub static RUNTIMES: GlobalSignal<Option<Vec<JavaRuntime>>> = GlobalSignal::new(|| None);
+
+#[derive(Snafu, Debug)]
+pub enum JavaError {
+    #[snafu(display("Failed to access {}", path.display()))]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to reach {url}"))]
+    Request { url: String, source: reqwest::Error },
+    #[snafu(display("Failed to extract {name}"))]
+    Zip {
+        name: String,
+        source: zip::result::ZipError,
+    },
+    #[snafu(display("{name} is neither a .zip nor a .tar.gz"))]
+    ArchiveFormat { name: String },
+    #[snafu(display("{name} does not contain a Java runtime"))]
+    NoRuntime { name: String },
+    #[snafu(display("No Java {major} runtime is available for this platform"))]
+    Unavailable { major: u32 },
+    #[snafu(transparent)]
+    Persistence { source: PersistenceError },
+}
+
+/// Where a runtime was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RuntimeOrigin {
+    Managed,
+    JavaHome,
+    Path,
+    System,
+    Sdkman,
+}
+
+impl std::fmt::Display for RuntimeOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Managed => "由啟動器下載",
+            Self::JavaHome => "JAVA_HOME",
+            Self::Path => "PATH",
+            Self::System => "系統安裝",
+            Self::Sdkman => "SDKMAN",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaRuntime {
+    pub home: PathBuf,
+    /// As the runtime reports it, `1.8.0_392` or `21.0.2`
+    pub version: String,
+    pub major: u32,
+    pub vendor: String,
+    pub origin: RuntimeOrigin,
+}
+
+impl JavaRuntime {
+    pub fn binary(&self) -> PathBuf {
+        self.home.join("bin").join(JAVA_BINARY)
+    }
+}
+
+/// `1.8.0_392` -> 8, `17.0.9` -> 17, `21` -> 21.
+pub fn major_of(version: &str) -> Option<u32> {
+    let mut parts = version.split(['.', '_', '-', '+']);
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// Version and vendor from the `release` file every JDK and most JREs ship with.
+fn parse_release(release: &str) -> Option<(String, String)> {
+    let field = |name: &str| {
+        release
+            .lines()
+            .find_map(|x| x.strip_prefix(name)?.strip_prefix('='))
+            .map(|x| x.trim().trim_matches('"').to_owned())
+    };
+    let version = field("JAVA_VERSION")?;
+    let vendor = field("IMPLEMENTOR")
+        .or_else(|| field("JAVA_VENDOR"))
+        .unwrap_or_else(|| String::from("Unknown"));
+    Some((version, vendor))
+}
+
+/// Version and vendor from `java -XshowSettings:properties -version`, for runtimes without a
+/// `release` file.
+fn query_runtime(java: &Path) -> Option<(String, String)> {
+    let output = std::process::Command::new(java)
+        .args(["-XshowSettings:properties", "-version"])
+        .output()
+        .ok()?;
+    // the JVM prints its settings to stderr
+    let output = String::from_utf8_lossy(&output.stderr);
+    let property = |name: &str| {
+        output
+            .lines()
+            .find_map(|x| x.trim().strip_prefix(name)?.trim_start().strip_prefix('='))
+            .map(|x| x.trim().to_owned())
+    };
+    let version = property("java.version")?;
+    let vendor = property("java.vendor").unwrap_or_else(|| String::from("Unknown"));
+    Some((version, vendor))
+}
+
+fn inspect(home: &Path, origin: RuntimeOrigin) -> Option<JavaRuntime> {
+    let java = home.join("bin").join(JAVA_BINARY);
+    if !java.is_file() {
+        return None;
+    }
+    let (version, vendor) = std::fs::read_to_string(home.join("release"))
+        .ok()
+        .as_deref()
+        .and_then(parse_release)
+        .or_else(|| query_runtime(&java))?;
+    Some(JavaRuntime {
+        home: home.to_owned(),
+        major: major_of(&version)?,
+        version,
+        vendor,
+        origin,
+    })
+}
+
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|x| Some(x.ok()?.path()))
+        .filter(|x| x.is_dir())
+        .collect()
+}
+
+/// Runtimes downloaded by the launcher, one directory per major version.
+fn managed_dir() -> PathBuf {
+    persistence::data_dir().join("runtimes")
+}
+
+fn candidate_homes() -> Vec<(PathBuf, RuntimeOrigin)> {
+    let mut homes = subdirectories(&managed_dir())
+        .into_iter()
+        .map(|x| (x, RuntimeOrigin::Managed))
+        .collect::<Vec<_>>();
+    if let Some(home) = std::env::var_os("JAVA_HOME") {
+        homes.push((PathBuf::from(home), RuntimeOrigin::JavaHome));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path) {
+            // `/usr/bin/java` is usually a chain of symlinks into the real home
+            let Ok(java) = dir.join(JAVA_BINARY).canonicalize() else {
+                continue;
+            };
+            if let Some(home) = java.parent().and_then(Path::parent) {
+                homes.push((home.to_owned(), RuntimeOrigin::Path));
+            }
+        }
+    }
+    homes.extend(
+        subdirectories(Path::new("/usr/lib/jvm"))
+            .into_iter()
+            .map(|x| (x, RuntimeOrigin::System)),
+    );
+    let sdkman = std::env::var_os("SDKMAN_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|x| x.join(".sdkman")));
+    if let Some(sdkman) = sdkman {
+        homes.extend(
+            subdirectories(&sdkman.join("candidates").join("java"))
+                .into_iter()
+                .map(|x| (x, RuntimeOrigin::Sdkman)),
+        );
+    }
+    homes
+}
+
+/// Every runtime on this machine, newest major first. A runtime reachable in several ways is
+/// listed once, under the first way it was found.
+fn detect_runtimes() -> Vec<JavaRuntime> {
+    let mut seen = Vec::new();
+    let mut runtimes = Vec::new();
+    for (home, origin) in candidate_homes() {
+        let Ok(canonical) = home.canonicalize() else {
+            continue;
+        };
+        if seen.contains(&canonical) {
+            continue;
+        }
+        seen.push(canonical);
+        runtimes.extend(inspect(&home, origin));
+    }
+    runtimes.sort_by(|a, b| b.major.cmp(&a.major).then(a.origin.cmp(&b.origin)));
+    runtimes
+}
+
+/// Detects the runtimes again on a blocking thread and keeps them in [`RUNTIMES`].
+pub async fn redetect_runtimes() -> Vec<JavaRuntime> {
+    let runtimes = tokio::task::spawn_blocking(detect_runtimes)
+        .await
+        .unwrap_or_default();
+    *RUNTIMES.write() = Some(runtimes.clone());
+    runtimes
+}
+
+/// The detected runtimes, detecting them first if that never happened.
+pub async fn runtimes() -> Vec<JavaRuntime> {
+    let detected = RUNTIMES.peek().clone();
+    match detected {
+        Some(x) => x,
+        None => redetect_runtimes().await,
+    }
+}
+
+/// [`RUNTIMES`] for a component, starting the first detection if needed. `None` while it runs.
+pub fn use_runtimes() -> Option<Vec<JavaRuntime>> {
+    use_future(|| async {
+        runtimes().await;
+    });
+    RUNTIMES.read().clone()
+}
+
+/// Release ids as `(minor, patch)` of `1.minor.patch`, snapshots as `(year, week)` of `YYwWWa`.
+enum GameVersion {
+    Release(u32, u32),
+    Snapshot(u32, u32),
+}
+
+fn parse_game_version(id: &str) -> Option<GameVersion> {
+    if let Some(rest) = id.strip_prefix("1.") {
+        let mut parts = rest.split(|x: char| !x.is_ascii_digit());
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next().and_then(|x| x.parse().ok()).unwrap_or(0);
+        return Some(GameVersion::Release(minor, patch));
+    }
+    let (year, week) = id.split_once('w')?;
+    let week = week.trim_end_matches(|x: char| x.is_ascii_alphabetic());
+    Some(GameVersion::Snapshot(
+        year.parse().ok()?,
+        week.parse().ok()?,
+    ))
+}
+
+/// The Java major the game version was built for. Unknown ids get the newest one.
+pub fn required_major(version: &VersionMetadata) -> u32 {
+    match parse_game_version(&version.id) {
+        // 1.20.5 and 24w14a moved to 21, 1.17 and 21w19a moved to 16 which 17 covers
+        Some(GameVersion::Release(minor, patch)) if (minor, patch) >= (20, 5) => 21,
+        Some(GameVersion::Release(minor, _)) if minor >= 17 => 17,
+        Some(GameVersion::Release(..)) => 8,
+        Some(GameVersion::Snapshot(year, week)) if (year, week) >= (24, 14) => 21,
+        Some(GameVersion::Snapshot(year, week)) if (year, week) >= (21, 19) => 17,
+        Some(GameVersion::Snapshot(..)) => 8,
+        None => 21,
+    }
+}
+
+/// The runtime best suited to `major`, the same major or else the closest newer one. Java 8
+/// games are picky, so they only ever get a Java 8.
+pub fn best_runtime(runtimes: &[JavaRuntime], major: u32) -> Option<JavaRuntime> {
+    runtimes
+        .iter()
+        .filter(|x| x.major == major || (major > 8 && x.major > major))
+        .min_by_key(|x| (x.major, x.origin))
+        .cloned()
+}
+
+pub fn runtime_override(collection_id: &CollectionId) -> Option<PathBuf> {
+    JAVA_OVERRIDES
+        .read()
+        .get(&collection_id.to_string())
+        .cloned()
+}
+
+/// Pins the collection to the runtime at `home`, `None` goes back to picking automatically.
+pub fn set_runtime_override(
+    collection_id: &CollectionId,
+    home: Option<PathBuf>,
+) -> Result<(), PersistenceError> {
+    {
+        let mut overrides = JAVA_OVERRIDES.write();
+        match home {
+            Some(home) => overrides.insert(collection_id.to_string(), home),
+            None => overrides.remove(&collection_id.to_string()),
+        };
+    }
+    persistence::save(OVERRIDES_FILE, &*JAVA_OVERRIDES.read())
+}
+
+/// The runtime the collection launches with, the override when it still exists.
+pub fn runtime_for(collection_id: &CollectionId, runtimes: &[JavaRuntime]) -> Option<JavaRuntime> {
+    if let Some(home) = runtime_override(collection_id) {
+        if let Some(runtime) = runtimes.iter().find(|x| x.home == home) {
+            return Some(runtime.clone());
+        }
+    }
+    let major = required_major(collection_id.get_collection_owned().minecraft_version());
+    best_runtime(runtimes, major)
+}
+
+/// A downloaded runtime, still packed.
+pub struct RuntimeArchive {
+    /// Tells the format apart, `.zip` or `.tar.gz`
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Where runtimes are downloaded from.
+pub trait RuntimeSource {
+    fn fetch(&self, major: u32) -> impl Future<Output = Result<RuntimeArchive, JavaError>> + Send;
+}
+
+/// Eclipse Temurin builds from the Adoptium API.
+pub struct Adoptium;
+
+impl Adoptium {
+    fn platform() -> Option<(&'static str, &'static str)> {
+        let os = match std::env::consts::OS {
+            "linux" => "linux",
+            "macos" => "mac",
+            "windows" => "windows",
+            _ => return None,
+        };
+        let arch = match std::env::consts::ARCH {
+            "x86_64" => "x64",
+            "aarch64" => "aarch64",
+            _ => return None,
+        };
+        Some((os, arch))
+    }
+}
+
+impl RuntimeSource for Adoptium {
+    async fn fetch(&self, major: u32) -> Result<RuntimeArchive, JavaError> {
+        let (os, arch) = Self::platform().context(UnavailableSnafu { major })?;
+        let url = format!("{ADOPTIUM_API}/{major}/ga/{os}/{arch}/jre/hotspot/normal/eclipse");
+        let response = reqwest::get(&url)
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context(RequestSnafu { url: &url })?;
+        // the binary endpoint redirects to the archive itself, named after the build
+        let name = response
+            .url()
+            .path_segments()
+            .and_then(|mut x| x.next_back().map(ToOwned::to_owned))
+            .unwrap_or_default();
+        let bytes = response.bytes().await.context(RequestSnafu { url: &url })?;
+        Ok(RuntimeArchive {
+            name,
+            bytes: bytes.to_vec(),
+        })
+    }
+}
+
+/// Serves `{major}.zip` or `{major}.tar.gz` out of a directory, for offline mirrors.
+pub struct LocalDirectory(pub PathBuf);
+
+impl RuntimeSource for LocalDirectory {
+    async fn fetch(&self, major: u32) -> Result<RuntimeArchive, JavaError> {
+        for name in [format!("{major}.zip"), format!("{major}.tar.gz")] {
+            let path = self.0.join(&name);
+            if path.is_file() {
+                let bytes = std::fs::read(&path).context(IoSnafu { path })?;
+                return Ok(RuntimeArchive { name, bytes });
+            }
+        }
+        UnavailableSnafu { major }.fail()
+    }
+}
+
+fn extract(archive: &RuntimeArchive, to: &Path) -> Result<(), JavaError> {
+    let name = &archive.name;
+    if name.ends_with(".zip") {
+        zip::ZipArchive::new(std::io::Cursor::new(&archive.bytes))
+            .and_then(|mut x| x.extract(to))
+            .context(ZipSnafu { name })
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        let decoder = flate2::read::GzDecoder::new(archive.bytes.as_slice());
+        tar::Archive::new(decoder)
+            .unpack(to)
+            .context(IoSnafu { path: to })
+    } else {
+        ArchiveFormatSnafu { name }.fail()
+    }
+}
+
+/// Archives wrap the home in a directory named after the build, and macOS ones nest it further
+/// in `Contents/Home`.
+fn find_home(dir: &Path) -> Option<PathBuf> {
+    if dir.join("bin").join(JAVA_BINARY).is_file() {
+        return Some(dir.to_owned());
+    }
+    let mac = dir.join("Contents").join("Home");
+    if mac.join("bin").join(JAVA_BINARY).is_file() {
+        return Some(mac);
+    }
+    subdirectories(dir).iter().find_map(|x| find_home(x))
+}
+
+/// Downloads a `major` runtime from `source` into the launcher's runtime directory, replacing
+/// the one it had.
+pub async fn install_runtime(
+    source: &impl RuntimeSource,
+    major: u32,
+) -> Result<JavaRuntime, JavaError> {
+    install_into(source, major, &managed_dir()).await
+}
+
+async fn install_into(
+    source: &impl RuntimeSource,
+    major: u32,
+    root: &Path,
+) -> Result<JavaRuntime, JavaError> {
+    let archive = source.fetch(major).await?;
+    let staging = root.join(format!(".{major}-staging"));
+    let target = root.join(major.to_string());
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging).context(IoSnafu { path: &staging })?;
+    extract(&archive, &staging)?;
+    let home = find_home(&staging).context(NoRuntimeSnafu {
+        name: &archive.name,
+    })?;
+    if target.exists() {
+        std::fs::remove_dir_all(&target).context(IoSnafu { path: &target })?;
+    }
+    std::fs::rename(&home, &target).context(IoSnafu { path: &target })?;
+    let _ = std::fs::remove_dir_all(&staging);
+    #[cfg(unix)]
+    {
+        // zip archives lose the executable bit
+        use std::os::unix::fs::PermissionsExt;
+        let java = target.join("bin").join(JAVA_BINARY);
+        std::fs::set_permissions(&java, std::fs::Permissions::from_mode(0o755))
+            .context(IoSnafu { path: &java })?;
+    }
+    inspect(&target, RuntimeOrigin::Managed).context(NoRuntimeSnafu { name: archive.name })
+}
+
+pub fn set_java_mirror(dir: Option<PathBuf>) -> Result<(), PersistenceError> {
+    *JAVA_MIRROR.write() = dir;
+    persistence::save(MIRROR_FILE, &*JAVA_MIRROR.read())
+}
+
+/// The mirror directory set in the settings, or else through `MIRROR_VARIABLE`.
+pub fn java_mirror() -> Option<PathBuf> {
+    JAVA_MIRROR
+        .read()
+        .clone()
+        .or_else(|| std::env::var_os(MIRROR_VARIABLE).map(PathBuf::from))
+}
+
+/// `install_runtime` from the mirror directory when there is one, Adoptium otherwise. The new
+/// runtime is detected along with the others.
+pub async fn download_runtime(major: u32) -> Result<JavaRuntime, JavaError> {
+    let runtime = match java_mirror() {
+        Some(dir) => install_runtime(&LocalDirectory(dir), major).await?,
+        None => install_runtime(&Adoptium, major).await?,
+    };
+    redetect_runtimes().await;
+    Ok(runtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn runtime_zip() -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(format!("jdk-21.0.2+13/bin/{JAVA_BINARY}"), options)
+            .unwrap();
+        zip.start_file("jdk-21.0.2+13/release", options).unwrap();
+        zip.write_all(b"IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"21.0.2\"\n")
+            .unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn installs_from_local_directory() {
+        let dir = std::env::temp_dir().join(format!("java-runtimes-{}", std::process::id()));
+        let mirror = dir.join("mirror");
+        let root = dir.join("runtimes");
+        std::fs::create_dir_all(&mirror).unwrap();
+        std::fs::write(mirror.join("21.zip"), runtime_zip()).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let installed = runtime
+            .block_on(install_into(&LocalDirectory(mirror.clone()), 21, &root))
+            .unwrap();
+        assert_eq!(installed.major, 21);
+        assert_eq!(installed.version, "21.0.2");
+        assert_eq!(installed.vendor, "Eclipse Adoptium");
+        assert_eq!(installed.origin, RuntimeOrigin::Managed);
+        assert_eq!(installed.home, root.join("21"));
+        assert!(!root.join(".21-staging").exists());
+
+        let missing = runtime.block_on(install_into(&LocalDirectory(mirror), 17, &root));
+        assert!(matches!(missing, Err(JavaError::Unavailable { major: 17 })));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
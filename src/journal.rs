@@ -0,0 +1,458 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use rust_lib::api::shared_resources::collection::{Collection, CollectionId};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collection_stats::unix_now,
+    loaders::loader_label,
+    local_mods::{local_mods_of, remove_local_mod},
+    mod_actions::{remove_mod, set_mod_enabled},
+    mod_index::{scan_jars, ModIndex},
+    persistence,
+    snapshots::{list_snapshots, Snapshot},
+    ErrorFormatted,
+};
+
+const JOURNAL_FILE: &str = "collection_journal";
+/// Consecutive renames by the same author within this window are merged into one entry, so
+/// typing a name does not produce one entry per keystroke.
+const MERGE_WINDOW_SECS: u64 = 60;
+
+/// Change history of every collection, keyed by `CollectionId::to_string`, oldest first.
+pub static JOURNAL: GlobalSignal<BTreeMap<String, Vec<JournalEntry>>> =
+    GlobalSignal::new(|| persistence::load(JOURNAL_FILE));
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Unix timestamp in seconds
+    pub timestamp: u64,
+    pub author: String,
+    pub change: Change,
+    /// `created` of the snapshot taken right before the change, restoring it reverts what can't
+    /// be undone in place
+    #[serde(default)]
+    pub snapshot: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Change {
+    Rename { from: String, to: String },
+    Picture { from: PathBuf, to: PathBuf },
+    ModAdded { name: String },
+    ModRemoved { name: String },
+    ModUpdated { name: String, from: Option<String>, to: Option<String> },
+    ModToggled { name: String, enabled: bool },
+    Version { from: String, to: String },
+    Loader { from: Option<String>, to: Option<String> },
+    /// Several changes made by one bulk action, undone together
+    Bulk {
+        description: String,
+        changes: Vec<Change>,
+    },
+}
+
+/// The parts of a collection the journal tracks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionState {
+    pub display_name: String,
+    pub picture_path: PathBuf,
+    pub version: String,
+    pub loader: Option<String>,
+    /// `(name, version, enabled)`
+    pub mods: Vec<(String, Option<String>, bool)>,
+}
+
+impl CollectionState {
+    pub fn capture(collection_id: &CollectionId) -> Self {
+        let collection = collection_id.get_collection_owned();
+        Self {
+            display_name: collection.display_name().clone(),
+            picture_path: collection.picture_path().to_path_buf(),
+            version: collection.minecraft_version().id.clone(),
+            loader: loader_label(collection_id),
+            mods: collection
+                .mod_controller()
+                .map(|x| {
+                    x.manager
+                        .mods
+                        .iter()
+                        .map(|x| (x.name.clone(), x.mod_version.clone(), x.enabled))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn diff(&self, after: &Self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        if self.display_name != after.display_name {
+            changes.push(Change::Rename {
+                from: self.display_name.clone(),
+                to: after.display_name.clone(),
+            });
+        }
+        if self.picture_path != after.picture_path {
+            changes.push(Change::Picture {
+                from: self.picture_path.clone(),
+                to: after.picture_path.clone(),
+            });
+        }
+        if self.version != after.version {
+            changes.push(Change::Version {
+                from: self.version.clone(),
+                to: after.version.clone(),
+            });
+        }
+        if self.loader != after.loader {
+            changes.push(Change::Loader {
+                from: self.loader.clone(),
+                to: after.loader.clone(),
+            });
+        }
+        for (name, version, enabled) in &after.mods {
+            match self.mods.iter().find(|x| &x.0 == name) {
+                None => changes.push(Change::ModAdded { name: name.clone() }),
+                Some((_, old_version, old_enabled)) => {
+                    if old_version != version {
+                        changes.push(Change::ModUpdated {
+                            name: name.clone(),
+                            from: old_version.clone(),
+                            to: version.clone(),
+                        });
+                    }
+                    if old_enabled != enabled {
+                        changes.push(Change::ModToggled {
+                            name: name.clone(),
+                            enabled: *enabled,
+                        });
+                    }
+                }
+            }
+        }
+        for (name, ..) in &self.mods {
+            if after.mods.iter().all(|x| &x.0 != name) {
+                changes.push(Change::ModRemoved { name: name.clone() });
+            }
+        }
+        changes
+    }
+}
+
+fn current_author() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("unknown"))
+}
+
+/// `rust_lib`'s `with_mut_collection`, with whatever `mutation` changed appended to the journal.
+/// Collections are changed through this or [`replace_collection`], never directly, so that
+/// every change ends up in the journal.
+pub fn with_mut_collection<T>(
+    collection_id: &CollectionId,
+    mutation: impl FnOnce(&mut Collection) -> T,
+) -> anyhow::Result<T> {
+    journaled(collection_id, || {
+        Ok(collection_id.with_mut_collection(mutation)?)
+    })
+}
+
+/// Puts back a collection `rust_lib` worked on, journaling what it changed against the
+/// collection as it is now.
+pub fn replace_collection(
+    collection_id: &CollectionId,
+    collection: Collection,
+) -> anyhow::Result<()> {
+    journaled(collection_id, || {
+        *collection_id.try_get_raw_mut_collection()? = collection;
+        Ok(())
+    })
+}
+
+/// Runs `mutation` and appends whatever it changed to the collection's journal.
+fn journaled<T, E>(
+    collection_id: &CollectionId,
+    mutation: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let before = CollectionState::capture(collection_id);
+    let result = mutation()?;
+    record_since(collection_id, &before);
+    Ok(result)
+}
+
+/// Appends every change between `before` and the collection's current state.
+fn record_since(collection_id: &CollectionId, before: &CollectionState) {
+    let changes = before.diff(&CollectionState::capture(collection_id));
+    if !changes.is_empty() {
+        append_all(collection_id, changes);
+    }
+}
+
+/// Records a change that can't be observed through [`CollectionState`], such as a jar
+/// copied straight into the mods directory.
+pub fn append(collection_id: &CollectionId, change: Change) {
+    append_all(collection_id, vec![change]);
+}
+
+/// Number of journal entries of the collection, the `start` of a later [`collapse_since`].
+pub fn entry_count(collection_id: &CollectionId) -> usize {
+    JOURNAL
+        .read()
+        .get(&collection_id.to_string())
+        .map_or(0, Vec::len)
+}
+
+/// Folds every entry appended after `start` into one [`Change::Bulk`], so a bulk action is a
+/// single step in the history and is undone as a whole.
+pub fn collapse_since(collection_id: &CollectionId, start: usize, description: String) {
+    let mut journal = JOURNAL.write();
+    let Some(entries) = journal.get_mut(&collection_id.to_string()) else {
+        return;
+    };
+    if entries.len() <= start + 1 {
+        return;
+    }
+    let folded = entries.split_off(start);
+    entries.push(JournalEntry {
+        timestamp: folded.last().map_or_else(unix_now, |x| x.timestamp),
+        author: folded[0].author.clone(),
+        // the oldest snapshot is the state before all of it
+        snapshot: folded.iter().find_map(|x| x.snapshot),
+        change: Change::Bulk {
+            description,
+            changes: folded.into_iter().map(|x| x.change).collect(),
+        },
+    });
+    if let Err(x) = persistence::save(JOURNAL_FILE, &*journal) {
+        error!("{}", x.to_formatted());
+    }
+}
+
+/// Marks the entries appended after `start` as reverted by restoring the snapshot `created`,
+/// which was taken right before them.
+pub fn attach_snapshot(collection_id: &CollectionId, start: usize, created: u64) {
+    let mut journal = JOURNAL.write();
+    let Some(entries) = journal.get_mut(&collection_id.to_string()) else {
+        return;
+    };
+    for entry in entries.iter_mut().skip(start) {
+        entry.snapshot.get_or_insert(created);
+    }
+    if let Err(x) = persistence::save(JOURNAL_FILE, &*journal) {
+        error!("{}", x.to_formatted());
+    }
+}
+
+fn append_all(collection_id: &CollectionId, changes: Vec<Change>) {
+    let author = current_author();
+    let timestamp = unix_now();
+    let mut journal = JOURNAL.write();
+    let entries = journal.entry(collection_id.to_string()).or_default();
+    for change in changes {
+        if let (
+            Change::Rename { to, .. },
+            Some(JournalEntry {
+                timestamp: last_timestamp,
+                author: last_author,
+                change: Change::Rename { to: last_to, .. },
+                ..
+            }),
+        ) = (&change, entries.last_mut())
+        {
+            if *last_author == author
+                && timestamp.saturating_sub(*last_timestamp) < MERGE_WINDOW_SECS
+            {
+                last_to.clone_from(to);
+                *last_timestamp = timestamp;
+                continue;
+            }
+        }
+        entries.push(JournalEntry {
+            timestamp,
+            author: author.clone(),
+            change,
+            snapshot: None,
+        });
+    }
+    if let Err(x) = persistence::save(JOURNAL_FILE, &*journal) {
+        error!("{}", x.to_formatted());
+    }
+}
+
+impl Change {
+    /// Whether [`Change::revert`] can undo it without a snapshot.
+    pub fn reverts_in_place(&self) -> bool {
+        match self {
+            Self::Bulk { changes, .. } => {
+                !changes.is_empty() && changes.iter().all(Self::reverts_in_place)
+            }
+            _ => matches!(
+                self,
+                Self::Rename { .. }
+                    | Self::Picture { .. }
+                    | Self::ModToggled { .. }
+                    | Self::ModAdded { .. }
+            ),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Rename { from, to } => format!("將名稱從「{from}」改為「{to}」"),
+            Self::Picture { .. } => String::from("更換了封面圖片"),
+            Self::ModAdded { name } => format!("新增模組 {name}"),
+            Self::ModRemoved { name } => format!("移除模組 {name}"),
+            Self::ModUpdated { name, from, to } => format!(
+                "更新模組 {name} ({} → {})",
+                from.as_deref().unwrap_or("?"),
+                to.as_deref().unwrap_or("?")
+            ),
+            Self::ModToggled { name, enabled: true } => format!("啟用模組 {name}"),
+            Self::ModToggled { name, enabled: false } => format!("停用模組 {name}"),
+            Self::Version { from, to } => format!("將遊戲版本從 {from} 改為 {to}"),
+            Self::Loader { from, to } => format!(
+                "將模組載入器從 {} 改為 {}",
+                from.as_deref().unwrap_or("無"),
+                to.as_deref().unwrap_or("無")
+            ),
+            Self::Bulk {
+                description,
+                changes,
+            } => format!("{description}（{} 項變更）", changes.len()),
+        }
+    }
+
+    /// Undoes the change in place, the revert itself is journaled like any other mutation.
+    pub fn revert(&self, collection_id: &CollectionId) -> anyhow::Result<()> {
+        match self.clone() {
+            Self::Rename { from, .. } => {
+                with_mut_collection(collection_id, |x| *x.display_name = from)?;
+            }
+            Self::Picture { from, .. } => {
+                with_mut_collection(collection_id, |x| *x.picture_path = from)?;
+            }
+            Self::ModToggled { name, enabled } => {
+                set_mod_enabled(collection_id, &name, !enabled)?;
+            }
+            Self::ModAdded { name } => remove_added(collection_id, &name)?,
+            Self::Bulk {
+                description,
+                changes,
+            } => {
+                let start = entry_count(collection_id);
+                for change in changes.iter().rev() {
+                    change.revert(collection_id)?;
+                }
+                collapse_since(collection_id, start, format!("復原「{description}」"));
+            }
+            _ => anyhow::bail!("this change can not be reverted"),
+        }
+        Ok(())
+    }
+}
+
+/// Removes a mod by the name it was journaled under, a dropped jar or a Modrinth mod.
+fn remove_added(collection_id: &CollectionId, name: &str) -> anyhow::Result<()> {
+    if let Some(local) = local_mods_of(collection_id)
+        .into_iter()
+        .find(|x| x.metadata.name == name)
+    {
+        remove_local_mod(collection_id, &local.metadata.id)?;
+        return Ok(());
+    }
+    let index = ModIndex {
+        jars: scan_jars(collection_id)?,
+        ..Default::default()
+    };
+    let file_name = index.find(name).map(|x| x.file_name.clone());
+    remove_mod(collection_id, name, file_name.as_deref())
+}
+
+impl JournalEntry {
+    /// `snapshots` are the collection's, as [`list_snapshots`] returns them.
+    pub fn is_revertible(&self, snapshots: &[Snapshot]) -> bool {
+        match self.snapshot {
+            Some(created) => snapshots.iter().any(|x| x.created == created),
+            None => self.change.reverts_in_place(),
+        }
+    }
+
+    /// Whether [`JournalEntry::revert`] restores a snapshot rather than undoing it in place.
+    /// An attached snapshot always wins, a bulk change journals its parts only roughly.
+    pub fn reverts_through_snapshot(&self) -> bool {
+        self.snapshot.is_some() || !self.change.reverts_in_place()
+    }
+
+    /// Undoes the entry by restoring the snapshot taken before it, which also undoes whatever
+    /// changed in the collection since, or in place when there is none.
+    pub fn revert(&self, collection_id: &CollectionId) -> anyhow::Result<()> {
+        if !self.reverts_through_snapshot() {
+            return self.change.revert(collection_id);
+        }
+        let created = self
+            .snapshot
+            .ok_or_else(|| anyhow::anyhow!("this change can not be reverted"))?;
+        let snapshot = list_snapshots(collection_id)?
+            .into_iter()
+            .find(|x| x.created == created)
+            .ok_or_else(|| anyhow::anyhow!("the snapshot taken before this change is gone"))?;
+        snapshot.restore(collection_id)?;
+        Ok(())
+    }
+}
+
+/// How a selection of journal entries is reverted, by index into the collection's entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevertPlan {
+    /// The oldest selected entry that reverts through its snapshot, restoring it also undoes
+    /// every later entry
+    pub restore: Option<usize>,
+    /// Selected entries older than `restore`, undone in place newest first
+    pub in_place: Vec<usize>,
+    /// Later entries that were not selected but are undone by `restore` anyway
+    pub lost: Vec<usize>,
+}
+
+impl RevertPlan {
+    /// Restores at most one snapshot per selection, a second one would be older and undo the
+    /// reverts just made.
+    pub fn new(entries: &[JournalEntry], selected: &BTreeSet<usize>) -> Self {
+        let restore = selected.iter().copied().find(|x| {
+            entries
+                .get(*x)
+                .is_some_and(JournalEntry::reverts_through_snapshot)
+        });
+        let until = restore.unwrap_or(entries.len());
+        Self {
+            restore,
+            in_place: selected
+                .iter()
+                .copied()
+                .filter(|x| *x < until)
+                .rev()
+                .collect(),
+            lost: restore.map_or_else(Vec::new, |restore| {
+                (restore + 1..entries.len())
+                    .filter(|x| !selected.contains(x))
+                    .collect()
+            }),
+        }
+    }
+
+    pub fn apply(
+        &self,
+        collection_id: &CollectionId,
+        entries: &[JournalEntry],
+    ) -> anyhow::Result<()> {
+        if let Some(index) = self.restore {
+            entries[index].revert(collection_id)?;
+        }
+        for index in &self.in_place {
+            entries[*index].revert(collection_id)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::{collection::CollectionId, entry::STORAGE};
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+use strum::EnumIter;
+
+use crate::{
+    java_runtimes::{required_major, runtime_for, JavaRuntime, RUNTIMES},
+    persistence::{self, PersistenceError},
+};
+
+const JVM_SETTINGS_FILE: &str = "jvm_settings";
+/// Left to the system when checking the heap against the installed RAM.
+const RESERVED_MEGABYTES: u64 = 1024;
+
+pub static JVM_SETTINGS: GlobalSignal<JvmSettingsFile> =
+    GlobalSignal::new(|| persistence::load(JVM_SETTINGS_FILE));
+
+#[derive(Snafu, Debug)]
+pub enum JvmSettingsError {
+    #[snafu(display("The minimum heap ({min} MB) is larger than the maximum ({max} MB)"))]
+    HeapOrder { min: u64, max: u64 },
+    #[snafu(display("{heap} MB of heap leaves too little of the {total} MB system memory"))]
+    HeapTooLarge { heap: u64, total: u64 },
+    #[snafu(display("`{name}` is not a valid environment variable name"))]
+    VariableName { name: String },
+    #[snafu(display("{gc} needs Java {required} or newer, the game runs on Java {major}"))]
+    GcUnsupported {
+        gc: GcPreset,
+        required: u32,
+        major: u32,
+    },
+    #[snafu(transparent)]
+    Persistence { source: PersistenceError },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum GcPreset {
+    G1,
+    Zgc,
+    Shenandoah,
+    Parallel,
+}
+
+impl std::fmt::Display for GcPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::G1 => "G1",
+            Self::Zgc => "ZGC",
+            Self::Shenandoah => "Shenandoah",
+            Self::Parallel => "Parallel",
+        })
+    }
+}
+
+impl GcPreset {
+    pub fn args(self) -> &'static [&'static str] {
+        match self {
+            // the flags most modpacks ship with
+            Self::G1 => &[
+                "-XX:+UseG1GC",
+                "-XX:+UnlockExperimentalVMOptions",
+                "-XX:G1NewSizePercent=20",
+                "-XX:G1ReservePercent=20",
+                "-XX:MaxGCPauseMillis=50",
+                "-XX:G1HeapRegionSize=32M",
+            ],
+            Self::Zgc => &["-XX:+UseZGC"],
+            Self::Shenandoah => &["-XX:+UseShenandoahGC"],
+            Self::Parallel => &["-XX:+UseParallelGC"],
+        }
+    }
+
+    /// The oldest Java that has the collector without unlocking experimental options.
+    pub const fn min_java_major(self) -> u32 {
+        match self {
+            Self::G1 | Self::Parallel => 8,
+            Self::Shenandoah => 12,
+            Self::Zgc => 15,
+        }
+    }
+}
+
+/// JVM options of a collection, or the global defaults. Unset fields fall through to the
+/// defaults, and from there to whatever the JVM picks.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct JvmSettings {
+    pub min_heap_megabytes: Option<u64>,
+    pub max_heap_megabytes: Option<u64>,
+    pub gc: Option<GcPreset>,
+    /// Raw arguments, split like a shell would
+    pub extra_args: Option<String>,
+    pub env: Option<BTreeMap<String, String>>,
+}
+
+impl JvmSettings {
+    /// `self` with its unset fields taken from `defaults`. Variables are merged by name, the
+    /// collection's own win.
+    pub fn over(&self, defaults: &JvmSettings) -> JvmSettings {
+        JvmSettings {
+            min_heap_megabytes: self.min_heap_megabytes.or(defaults.min_heap_megabytes),
+            max_heap_megabytes: self.max_heap_megabytes.or(defaults.max_heap_megabytes),
+            gc: self.gc.or(defaults.gc),
+            extra_args: self
+                .extra_args
+                .clone()
+                .or_else(|| defaults.extra_args.clone()),
+            env: match (&self.env, &defaults.env) {
+                (Some(own), Some(defaults)) => Some(
+                    defaults
+                        .iter()
+                        .chain(own)
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect(),
+                ),
+                (own, defaults) => own.clone().or_else(|| defaults.clone()),
+            },
+        }
+    }
+
+    /// `java_major` is the Java the settings are launched with, `None` leaves the collector
+    /// unchecked.
+    pub fn validate(
+        &self,
+        total_megabytes: Option<u64>,
+        java_major: Option<u32>,
+    ) -> Result<(), JvmSettingsError> {
+        if let (Some(min), Some(max)) = (self.min_heap_megabytes, self.max_heap_megabytes) {
+            ensure!(min <= max, HeapOrderSnafu { min, max });
+        }
+        if let Some(total) = total_megabytes {
+            let heap = self.max_heap_megabytes.or(self.min_heap_megabytes);
+            if let Some(heap) = heap {
+                ensure!(
+                    heap + RESERVED_MEGABYTES <= total,
+                    HeapTooLargeSnafu { heap, total }
+                );
+            }
+        }
+        for name in self.env.iter().flat_map(BTreeMap::keys) {
+            let valid = !name.is_empty()
+                && !name.starts_with(|x: char| x.is_ascii_digit())
+                && name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_');
+            ensure!(valid, VariableNameSnafu { name });
+        }
+        if let (Some(gc), Some(major)) = (self.gc, java_major) {
+            let required = gc.min_java_major();
+            ensure!(
+                major >= required,
+                GcUnsupportedSnafu {
+                    gc,
+                    required,
+                    major
+                }
+            );
+        }
+        Ok(())
+    }
+
+    /// The options handed to the JVM, in the order they are applied.
+    pub fn java_options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(min) = self.min_heap_megabytes {
+            options.push(format!("-Xms{min}M"));
+        }
+        if let Some(max) = self.max_heap_megabytes {
+            options.push(format!("-Xmx{max}M"));
+        }
+        if let Some(gc) = self.gc {
+            options.extend(gc.args().iter().map(ToString::to_string));
+        }
+        if let Some(extra) = &self.extra_args {
+            options.extend(split_args(extra));
+        }
+        options
+    }
+
+    /// `args` with the options appended. The heap sizes and collector the options set are taken
+    /// out of `args` first, so they replace what `rust_lib` picked instead of fighting it.
+    pub fn apply_to(&self, args: Vec<String>) -> Vec<String> {
+        let replaced = |x: &str| {
+            self.min_heap_megabytes.is_some() && x.starts_with("-Xms")
+                || self.max_heap_megabytes.is_some() && x.starts_with("-Xmx")
+                || self.gc.is_some() && x.starts_with("-XX:+Use") && x.ends_with("GC")
+        };
+        args.into_iter()
+            .filter(|x| !replaced(x))
+            .chain(self.java_options())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct JvmSettingsFile {
+    pub defaults: JvmSettings,
+    /// Keyed by `CollectionId::to_string`
+    pub collections: BTreeMap<String, JvmSettings>,
+}
+
+/// Splits on whitespace, keeping quoted runs together. `-Dname="a b"` is one argument.
+pub fn split_args(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut started = false;
+    for c in input.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                started = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        args.push(current);
+    }
+    args
+}
+
+/// `KEY=VALUE` per line, blank lines and lines without `=` are skipped.
+pub fn parse_env(input: &str) -> BTreeMap<String, String> {
+    input
+        .lines()
+        .filter_map(|x| x.split_once('='))
+        .map(|(key, value)| (key.trim().to_owned(), value.trim().to_owned()))
+        .collect()
+}
+
+pub fn format_env(env: &BTreeMap<String, String>) -> String {
+    env.iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `MemTotal` of `/proc/meminfo`, in megabytes.
+pub fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo.lines().find(|x| x.starts_with("MemTotal:"))?;
+    let mut parts = line["MemTotal:".len()..].split_whitespace();
+    let value = parts.next()?.parse::<u64>().ok()?;
+    let megabytes = match parts.next().unwrap_or("kB") {
+        "kB" => value / 1024,
+        "mB" | "MB" => value,
+        "gB" | "GB" => value * 1024,
+        _ => return None,
+    };
+    Some(megabytes)
+}
+
+/// Installed RAM in megabytes, `None` where it cannot be read and heap sizes go unchecked.
+pub fn system_memory_megabytes() -> Option<u64> {
+    if cfg!(target_os = "linux") {
+        std::fs::read_to_string("/proc/meminfo")
+            .ok()
+            .as_deref()
+            .and_then(parse_meminfo)
+    } else {
+        None
+    }
+}
+
+/// What the collection overrides, empty when it follows the defaults entirely.
+pub fn collection_settings(collection_id: &CollectionId) -> JvmSettings {
+    JVM_SETTINGS
+        .read()
+        .collections
+        .get(&collection_id.to_string())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// The Java major the collection launches with, its runtime once runtimes have been detected
+/// and otherwise the one its game version needs.
+fn java_major(collection_id: &CollectionId) -> u32 {
+    RUNTIMES
+        .read()
+        .as_deref()
+        .and_then(|x| runtime_for(collection_id, x))
+        .map_or_else(
+            || required_major(collection_id.get_collection_owned().minecraft_version()),
+            |x| x.major,
+        )
+}
+
+/// The collection's overrides layered over the global defaults.
+pub fn effective_settings(collection_id: &CollectionId) -> JvmSettings {
+    let file = JVM_SETTINGS.read();
+    collection_settings(collection_id).over(&file.defaults)
+}
+
+fn save() -> Result<(), JvmSettingsError> {
+    persistence::save(JVM_SETTINGS_FILE, &*JVM_SETTINGS.read())?;
+    Ok(())
+}
+
+/// Validates the defaults as every collection would end up with them before saving.
+pub fn save_defaults(settings: JvmSettings) -> Result<(), JvmSettingsError> {
+    let total = system_memory_megabytes();
+    settings.validate(total, None)?;
+    let collection_ids = (STORAGE.collections)
+        .read()
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    for collection_id in collection_ids {
+        collection_settings(&collection_id)
+            .over(&settings)
+            .validate(total, Some(java_major(&collection_id)))?;
+    }
+    JVM_SETTINGS.write().defaults = settings;
+    save()
+}
+
+/// Saves the overrides of a collection, an empty `settings` drops them.
+pub fn save_collection_settings(
+    collection_id: &CollectionId,
+    settings: JvmSettings,
+) -> Result<(), JvmSettingsError> {
+    settings
+        .over(&JVM_SETTINGS.read().defaults)
+        .validate(system_memory_megabytes(), Some(java_major(collection_id)))?;
+    {
+        let mut file = JVM_SETTINGS.write();
+        let key = collection_id.to_string();
+        if settings == JvmSettings::default() {
+            file.collections.remove(&key);
+        } else {
+            file.collections.insert(key, settings);
+        }
+    }
+    save()
+}
+
+/// The variables the game is started with on top of the launcher's own environment: the
+/// collection's variables and the Java runtime it runs on.
+pub fn launch_variables(
+    collection_id: &CollectionId,
+    runtime: Option<&JavaRuntime>,
+) -> BTreeMap<String, String> {
+    let mut vars = effective_settings(collection_id).env.unwrap_or_default();
+    if let Some(runtime) = runtime {
+        // for whatever the game itself starts with `java`
+        vars.entry(String::from("JAVA_HOME"))
+            .or_insert_with(|| runtime.home.to_string_lossy().into_owned());
+    }
+    vars
+}
@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use dioxus_logger::tracing::{info, warn};
+use rust_lib::api::shared_resources::collection::CollectionId;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use crate::{
+    jvm_settings::split_args,
+    persistence::{self, PersistenceError},
+};
+
+const LAUNCH_HOOKS_FILE: &str = "launch_hooks";
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 60;
+pub const TIMEOUT_PRESETS: [u64; 4] = [30, 60, 120, 300];
+
+/// Keyed by `CollectionId::to_string`.
+pub static LAUNCH_HOOKS: GlobalSignal<BTreeMap<String, LaunchHooks>> =
+    GlobalSignal::new(|| persistence::load(LAUNCH_HOOKS_FILE));
+
+#[derive(Snafu, Debug)]
+pub enum HookError {
+    #[snafu(display("Failed to run the {stage} hook `{command}`"))]
+    Spawn {
+        stage: HookStage,
+        command: String,
+        source: std::io::Error,
+    },
+    #[snafu(display("The {stage} hook `{command}` failed with {status}"))]
+    Failed {
+        stage: HookStage,
+        command: String,
+        status: ExitStatus,
+    },
+    #[snafu(display("The {stage} hook `{command}` did not finish within {seconds} seconds"))]
+    TimedOut {
+        stage: HookStage,
+        command: String,
+        seconds: u64,
+    },
+    #[snafu(transparent)]
+    Persistence { source: PersistenceError },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreLaunch,
+    PostExit,
+}
+
+impl std::fmt::Display for HookStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PreLaunch => write!(f, "pre-launch"),
+            Self::PostExit => write!(f, "post-exit"),
+        }
+    }
+}
+
+/// Commands run around the game, each one a shell command line.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LaunchHooks {
+    /// Put in front of `java` on the command the game is started with, e.g. `gamemoderun mangohud`
+    pub wrapper: Option<String>,
+    /// Run before the game starts, a failure aborts the launch
+    pub pre_launch: Option<String>,
+    /// Run after the game exits
+    pub post_exit: Option<String>,
+    /// `None` is `DEFAULT_TIMEOUT_SECONDS`
+    pub timeout_seconds: Option<u64>,
+}
+
+impl LaunchHooks {
+    pub fn timeout_seconds(&self) -> u64 {
+        self.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS)
+    }
+
+    pub fn wrapper_args(&self) -> Vec<String> {
+        self.wrapper.as_deref().map(split_args).unwrap_or_default()
+    }
+
+    /// Runs the hook for `stage` in the game directory, if there is one. Its output goes to the
+    /// log, line by line, as it is printed.
+    pub async fn run(&self, stage: HookStage, game_directory: &Path) -> Result<(), HookError> {
+        let command = match stage {
+            HookStage::PreLaunch => &self.pre_launch,
+            HookStage::PostExit => &self.post_exit,
+        };
+        match command.as_deref().map(str::trim) {
+            Some(command) if !command.is_empty() => {
+                run_hook(stage, command, game_directory, self.timeout_seconds()).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn launch_hooks(collection_id: &CollectionId) -> LaunchHooks {
+    LAUNCH_HOOKS
+        .read()
+        .get(&collection_id.to_string())
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn save_launch_hooks(
+    collection_id: &CollectionId,
+    hooks: LaunchHooks,
+) -> Result<(), PersistenceError> {
+    LAUNCH_HOOKS
+        .write()
+        .insert(collection_id.to_string(), hooks);
+    persistence::save(LAUNCH_HOOKS_FILE, &*LAUNCH_HOOKS.read())
+}
+
+/// Logs `output` line by line under `label`, stderr as warnings.
+pub(crate) async fn forward(
+    label: impl std::fmt::Display,
+    output: Option<impl AsyncRead + Unpin>,
+    stderr: bool,
+) {
+    let Some(output) = output else {
+        return;
+    };
+    let mut lines = BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if stderr {
+            warn!("[{label}] {line}");
+        } else {
+            info!("[{label}] {line}");
+        }
+    }
+}
+
+/// Kills a hook and everything it started when dropped, which is how a timed out hook ends.
+struct ProcessTree(Option<u32>);
+
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        let Some(pid) = self.0 else {
+            return;
+        };
+        let mut kill = if cfg!(windows) {
+            let mut kill = std::process::Command::new("taskkill");
+            kill.args(["/T", "/F", "/PID", &pid.to_string()]);
+            kill
+        } else {
+            // the hook leads its own process group, a negative pid names the group
+            let mut kill = std::process::Command::new("kill");
+            kill.args(["-KILL", "--", &format!("-{pid}")]);
+            kill
+        };
+        if let Err(x) = kill.stdout(Stdio::null()).stderr(Stdio::null()).status() {
+            warn!("Failed to kill hook process {pid}: {x}");
+        }
+    }
+}
+
+async fn run_hook(
+    stage: HookStage,
+    command: &str,
+    game_directory: &Path,
+    seconds: u64,
+) -> Result<(), HookError> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut process = tokio::process::Command::new(shell);
+    process
+        .arg(flag)
+        .arg(command)
+        .env("PRAG_PORTAL_GAME_DIRECTORY", game_directory)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // whatever the shell starts joins its group, so a timeout can kill all of it
+    #[cfg(unix)]
+    process.process_group(0);
+    if game_directory.is_dir() {
+        process.current_dir(game_directory);
+    }
+    info!("[{stage}] {command}");
+    let mut child = process.spawn().context(SpawnSnafu { stage, command })?;
+    let mut tree = ProcessTree(child.id());
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let finished = async {
+        futures_util::future::join(forward(stage, stdout, false), forward(stage, stderr, true))
+            .await;
+        child.wait().await
+    };
+    let status = tokio::time::timeout(Duration::from_secs(seconds), finished)
+        .await
+        .ok()
+        .context(TimedOutSnafu {
+            stage,
+            command,
+            seconds,
+        })?
+        .context(SpawnSnafu { stage, command })?;
+    // it exited on its own, leave whatever it left running in the background alone
+    tree.0 = None;
+    ensure!(
+        status.success(),
+        FailedSnafu {
+            stage,
+            command,
+            status
+        }
+    );
+    Ok(())
+}
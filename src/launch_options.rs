@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use dioxus::prelude::*;
+use dioxus_logger::tracing::{error, warn};
+use rust_lib::api::shared_resources::collection::CollectionId;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    collection_edit::advanced::PillAction,
+    collection_stats::format_date,
+    nbt,
+    options_txt::{GameOptions, OptionsError},
+    persistence::{self, PersistenceError},
+    servers::{read_servers, split_address, ServerError},
+    BaseComponents::{
+        atoms::button::{Button, Roundness},
+        string_placements::{Alignment, ContentType, Contents, Hint},
+    },
+    ErrorFormatted,
+};
+
+const LAUNCH_OPTIONS_FILE: &str = "launch_options";
+/// The first snapshot with the `--quickPlay` arguments, they shipped with 1.20.
+const QUICK_PLAY_SNAPSHOT: (u32, u32) = (23, 14);
+
+/// Keyed by `CollectionId::to_string`.
+pub static LAUNCH_OPTIONS: GlobalSignal<BTreeMap<String, LaunchOptions>> =
+    GlobalSignal::new(|| persistence::load(LAUNCH_OPTIONS_FILE));
+
+/// Set by the "play this" shortcuts, picked up by the collection display which launches the
+/// collection.
+pub static LAUNCH_REQUEST: GlobalSignal<Option<CollectionId>> = GlobalSignal::new(|| None);
+
+/// Quick play targets for the next launch only, keyed by `CollectionId::to_string`. They win over
+/// the collection's saved target and are not persisted.
+static QUICK_PLAY_ONCE: GlobalSignal<BTreeMap<String, QuickPlay>> =
+    GlobalSignal::new(BTreeMap::new);
+
+#[derive(Snafu, Debug)]
+pub enum LaunchOptionsError {
+    #[snafu(display("Failed to list {}", path.display()))]
+    ListDir {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(transparent)]
+    Options { source: OptionsError },
+    #[snafu(transparent)]
+    Servers { source: ServerError },
+    #[snafu(transparent)]
+    Persistence { source: PersistenceError },
+}
+
+/// Where the game drops the player right after it starts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuickPlay {
+    /// The world's directory name under `saves`
+    Singleplayer(String),
+    /// `host` or `host:port`
+    Multiplayer(String),
+}
+
+impl std::fmt::Display for QuickPlay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Singleplayer(x) => write!(f, "單人世界 {x}"),
+            Self::Multiplayer(x) => write!(f, "伺服器 {x}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LaunchOptions {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// `None` leaves it to the game's own setting
+    pub fullscreen: Option<bool>,
+    pub demo: bool,
+    pub quick_play: Option<QuickPlay>,
+}
+
+impl LaunchOptions {
+    fn resolution(&self) -> Option<(u32, u32)> {
+        Some((self.width?, self.height?))
+    }
+
+    /// The game arguments for these options on `version`, leaving out flags `present` already
+    /// has. `quick_play` is the target of this launch, which singleplayer needs 1.20 for.
+    pub fn arguments(
+        &self,
+        version: &str,
+        quick_play: Option<&QuickPlay>,
+        present: &[String],
+    ) -> Vec<String> {
+        let has = |flag: &str| present.iter().any(|x| x == flag);
+        let mut args = Vec::new();
+        if let Some((width, height)) = self.resolution().filter(|_| !has("--width")) {
+            args.extend([
+                String::from("--width"),
+                width.to_string(),
+                String::from("--height"),
+                height.to_string(),
+            ]);
+        }
+        // windowed has no flag, `apply_window_options` writes it to options.txt
+        if self.fullscreen == Some(true) && !has("--fullscreen") {
+            args.push(String::from("--fullscreen"));
+        }
+        if self.demo && !has("--demo") {
+            args.push(String::from("--demo"));
+        }
+        match (quick_play, supports_quick_play(version)) {
+            (Some(QuickPlay::Singleplayer(world)), true) => {
+                args.extend([String::from("--quickPlaySingleplayer"), world.clone()]);
+            }
+            (Some(QuickPlay::Multiplayer(address)), true) => {
+                args.extend([String::from("--quickPlayMultiplayer"), address.clone()]);
+            }
+            (Some(QuickPlay::Multiplayer(address)), false) => {
+                let (host, port) = split_address(address);
+                args.extend([
+                    String::from("--server"),
+                    host,
+                    String::from("--port"),
+                    port.to_string(),
+                ]);
+            }
+            (Some(QuickPlay::Singleplayer(world)), false) => {
+                warn!("Quick play into {world} needs Minecraft 1.20, {version} starts on the menu");
+            }
+            (None, _) => {}
+        }
+        args
+    }
+}
+
+/// 1.20 and later, its pre-releases, and the snapshots since quick play arrived.
+fn supports_quick_play(version: &str) -> bool {
+    if let Some((year, rest)) = version.split_once('w') {
+        let week = rest.trim_end_matches(|x: char| x.is_ascii_alphabetic());
+        if let (Ok(year), Ok(week)) = (year.parse::<u32>(), week.parse::<u32>()) {
+            return (year, week) >= QUICK_PLAY_SNAPSHOT;
+        }
+    }
+    let release = version.split(['-', ' ']).next().unwrap_or(version);
+    let mut parts = release.split('.').map(|x| x.parse::<u32>().ok());
+    match (parts.next().flatten(), parts.next().flatten()) {
+        (Some(major), Some(minor)) => (major, minor) >= (1, 20),
+        _ => false,
+    }
+}
+
+pub fn launch_options(collection_id: &CollectionId) -> LaunchOptions {
+    LAUNCH_OPTIONS
+        .read()
+        .get(&collection_id.to_string())
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn save_launch_options(
+    collection_id: &CollectionId,
+    options: LaunchOptions,
+) -> Result<(), PersistenceError> {
+    LAUNCH_OPTIONS
+        .write()
+        .insert(collection_id.to_string(), options);
+    persistence::save(LAUNCH_OPTIONS_FILE, &*LAUNCH_OPTIONS.read())
+}
+
+/// A windowed launch has no argument, so it goes through `options.txt`, which the game reads on
+/// start. Left alone unless the user picked windowed.
+pub fn apply_window_options(
+    game_directory: &Path,
+    options: &LaunchOptions,
+) -> Result<(), LaunchOptionsError> {
+    if options.fullscreen != Some(false) {
+        return Ok(());
+    }
+    let mut game_options = GameOptions::load(game_directory)?;
+    game_options.set("fullscreen", String::from("false"));
+    game_options.save(game_directory)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct World {
+    /// Directory name under `saves`, what quick play takes
+    pub directory: String,
+    pub name: String,
+    /// Unix timestamp in seconds
+    pub last_played: Option<u64>,
+}
+
+/// The collection's singleplayer worlds, most recently played first.
+pub fn list_worlds(game_directory: &Path) -> Result<Vec<World>, LaunchOptionsError> {
+    let saves = game_directory.join("saves");
+    if !saves.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut worlds = Vec::new();
+    for entry in std::fs::read_dir(&saves).context(ListDirSnafu { path: &saves })? {
+        let path = entry.context(ListDirSnafu { path: &saves })?.path();
+        let level = path.join("level.dat");
+        if !level.is_file() {
+            continue;
+        }
+        let directory = path
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        // a world the game is writing to right now may not parse, list it by its directory
+        let data = nbt::read_file(&level).ok();
+        let data = data.as_ref().and_then(|x| x.get("Data"));
+        worlds.push(World {
+            name: data
+                .and_then(|x| x.get("LevelName"))
+                .and_then(|x| x.as_str())
+                .map_or_else(|| directory.clone(), ToOwned::to_owned),
+            last_played: data
+                .and_then(|x| x.get("LastPlayed"))
+                .and_then(|x| x.as_i64())
+                .map(|x| (x / 1000) as u64),
+            directory,
+        });
+    }
+    worlds.sort_by_key(|x| std::cmp::Reverse(x.last_played));
+    Ok(worlds)
+}
+
+/// Asks for a launch straight into `target`, once, leaving the saved target alone.
+pub fn play(collection_id: &CollectionId, target: QuickPlay) {
+    QUICK_PLAY_ONCE
+        .write()
+        .insert(collection_id.to_string(), target);
+    *LAUNCH_REQUEST.write() = Some(collection_id.clone());
+}
+
+/// The target of the collection's next launch, a pending [`play`] before the saved one. Taking it
+/// ends the one-off, whether the launch goes ahead or not.
+pub fn take_quick_play(collection_id: &CollectionId) -> Option<QuickPlay> {
+    QUICK_PLAY_ONCE
+        .write()
+        .remove(&collection_id.to_string())
+        .or_else(|| launch_options(collection_id).quick_play)
+}
+
+#[component]
+pub fn WorldViewer(collection_id: ReadOnlySignal<CollectionId>) -> Element {
+    let game_directory = collection_id().get_collection_owned().game_directory();
+    let worlds = list_worlds(&game_directory);
+    let servers = read_servers(&game_directory).map_err(LaunchOptionsError::from);
+    let (worlds, servers) = match (worlds, servers) {
+        (Ok(worlds), Ok(servers)) => (worlds, servers),
+        (Err(x), _) | (_, Err(x)) => {
+            error!("{}", x.to_formatted());
+            (Vec::new(), Vec::new())
+        }
+    };
+    let current = launch_options(&collection_id()).quick_play;
+    let rows = worlds
+        .into_iter()
+        .map(|x| {
+            let hint = x.last_played.map_or_else(
+                || x.directory.clone(),
+                |time| format!("{}•上次遊玩 {}", x.directory, format_date(time)),
+            );
+            (x.name, hint, QuickPlay::Singleplayer(x.directory))
+        })
+        .chain(servers.into_iter().map(|x| {
+            let name = if x.name.is_empty() {
+                x.address.clone()
+            } else {
+                x.name
+            };
+            (name, x.address.clone(), QuickPlay::Multiplayer(x.address))
+        }))
+        .collect::<Vec<_>>();
+    rsx! {
+        div {
+            class: "flex flex-col gap-[3px] p-[20px]",
+            if rows.is_empty() {
+                Hint { css: "text-[18px] p-[20px]", "這個收藏還沒有任何世界或伺服器" }
+            }
+            for (name , hint , target) in rows {
+                Button {
+                    key: "{target}",
+                    roundness: Roundness::None,
+                    clickable: false,
+                    extended_css_class: "bg-deep-background p-[20px] first:rounded-t-3xl last:rounded-b-3xl",
+                    string_placements: vec![
+                        Contents::new(
+                                vec![
+                                    ContentType::text(name).css("text-white text-[24px] font-bold font-english"),
+                                    ContentType::hint(hint).css("text-[15px] font-english"),
+                                ],
+                                Alignment::Left,
+                            )
+                            .css("flex flex-col gap-[10px]"),
+                        ContentType::custom(rsx! {
+                            PillAction {
+                                label: if matches!(target, QuickPlay::Singleplayer(_)) { "遊玩此世界" } else { "加入此伺服器" },
+                                selected: current.as_ref() == Some(&target),
+                                onclick: {
+                                    let target = target.clone();
+                                    move |()| play(&collection_id(), target.clone())
+                                }
+                            }
+                        }).align_right(),
+                    ]
+                }
+            }
+        }
+    }
+}
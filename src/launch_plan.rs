@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+
+use dioxus::prelude::*;
+use rust_lib::api::{
+    backend_exclusive::vanilla::launcher::LaunchArgs, shared_resources::collection::CollectionId,
+};
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    java_runtimes::{runtime_for, runtimes, JavaRuntime, RUNTIMES},
+    journal::with_mut_collection,
+    jvm_settings::{effective_settings, launch_variables},
+    launch_hooks::{forward, launch_hooks},
+    launch_options::{launch_options, QuickPlay},
+    mod_actions::added_mods,
+    persistence::{self, PersistenceError},
+};
+
+const GAME_COMMANDS_FILE: &str = "game_commands";
+/// Stands in for secrets in anything shown to the user or written out.
+pub const REDACTED: &str = "<redacted>";
+/// Arguments whose value is a credential.
+const SECRET_FLAGS: [&str; 3] = ["--accessToken", "--session", "--xuid"];
+
+/// The command `rust_lib` resolved on each collection's last launch, with its credentials
+/// redacted, keyed by `CollectionId::to_string`. The preview is built on it, so it never has to
+/// resolve or download the game itself.
+pub static GAME_COMMANDS: GlobalSignal<BTreeMap<String, GameCommand>> =
+    GlobalSignal::new(|| persistence::load(GAME_COMMANDS_FILE));
+
+#[derive(Snafu, Debug)]
+pub enum LaunchPlanError {
+    #[snafu(display("Failed to prepare {name} for launch: {message}"))]
+    Prepare { name: String, message: String },
+    #[snafu(display("Failed to start `{program}`"))]
+    Spawn {
+        program: String,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to wait for `{program}` to exit"))]
+    Wait {
+        program: String,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to write {}", path.display()))]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(transparent)]
+    Persistence { source: PersistenceError },
+}
+
+/// The game as `rust_lib` resolves it: the version's arguments filled in and everything they
+/// point at downloaded.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct GameCommand {
+    pub jvm_args: Vec<String>,
+    pub main_class: String,
+    pub game_args: Vec<String>,
+}
+
+impl From<LaunchArgs> for GameCommand {
+    fn from(value: LaunchArgs) -> Self {
+        Self {
+            jvm_args: value.jvm_args,
+            main_class: value.main_class,
+            game_args: value.game_args,
+        }
+    }
+}
+
+fn redact(args: &[String]) -> Vec<String> {
+    let mut secret_next = false;
+    args.iter()
+        .map(|x| {
+            let value = if secret_next { REDACTED } else { x };
+            secret_next = SECRET_FLAGS.contains(&x.as_str());
+            value.to_owned()
+        })
+        .collect()
+}
+
+impl GameCommand {
+    pub fn redacted(&self) -> Self {
+        Self {
+            jvm_args: redact(&self.jvm_args),
+            main_class: self.main_class.clone(),
+            game_args: redact(&self.game_args),
+        }
+    }
+}
+
+/// Everything the game is started with. The launch and the preview build it the same way, the
+/// preview from the command of the last launch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchPlan {
+    /// Put in front of `java`
+    pub wrapper: Vec<String>,
+    pub java: PathBuf,
+    pub jvm_args: Vec<String>,
+    pub main_class: String,
+    pub game_args: Vec<String>,
+    pub working_directory: PathBuf,
+    /// Set on top of the launcher's own environment, for the game only
+    pub env: BTreeMap<String, String>,
+}
+
+impl LaunchPlan {
+    /// Lays the collection's settings over the command `rust_lib` resolved, with `quick_play` as
+    /// the launch's target.
+    pub fn new(
+        collection_id: &CollectionId,
+        game: GameCommand,
+        runtime: Option<&JavaRuntime>,
+        quick_play: Option<&QuickPlay>,
+    ) -> Self {
+        let collection = collection_id.get_collection_owned();
+        let options = launch_options(collection_id).arguments(
+            &collection.minecraft_version().id,
+            quick_play,
+            &game.game_args,
+        );
+        Self {
+            wrapper: launch_hooks(collection_id).wrapper_args(),
+            java: runtime.map_or_else(|| PathBuf::from("java"), JavaRuntime::binary),
+            jvm_args: effective_settings(collection_id).apply_to(game.jvm_args),
+            main_class: game.main_class,
+            game_args: game.game_args.into_iter().chain(options).collect(),
+            working_directory: collection.game_directory(),
+            env: launch_variables(collection_id, runtime),
+        }
+    }
+
+    /// The entries of the `-cp` argument.
+    pub fn classpath(&self) -> Vec<PathBuf> {
+        let separator = if cfg!(windows) { ';' } else { ':' };
+        self.jvm_args
+            .iter()
+            .skip_while(|x| *x != "-cp" && *x != "-classpath")
+            .nth(1)
+            .map(|x| x.split(separator).map(PathBuf::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// The full command line, classpath included.
+    pub fn command(&self) -> Vec<String> {
+        self.wrapper
+            .iter()
+            .cloned()
+            .chain(std::iter::once(self.java.to_string_lossy().into_owned()))
+            .chain(self.jvm_args.iter().cloned())
+            .chain(std::iter::once(self.main_class.clone()))
+            .chain(self.game_args.iter().cloned())
+            .collect()
+    }
+
+    /// `self` with every credential replaced by `REDACTED`, safe to copy or share.
+    pub fn redacted(&self) -> Self {
+        let secret = |name: &str| {
+            let name = name.to_uppercase();
+            ["TOKEN", "SECRET", "PASSWORD"]
+                .iter()
+                .any(|x| name.contains(x))
+        };
+        Self {
+            jvm_args: redact(&self.jvm_args),
+            game_args: redact(&self.game_args),
+            env: self
+                .env
+                .iter()
+                .map(|(key, value)| {
+                    let value = if secret(key) { REDACTED } else { value };
+                    (key.clone(), value.to_owned())
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// A POSIX shell script that starts the game the same way.
+    pub fn shell_script(&self) -> String {
+        let mut script = String::from("#!/bin/sh\nset -e\n");
+        script.push_str(&format!(
+            "cd {}\n",
+            shell_quote(&self.working_directory.to_string_lossy())
+        ));
+        for (key, value) in &self.env {
+            script.push_str(&format!("export {key}={}\n", shell_quote(value)));
+        }
+        let command = self
+            .command()
+            .iter()
+            .map(|x| shell_quote(x))
+            .collect::<Vec<_>>()
+            .join(" \\\n    ");
+        script.push_str(&format!("exec {command}\n"));
+        script
+    }
+
+    /// Starts the game. It runs on its own until [`RunningGame::wait`].
+    pub fn spawn(&self) -> Result<RunningGame, LaunchPlanError> {
+        let command = self.command();
+        let (program, args) = command.split_first().expect("the command always has java");
+        let mut process = tokio::process::Command::new(program);
+        process
+            .args(args)
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if self.working_directory.is_dir() {
+            process.current_dir(&self.working_directory);
+        }
+        let child = process.spawn().context(SpawnSnafu { program })?;
+        Ok(RunningGame {
+            program: program.clone(),
+            child,
+        })
+    }
+}
+
+/// A game process started by [`LaunchPlan::spawn`].
+pub struct RunningGame {
+    program: String,
+    child: tokio::process::Child,
+}
+
+impl RunningGame {
+    /// Waits for the game to exit, its output goes to the log meanwhile.
+    pub async fn wait(mut self) -> Result<ExitStatus, LaunchPlanError> {
+        let stdout = self.child.stdout.take();
+        let stderr = self.child.stderr.take();
+        futures_util::future::join(
+            forward("game", stdout, false),
+            forward("game", stderr, true),
+        )
+        .await;
+        let program = &self.program;
+        self.child.wait().await.context(WaitSnafu { program })
+    }
+}
+
+/// Single quotes, which the shell takes literally, with embedded ones spliced in.
+pub fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() || "-_./=:,+@%".contains(x))
+    {
+        return value.to_owned();
+    }
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Has `rust_lib` download whatever the collection is missing and resolve its command. The
+/// redacted command is kept for the preview.
+pub async fn resolve(collection_id: &CollectionId) -> Result<GameCommand, LaunchPlanError> {
+    let mut collection = collection_id.get_collection_owned();
+    let before = collection
+        .mod_controller()
+        .map(|x| x.manager.mods.clone())
+        .unwrap_or_default();
+    let game = match collection.verify_and_download_game().await {
+        Ok(x) => GameCommand::from(x),
+        Err(x) => {
+            return PrepareSnafu {
+                name: collection.display_name().clone(),
+                message: x.to_string(),
+            }
+            .fail()
+        }
+    };
+    // resolving downloads the mods the collection was missing, like a launch through `rust_lib`
+    // does. Only those are merged back, the collection may have been edited meanwhile and the
+    // rest of the clone is stale
+    let added = added_mods(&before, &collection);
+    with_mut_collection(collection_id, |x| {
+        if let Some(controller) = x.mod_controller.as_mut() {
+            controller.manager.mods.extend(added);
+        }
+    })
+    .map_err(|x| LaunchPlanError::Prepare {
+        name: collection.display_name().clone(),
+        message: x.to_string(),
+    })?;
+    GAME_COMMANDS
+        .write()
+        .insert(collection_id.to_string(), game.redacted());
+    persistence::save(GAME_COMMANDS_FILE, &*GAME_COMMANDS.read())?;
+    Ok(game)
+}
+
+/// The plan of a launch into `quick_play`, resolved now.
+pub async fn plan_launch(
+    collection_id: &CollectionId,
+    quick_play: Option<QuickPlay>,
+) -> Result<LaunchPlan, LaunchPlanError> {
+    let game = resolve(collection_id).await?;
+    let runtime = runtime_for(collection_id, &runtimes().await);
+    Ok(LaunchPlan::new(
+        collection_id,
+        game,
+        runtime.as_ref(),
+        quick_play.as_ref(),
+    ))
+}
+
+/// The plan the collection would launch with, from the command of its last launch and its
+/// current settings. `None` until it was launched or resolved once. The runtime is taken from
+/// the last detection, if there was one.
+pub fn preview_plan(collection_id: &CollectionId) -> Option<LaunchPlan> {
+    let game = GAME_COMMANDS
+        .read()
+        .get(&collection_id.to_string())
+        .cloned()?;
+    let runtime = runtime_for(collection_id, &RUNTIMES.read().clone().unwrap_or_default());
+    let quick_play = launch_options(collection_id).quick_play;
+    let plan = LaunchPlan::new(collection_id, game, runtime.as_ref(), quick_play.as_ref());
+    Some(plan.redacted())
+}
+
+/// Where [`export_script`] writes.
+pub fn script_path(plan: &LaunchPlan) -> PathBuf {
+    plan.working_directory.join("launch.sh")
+}
+
+/// Writes the redacted plan as an executable script at [`script_path`], which the caller has
+/// the user confirm first.
+pub fn export_script(plan: &LaunchPlan) -> Result<PathBuf, LaunchPlanError> {
+    let path = script_path(plan);
+    std::fs::write(&path, plan.redacted().shell_script()).context(WriteSnafu { path: &path })?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .context(WriteSnafu { path: &path })?;
+    }
+    Ok(path)
+}
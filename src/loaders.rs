@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use dioxus_logger::tracing::{error, warn};
+use rust_lib::api::shared_resources::collection::{CollectionId, ModLoader, ModLoaderType};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    jar_metadata::LoaderKind,
+    java_runtimes::{download_runtime, required_major, runtime_for, runtimes, JavaError},
+    journal::{self, with_mut_collection},
+    local_mods::{collection_loader, local_mods_of},
+    mod_index::{scan_jars, ModIndexError},
+    persistence,
+    snapshots::{take_snapshot, SnapshotError, SnapshotReason},
+    ErrorFormatted,
+};
+
+const FABRIC_META: &str = "https://meta.fabricmc.net/v2/versions/loader";
+const QUILT_META: &str = "https://meta.quiltmc.org/v3/versions/loader";
+const FORGE_METADATA: &str =
+    "https://files.minecraftforge.net/net/minecraftforge/forge/maven-metadata.json";
+const FORGE_MAVEN: &str = "https://maven.minecraftforge.net/net/minecraftforge/forge";
+const NEOFORGE_METADATA: &str =
+    "https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge";
+const NEOFORGE_MAVEN: &str = "https://maven.neoforged.net/releases/net/neoforged/neoforge";
+const LOADER_BUILDS_FILE: &str = "loader_builds";
+/// Loader metadata that takes longer than this counts as unreachable.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The builds last fetched for each loader and game version, keyed by `{loader} {game_version}`.
+/// They stand in when the loader's metadata can't be reached, so collections can still be
+/// created offline.
+static KNOWN_BUILDS: GlobalSignal<BTreeMap<String, Vec<LoaderBuild>>> =
+    GlobalSignal::new(|| persistence::load(LOADER_BUILDS_FILE));
+
+#[derive(Snafu, Debug)]
+pub enum LoaderError {
+    #[snafu(display("Failed to reach {url}"))]
+    Request { url: String, source: reqwest::Error },
+    #[snafu(display("{url} answered with an unexpected body"))]
+    Body {
+        url: String,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Failed to access {}", path.display()))]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("The {loader} installer exited with {status}"))]
+    Installer {
+        loader: LoaderKind,
+        status: std::process::ExitStatus,
+    },
+    #[snafu(display("Failed to update the collection: {message}"))]
+    Update { message: String },
+    #[snafu(transparent)]
+    Index { source: ModIndexError },
+    #[snafu(transparent)]
+    Snapshot { source: SnapshotError },
+    #[snafu(transparent)]
+    Java { source: JavaError },
+}
+
+/// A single release of a mod loader for a Minecraft version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoaderBuild {
+    pub version: String,
+    pub stable: bool,
+}
+
+pub const fn mod_loader_type(loader: LoaderKind) -> ModLoaderType {
+    match loader {
+        LoaderKind::Fabric => ModLoaderType::Fabric,
+        LoaderKind::Quilt => ModLoaderType::Quilt,
+        LoaderKind::Forge => ModLoaderType::Forge,
+        LoaderKind::NeoForge => ModLoaderType::NeoForge,
+    }
+}
+
+/// The loader of the collection and the build it is pinned to, `None` for vanilla.
+pub fn current_loader(collection_id: &CollectionId) -> Option<(LoaderKind, Option<String>)> {
+    let kind = collection_loader(collection_id)?;
+    let version = collection_id
+        .get_collection_owned()
+        .mod_loader()
+        .and_then(|x| x.version.clone());
+    Some((kind, version))
+}
+
+/// `Fabric 0.15.11`, or just `Fabric` when no build is pinned.
+pub fn loader_label(collection_id: &CollectionId) -> Option<String> {
+    current_loader(collection_id).map(|(kind, version)| match version {
+        Some(version) => format!("{kind} {version}"),
+        None => kind.to_string(),
+    })
+}
+
+async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, LoaderError> {
+    let bytes = reqwest::Client::new()
+        .get(url)
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(RequestSnafu { url })?
+        .bytes()
+        .await
+        .context(RequestSnafu { url })?;
+    serde_json::from_slice(&bytes).context(BodySnafu { url })
+}
+
+#[derive(Deserialize)]
+struct MetaLoaderEntry {
+    loader: MetaLoader,
+}
+
+#[derive(Deserialize)]
+struct MetaLoader {
+    version: String,
+    /// Quilt's meta has no such field, its betas are marked in the version instead
+    stable: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct MavenVersions {
+    versions: Vec<String>,
+}
+
+/// NeoForge versions drop the leading `1.` of the Minecraft version, `1.21.1` -> `21.1.x`.
+fn neoforge_prefix(game_version: &str) -> Option<String> {
+    let mut parts = game_version.strip_prefix("1.")?.split('.');
+    let minor = parts.next()?;
+    let patch = parts.next().unwrap_or("0");
+    Some(format!("{minor}.{patch}."))
+}
+
+/// Every build of `loader` for `game_version`, newest first. When the metadata can't be reached
+/// the builds fetched last time are returned instead, if there are any.
+pub async fn loader_builds(
+    loader: LoaderKind,
+    game_version: &str,
+) -> Result<Vec<LoaderBuild>, LoaderError> {
+    let key = format!("{loader} {game_version}");
+    match fetch_builds(loader, game_version).await {
+        Ok(builds) => {
+            KNOWN_BUILDS.write().insert(key, builds.clone());
+            if let Err(x) = persistence::save(LOADER_BUILDS_FILE, &*KNOWN_BUILDS.read()) {
+                error!("{}", x.to_formatted());
+            }
+            Ok(builds)
+        }
+        Err(x) => match KNOWN_BUILDS.read().get(&key) {
+            Some(builds) => {
+                warn!("Using the known {key} builds, the metadata is unreachable: {x}");
+                Ok(builds.clone())
+            }
+            None => Err(x),
+        },
+    }
+}
+
+async fn fetch_builds(
+    loader: LoaderKind,
+    game_version: &str,
+) -> Result<Vec<LoaderBuild>, LoaderError> {
+    match loader {
+        LoaderKind::Fabric | LoaderKind::Quilt => {
+            let base = if loader == LoaderKind::Fabric {
+                FABRIC_META
+            } else {
+                QUILT_META
+            };
+            let entries: Vec<MetaLoaderEntry> =
+                get_json(&format!("{base}/{game_version}")).await?;
+            Ok(entries
+                .into_iter()
+                .map(|x| LoaderBuild {
+                    stable: x
+                        .loader
+                        .stable
+                        .unwrap_or_else(|| !x.loader.version.contains("beta")),
+                    version: x.loader.version,
+                })
+                .collect())
+        }
+        LoaderKind::Forge => {
+            let mut versions: std::collections::BTreeMap<String, Vec<String>> =
+                get_json(FORGE_METADATA).await?;
+            Ok(versions
+                .remove(game_version)
+                .unwrap_or_default()
+                .into_iter()
+                .rev()
+                .map(|version| LoaderBuild {
+                    stable: true,
+                    version,
+                })
+                .collect())
+        }
+        LoaderKind::NeoForge => {
+            let Some(prefix) = neoforge_prefix(game_version) else {
+                return Ok(Vec::new());
+            };
+            let maven: MavenVersions = get_json(NEOFORGE_METADATA).await?;
+            Ok(maven
+                .versions
+                .into_iter()
+                .rev()
+                .filter(|x| x.starts_with(&prefix))
+                .map(|version| LoaderBuild {
+                    stable: !version.contains("beta"),
+                    version,
+                })
+                .collect())
+        }
+    }
+}
+
+/// The newest stable build, used when a collection is created.
+pub async fn latest_stable_build(
+    loader: LoaderKind,
+    game_version: &str,
+) -> Result<Option<String>, LoaderError> {
+    Ok(loader_builds(loader, game_version)
+        .await?
+        .into_iter()
+        .find(|x| x.stable)
+        .map(|x| x.version))
+}
+
+/// Installed mods that will not load on `target`, judged by their jar metadata.
+pub fn incompatible_mods(
+    collection_id: &CollectionId,
+    target: LoaderKind,
+) -> Result<Vec<String>, LoaderError> {
+    let mut incompatible = scan_jars(collection_id)?
+        .into_iter()
+        .filter_map(|x| x.metadata)
+        .filter(|x| !x.loader.runs_on(target))
+        .map(|x| x.name)
+        .collect::<Vec<_>>();
+    for x in local_mods_of(collection_id) {
+        if !x.metadata.loader.runs_on(target) && !incompatible.contains(&x.metadata.name) {
+            incompatible.push(x.metadata.name);
+        }
+    }
+    incompatible.sort();
+    Ok(incompatible)
+}
+
+fn installer_url(loader: LoaderKind, version: &str) -> Option<String> {
+    match loader {
+        LoaderKind::Forge => Some(format!(
+            "{FORGE_MAVEN}/{version}/forge-{version}-installer.jar"
+        )),
+        LoaderKind::NeoForge => Some(format!(
+            "{NEOFORGE_MAVEN}/{version}/neoforge-{version}-installer.jar"
+        )),
+        LoaderKind::Fabric | LoaderKind::Quilt => None,
+    }
+}
+
+/// Downloads the Forge/NeoForge installer and runs it against the collection's game directory,
+/// on the runtime the collection launches with.
+async fn run_installer(
+    collection_id: &CollectionId,
+    loader: LoaderKind,
+    version: &str,
+) -> Result<(), LoaderError> {
+    let Some(url) = installer_url(loader, version) else {
+        return Ok(());
+    };
+    let game_directory = collection_id.get_collection_owned().game_directory();
+    let bytes = reqwest::get(&url)
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(RequestSnafu { url: &url })?
+        .bytes()
+        .await
+        .context(RequestSnafu { url: &url })?;
+    let dir = persistence::data_dir().join("installers");
+    std::fs::create_dir_all(&dir).context(IoSnafu { path: &dir })?;
+    let installer = dir.join(format!("{loader}-{version}-installer.jar"));
+    std::fs::write(&installer, bytes).context(IoSnafu { path: &installer })?;
+    // the installers refuse to run without a launcher profile to add themselves to
+    let profiles = game_directory.join("launcher_profiles.json");
+    if !profiles.exists() {
+        std::fs::create_dir_all(&game_directory).context(IoSnafu {
+            path: &game_directory,
+        })?;
+        std::fs::write(&profiles, r#"{"profiles":{}}"#).context(IoSnafu { path: &profiles })?;
+    }
+    let runtime = match runtime_for(collection_id, &runtimes().await) {
+        Some(x) => x,
+        None => {
+            let major = required_major(collection_id.get_collection_owned().minecraft_version());
+            download_runtime(major).await?
+        }
+    };
+    let status = tokio::process::Command::new(runtime.binary())
+        .arg("-jar")
+        .arg(&installer)
+        .arg("--installClient")
+        .arg(&game_directory)
+        .current_dir(&dir)
+        .status()
+        .await
+        .context(IoSnafu { path: &installer })?;
+    let _ = std::fs::remove_file(&installer);
+    ensure!(status.success(), InstallerSnafu { loader, status });
+    Ok(())
+}
+
+/// Switches the collection to `loader` pinned at `version`, taking a snapshot first.
+pub async fn switch_loader(
+    collection_id: &CollectionId,
+    loader: LoaderKind,
+    version: String,
+) -> Result<(), LoaderError> {
+    let snapshot = take_snapshot(collection_id, SnapshotReason::LoaderChange).await?;
+    run_installer(collection_id, loader, &version).await?;
+    let start = journal::entry_count(collection_id);
+    with_mut_collection(collection_id, |x| {
+        *x.mod_loader = Some(ModLoader::new(mod_loader_type(loader), Some(version)));
+    })
+    .map_err(|x| LoaderError::Update {
+        message: x.to_string(),
+    })?;
+    journal::attach_snapshot(collection_id, start, snapshot.created);
+    Ok(())
+}
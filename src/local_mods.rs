@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use dioxus::prelude::*;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    download_files::record_retry,
+    download_queue::{queued, JobKind, QueueError},
+    jar_metadata::{read_jar_entry, JarMetadata, JarMetadataError, LoaderKind},
+    journal::{self, Change},
+    mod_index::mods_dir,
+    modrinth::{self, ModrinthError, ModrinthFile},
+    persistence::{self, PersistenceError},
+};
+
+const LOCAL_MODS_FILE: &str = "local_mods";
+/// Appended to the jar of a disabled mod, loaders only pick up `*.jar`.
+const DISABLED_SUFFIX: &str = ".disabled";
+/// Tries a download gets before its error is reported.
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Jars that were dropped into a collection rather than downloaded, keyed by
+/// `CollectionId::to_string`.
+pub static LOCAL_MODS: GlobalSignal<BTreeMap<String, Vec<LocalMod>>> =
+    GlobalSignal::new(|| persistence::load(LOCAL_MODS_FILE));
+
+#[derive(Snafu, Debug)]
+pub enum LocalModError {
+    #[snafu(transparent)]
+    Metadata { source: JarMetadataError },
+    #[snafu(display("{name} is a {jar} mod, but this collection uses {}", collection.map_or_else(|| String::from("no mod loader"), |x| x.to_string())))]
+    LoaderMismatch {
+        name: String,
+        jar: LoaderKind,
+        collection: Option<LoaderKind>,
+    },
+    #[snafu(display("{file_name} already belongs to another mod in the collection"))]
+    FileTaken { file_name: String },
+    #[snafu(display("Failed to access {} in the collection", path.display()))]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(transparent)]
+    Modrinth { source: ModrinthError },
+    #[snafu(transparent)]
+    Queue { source: QueueError },
+    #[snafu(transparent)]
+    Persistence { source: PersistenceError },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalMod {
+    pub metadata: JarMetadata,
+    pub file_name: String,
+    /// Icon extracted from the jar
+    pub icon_path: Option<PathBuf>,
+    pub enabled: bool,
+}
+
+impl LocalMod {
+    /// Where the jar currently is, disabled jars carry [`DISABLED_SUFFIX`].
+    pub fn path_in(&self, mods_dir: &Path) -> PathBuf {
+        if self.enabled {
+            mods_dir.join(&self.file_name)
+        } else {
+            mods_dir.join(format!("{}{DISABLED_SUFFIX}", self.file_name))
+        }
+    }
+}
+
+pub fn collection_loader(collection_id: &CollectionId) -> Option<LoaderKind> {
+    collection_id
+        .get_collection_owned()
+        .mod_loader()
+        .and_then(|x| LoaderKind::from_loader_name(&x.to_string()))
+}
+
+pub fn local_mods_of(collection_id: &CollectionId) -> Vec<LocalMod> {
+    LOCAL_MODS
+        .read()
+        .get(&collection_id.to_string())
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn save() -> Result<(), PersistenceError> {
+    persistence::save(LOCAL_MODS_FILE, &*LOCAL_MODS.read())
+}
+
+/// Replaces the collection's dropped jars, for a `mods` directory that was put back as a whole.
+pub fn replace_local_mods(
+    collection_id: &CollectionId,
+    mods: Vec<LocalMod>,
+) -> Result<(), PersistenceError> {
+    LOCAL_MODS.write().insert(collection_id.to_string(), mods);
+    save()
+}
+
+fn extract_icon(jar: &Path, metadata: &JarMetadata) -> Option<PathBuf> {
+    let icon = metadata.icon.as_deref()?;
+    let bytes = read_jar_entry(jar, icon.trim_start_matches('/')).ok()??;
+    let extension = Path::new(icon).extension()?.to_string_lossy().to_string();
+    let dir = persistence::data_dir().join("local_mod_icons");
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(format!("{}.{extension}", metadata.id));
+    std::fs::write(&path, bytes).ok()?;
+    Some(path)
+}
+
+/// Validates a dropped jar against the collection's mod loader and copies it into the
+/// collection's `mods` directory. A jar of another mod under the same file name is never
+/// overwritten.
+pub fn import_jar(collection_id: &CollectionId, jar: &Path) -> Result<LocalMod, LocalModError> {
+    let metadata = JarMetadata::read(jar)?;
+    let loader = collection_loader(collection_id);
+    if !loader.is_some_and(|x| metadata.loader.runs_on(x)) {
+        return LoaderMismatchSnafu {
+            name: metadata.name,
+            jar: metadata.loader,
+            collection: loader,
+        }
+        .fail();
+    }
+    let file_name = jar
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_else(|| format!("{}.jar", metadata.id));
+    let mods_dir = mods_dir(collection_id);
+    std::fs::create_dir_all(&mods_dir).context(IoSnafu { path: &mods_dir })?;
+    let target = mods_dir.join(&file_name);
+    let taken_on_disk =
+        target.exists() && JarMetadata::read(&target).map_or(true, |x| x.id != metadata.id);
+    // a disabled jar of another mod is on disk under a different name
+    let taken_by_local = local_mods_of(collection_id)
+        .iter()
+        .any(|x| x.file_name == file_name && x.metadata.id != metadata.id);
+    if taken_on_disk || taken_by_local {
+        return FileTakenSnafu { file_name }.fail();
+    }
+    std::fs::copy(jar, &target).context(IoSnafu { path: jar })?;
+    let local = LocalMod {
+        icon_path: extract_icon(jar, &metadata),
+        metadata,
+        file_name,
+        enabled: true,
+    };
+    let replaced = {
+        let mut local_mods = LOCAL_MODS.write();
+        let mods = local_mods.entry(collection_id.to_string()).or_default();
+        // dropping a newer build of the same mod replaces the old one
+        let replaced = mods
+            .iter()
+            .position(|x| x.metadata.id == local.metadata.id)
+            .map(|x| mods.remove(x));
+        if let Some(old) = &replaced {
+            if old.path_in(&mods_dir) != target {
+                let _ = std::fs::remove_file(old.path_in(&mods_dir));
+            }
+        }
+        mods.push(local.clone());
+        replaced
+    };
+    save()?;
+    let name = local.metadata.name.clone();
+    // a replacement is journaled as an update, reverting an addition would remove the mod
+    journal::append(
+        collection_id,
+        match replaced {
+            Some(old) => Change::ModUpdated {
+                name,
+                from: old.metadata.version,
+                to: local.metadata.version.clone(),
+            },
+            None => Change::ModAdded { name },
+        },
+    );
+    Ok(local)
+}
+
+/// Downloads a Modrinth file and imports it like a dropped jar, replacing the older build.
+pub async fn import_download(
+    collection_id: &CollectionId,
+    file: &ModrinthFile,
+) -> Result<LocalMod, LocalModError> {
+    let staging = persistence::data_dir().join("download_staging");
+    std::fs::create_dir_all(&staging).context(IoSnafu { path: &staging })?;
+    let path = staging.join(&file.filename);
+    let kind = JobKind::Import { file: file.clone() };
+    let attempts = async {
+        let mut attempt = 1;
+        loop {
+            match modrinth::download_file(file, &path).await {
+                Err(x) if attempt < DOWNLOAD_ATTEMPTS => {
+                    record_retry(collection_id, &file.filename, x.to_string());
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        }
+    };
+    let download = queued(collection_id, kind, attempts).await;
+    if download.is_err() {
+        // a cancelled download leaves whatever it wrote in staging
+        let _ = std::fs::remove_file(&path);
+    }
+    download??;
+    let result = import_jar(collection_id, &path);
+    std::fs::remove_file(&path).context(IoSnafu { path: &path })?;
+    result
+}
+
+/// Renames the jars of the dropped mods named `name`, returns whether any of them changed.
+pub fn set_local_enabled(
+    collection_id: &CollectionId,
+    name: &str,
+    enabled: bool,
+) -> Result<bool, LocalModError> {
+    let mods_dir = mods_dir(collection_id);
+    let mut changed = false;
+    {
+        let mut local_mods = LOCAL_MODS.write();
+        let Some(mods) = local_mods.get_mut(&collection_id.to_string()) else {
+            return Ok(false);
+        };
+        for local in mods
+            .iter_mut()
+            .filter(|x| x.metadata.name == name && x.enabled != enabled)
+        {
+            // the flag only changes once the jar has been renamed
+            let from = local.path_in(&mods_dir);
+            let to = LocalMod {
+                enabled,
+                ..local.clone()
+            }
+            .path_in(&mods_dir);
+            if from.exists() {
+                std::fs::rename(&from, &to).context(IoSnafu { path: from })?;
+            }
+            local.enabled = enabled;
+            changed = true;
+        }
+    }
+    if changed {
+        save()?;
+    }
+    Ok(changed)
+}
+
+/// Deletes a dropped jar from the collection's `mods` directory and forgets it.
+pub fn remove_local_mod(collection_id: &CollectionId, id: &str) -> Result<(), LocalModError> {
+    let removed = {
+        let mut local_mods = LOCAL_MODS.write();
+        let Some(mods) = local_mods.get_mut(&collection_id.to_string()) else {
+            return Ok(());
+        };
+        let Some(position) = mods.iter().position(|x| x.metadata.id == id) else {
+            return Ok(());
+        };
+        mods.remove(position)
+    };
+    let path = removed.path_in(&mods_dir(collection_id));
+    if path.exists() {
+        std::fs::remove_file(&path).context(IoSnafu { path })?;
+    }
+    save()?;
+    journal::append(
+        collection_id,
+        Change::ModRemoved {
+            name: removed.metadata.name,
+        },
+    );
+    Ok(())
+}
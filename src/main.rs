@@ -1,14 +1,45 @@
 #![allow(non_snake_case)]
 pub mod BaseComponents;
+pub mod bulk_actions;
 pub mod collection_display;
 pub mod collection_edit;
+pub mod collection_grid;
+pub mod collection_search;
+pub mod collection_stats;
 pub mod collections;
+pub mod conflicts;
+pub mod crash_report;
+pub mod dependency_graph;
+pub mod download_files;
+pub mod download_history;
 pub mod download_progress;
+pub mod download_queue;
+pub mod favorites;
+pub mod folders;
+pub mod jar_metadata;
+pub mod java_runtimes;
+pub mod journal;
+pub mod jvm_settings;
+pub mod launch_hooks;
+pub mod launch_options;
+pub mod launch_plan;
+pub mod loaders;
+pub mod local_mods;
 pub mod main_page;
+pub mod mod_actions;
+pub mod mod_index;
+pub mod mod_query;
+pub mod modrinth;
+pub mod nbt;
+pub mod options_txt;
 pub mod pages;
+pub mod persistence;
 pub mod scrollable;
+pub mod servers;
 pub mod side_bar;
+pub mod snapshots;
 pub mod text_scroller;
+pub mod upgrade;
 
 use builder::collection_builder;
 use collection_edit::CollectionEditContainer;
@@ -42,6 +73,8 @@ use dioxus::{prelude::*, CapturedError};
 
 use crate::collection_display::CollectionDisplay;
 use crate::collections::Collections;
+use crate::conflicts::PreLaunchModal;
+use crate::crash_report::CrashReportModal;
 use crate::download_progress::DownloadProgress;
 use crate::main_page::MainPage;
 use crate::side_bar::SideBar;
@@ -181,7 +214,7 @@ fn main() {
 mod builder {
     use std::path::PathBuf;
 
-    use dioxus_logger::tracing::info;
+    use dioxus_logger::tracing::{error, info};
     use rust_lib::api::{
         backend_exclusive::{errors::ManifestProcessingError, vanilla::version::VersionMetadata},
         shared_resources::{
@@ -191,7 +224,10 @@ mod builder {
     };
     use snafu::prelude::*;
 
-    use crate::get_random_collection_picture;
+    use crate::{
+        get_random_collection_picture, jar_metadata::LoaderKind, loaders::latest_stable_build,
+        options_txt::apply_default_options, ErrorFormatted,
+    };
 
     #[derive(Snafu, Debug)]
     pub enum CollectionBuilderError {
@@ -215,16 +251,25 @@ mod builder {
             .await
             .context(VersionIdParsingSnafu { id: &version_id })?
             .context(InvalidVersionIdSnafu { id: &version_id })?;
+        let loader_build = latest_stable_build(LoaderKind::Fabric, &version_id)
+            .await
+            .unwrap_or_else(|x| {
+                error!("{}", x.to_formatted());
+                None
+            });
         let mut collection = entry::create_collection(
             "新的收藏",
             picture_path
                 .into()
                 .unwrap_or_else(|| get_random_collection_picture().into()),
             version,
-            ModLoader::new(ModLoaderType::Fabric, None),
+            ModLoader::new(ModLoaderType::Fabric, loader_build),
             None,
         )
         .await?;
+        if let Err(x) = apply_default_options(&collection.game_directory()) {
+            error!("{}", x.to_formatted());
+        }
         info!("Adding mods...");
         collection
             .add_multiple_modrinth_mod(
@@ -288,6 +333,12 @@ fn App() -> Element {
 
                 }
             }
+            CrashReportModal {
+
+            }
+            PreLaunchModal {
+
+            }
         }
     }
 }
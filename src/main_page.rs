@@ -4,13 +4,16 @@ use rust_lib::api::shared_resources::collection::CollectionId;
 use tailwind_fuse::*;
 
 use crate::{
+    collection_search::Highlighted,
+    collection_stats::status_hint,
+    favorites::{favorites_of, move_favorite, toggle_favorite},
     text_scroller::use_text_scroller,
     BaseComponents::{
         atoms::button::{Button, ButtonClass, FillMode, Roundness, Size},
         molecules::switcher::StateSwitcher,
         string_placements::{Alignment, ContentType, Contents, Hint, Text},
     },
-    Pages, ARROW_RIGHT,
+    use_error_handler, Pages, ARROW_RIGHT,
 };
 
 // pub const COLLECTION_PIC: ImageAsset =
@@ -41,6 +44,9 @@ pub fn CollectionBlock(
     #[props(default = true)] gradient: bool,
     #[props(extends=GlobalAttributes)] attributes: Vec<Attribute>,
     #[props(default)] extended_class: String,
+    /// Chars of the name matched by a search
+    #[props(default)]
+    highlight: Vec<usize>,
 ) -> Element {
     let collection = collection_id().get_collection();
     let (roundness, extended_class): (Vec<_>, Vec<_>) = extended_class
@@ -82,11 +88,18 @@ pub fn CollectionBlock(
                         css: "group-hover:group-aria-selected:animate-scroll-left w-full text-3xl text-white text-nowrap font-bold text-left",
                         style: style(),
                         onmounted,
-                        {collection.read().display_name().clone()}
+                        if highlight.is_empty() {
+                            {collection.read().display_name().clone()}
+                        } else {
+                            Highlighted {
+                                text: collection.read().display_name().clone(),
+                                indices: highlight.clone()
+                            }
+                        }
                     }
                     Hint {
                         css: "text-[15px] text-hint text-ellipsis text-nowrap",
-                        "遊玩中•由我建立"
+                        {status_hint(&collection_id())}
                     }
                 }
             }
@@ -288,6 +301,9 @@ fn SuggestionPage() -> Element {
 #[component]
 fn CollectionsPage() -> Element {
     let keys = use_context::<Memo<Vec<CollectionId>>>();
+    let mut reordering = use_signal(|| false);
+    let favorites = favorites_of(&keys());
+    let len = favorites.len();
     rsx! {
         div {
             class: "flex flex-col space-x-0",
@@ -303,14 +319,19 @@ fn CollectionsPage() -> Element {
                             Alignment::Left,
                         )
                         .css("flex flex-col gap-[20px]"),
-                    Contents::new(
-                        vec![
-                            ContentType::svg(ARROW_LEFT),
-                            ContentType::svg(STAR),
-                            ContentType::svg(ARROW_RIGHT),
-                        ],
-                        Alignment::Right,
-                    ),
+                    ContentType::custom(rsx! {
+                        Button {
+                            roundness: Roundness::Pill,
+                            fill_mode: FillMode::Fit,
+                            extended_css_class: if reordering() { "bg-lime-300 text-black px-[20px] py-[10px]" } else { "bg-background px-[20px] py-[10px]" },
+                            onclick: move |()| reordering.toggle(),
+                            string_placements: vec![
+                                ContentType::svg(STAR).align_left(),
+                                ContentType::text(if reordering() { "完成" } else { "排序" }).align_right(),
+                            ]
+                        }
+                    })
+                    .align_right(),
                 ],
                 extended_css_class: "p-[30px] mb-0",
                 clickable: false
@@ -319,10 +340,26 @@ fn CollectionsPage() -> Element {
                 class: ButtonClass::builder().roundness(Roundness::Bottom).with_class("min-w-screen p-0"),
                 div {
                     class: "flex space-x-[3px] overflow-scroll",
-                    for collection_id in keys() {
-                        CollectionBlock {
+                    if favorites.is_empty() {
+                        Hint {
+                            css: "p-[30px] text-[18px]",
+                            "在收藏頁面按下星號，就會出現在這裡"
+                        }
+                    }
+                    for (index , collection_id) in favorites.into_iter().enumerate() {
+                        div {
                             key: "{collection_id}",
-                            collection_id
+                            class: "relative",
+                            CollectionBlock {
+                                collection_id: collection_id.clone()
+                            }
+                            if reordering() {
+                                FavoriteControls {
+                                    collection_id,
+                                    first: index == 0,
+                                    last: index + 1 == len
+                                }
+                            }
                         }
                     }
                 }
@@ -330,3 +367,43 @@ fn CollectionsPage() -> Element {
         }
     }
 }
+
+/// Move and unstar buttons laid over a favorite while the carousel is being reordered.
+#[component]
+fn FavoriteControls(collection_id: ReadOnlySignal<CollectionId>, first: bool, last: bool) -> Element {
+    let mut error = use_error_handler();
+    rsx! {
+        div {
+            class: "absolute top-[15px] inset-x-[15px] flex justify-between",
+            Button {
+                roundness: Roundness::Pill,
+                fill_mode: FillMode::Fit,
+                clickable: !first,
+                extended_css_class: "bg-background p-[5px]",
+                onclick: move |()| {
+                    error.set(Some(move_favorite(&collection_id(), -1).map_err(Into::into)));
+                },
+                string_placements: vec![ContentType::svg(ARROW_LEFT).align_center()]
+            }
+            Button {
+                roundness: Roundness::Pill,
+                fill_mode: FillMode::Fit,
+                extended_css_class: "bg-background p-[5px]",
+                onclick: move |()| {
+                    error.set(Some(toggle_favorite(&collection_id()).map_err(Into::into)));
+                },
+                string_placements: vec![ContentType::text("移除").align_center()]
+            }
+            Button {
+                roundness: Roundness::Pill,
+                fill_mode: FillMode::Fit,
+                clickable: !last,
+                extended_css_class: "bg-background p-[5px]",
+                onclick: move |()| {
+                    error.set(Some(move_favorite(&collection_id(), 1).map_err(Into::into)));
+                },
+                string_placements: vec![ContentType::svg(ARROW_RIGHT).align_center()]
+            }
+        }
+    }
+}
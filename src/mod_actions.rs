@@ -0,0 +1,139 @@
+use rust_lib::api::{
+    backend_exclusive::mod_management::mods::ModMetadata,
+    shared_resources::collection::{Collection, CollectionId},
+};
+
+use crate::{
+    download_queue::{queued, JobKind},
+    journal::{self, with_mut_collection, Change},
+    local_mods::set_local_enabled,
+    mod_index::mods_dir,
+    modrinth::ModrinthVersion,
+};
+
+/// Enables or disables every installed mod named `mod_name` in the collection, dropped jars
+/// included.
+pub fn set_mod_enabled(
+    collection_id: &CollectionId,
+    mod_name: &str,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    with_mut_collection(collection_id, |x| {
+        if let Some(controller) = x.mod_controller.as_mut() {
+            controller
+                .manager
+                .mods
+                .iter_mut()
+                .filter(|x| x.name == mod_name)
+                .for_each(|x| x.enabled = enabled);
+        }
+    })?;
+    if set_local_enabled(collection_id, mod_name, enabled)? {
+        journal::append(
+            collection_id,
+            Change::ModToggled {
+                name: mod_name.to_owned(),
+                enabled,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Removes a Modrinth mod from the collection along with its jar, when it is known.
+pub fn remove_mod(
+    collection_id: &CollectionId,
+    mod_name: &str,
+    file_name: Option<&str>,
+) -> anyhow::Result<()> {
+    with_mut_collection(collection_id, |x| {
+        if let Some(controller) = x.mod_controller.as_mut() {
+            controller.manager.mods.retain(|x| x.name != mod_name);
+        }
+    })?;
+    if let Some(file_name) = file_name {
+        let path = mods_dir(collection_id).join(file_name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds a mod from Modrinth by its slug and downloads it into the collection. Only the mods the
+/// download added are written back, the collection may have been edited while it was queued.
+pub async fn install_modrinth_mod(collection_id: &CollectionId, slug: &str) -> anyhow::Result<()> {
+    let mut collection = collection_id.get_collection_owned();
+    let before = collection
+        .mod_controller()
+        .map(|x| x.manager.mods.clone())
+        .unwrap_or_default();
+    collection
+        .add_multiple_modrinth_mod(vec![slug], vec![], None)
+        .await?;
+    let kind = JobKind::Install {
+        slug: slug.to_owned(),
+    };
+    queued(collection_id, kind, collection.download_mods()).await??;
+    let added = added_mods(&before, &collection);
+    with_mut_collection(collection_id, |x| {
+        if let Some(controller) = x.mod_controller.as_mut() {
+            controller.manager.mods.extend(added);
+        }
+    })
+}
+
+/// Mods `rust_lib` added to `downloaded` that `before` does not have, by name and version.
+pub(crate) fn added_mods(before: &[ModMetadata], downloaded: &Collection) -> Vec<ModMetadata> {
+    downloaded
+        .mod_controller()
+        .map(|x| x.manager.mods.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|x| {
+            !before
+                .iter()
+                .any(|old| old.name == x.name && old.mod_version == x.mod_version)
+        })
+        .collect()
+}
+
+/// Replaces an installed Modrinth mod with `version`, journaled as a single update. The new
+/// version is downloaded first, the old one is only removed once it is in.
+pub async fn update_modrinth_mod(
+    collection_id: &CollectionId,
+    mod_name: &str,
+    file_name: Option<&str>,
+    version: &ModrinthVersion,
+) -> anyhow::Result<()> {
+    let mut collection = collection_id.get_collection_owned();
+    let before = collection
+        .mod_controller()
+        .map(|x| x.manager.mods.clone())
+        .unwrap_or_default();
+    collection
+        .add_multiple_modrinth_mod(vec![version.project_id.as_str()], vec![], None)
+        .await?;
+    let kind = JobKind::Update {
+        mod_name: mod_name.to_owned(),
+        file_name: file_name.map(str::to_owned),
+        version: version.clone(),
+    };
+    queued(collection_id, kind, collection.download_mods()).await??;
+    let added = added_mods(&before, &collection);
+    with_mut_collection(collection_id, |x| {
+        if let Some(controller) = x.mod_controller.as_mut() {
+            controller.manager.mods.retain(|x| x.name != mod_name);
+            controller.manager.mods.extend(added);
+        }
+    })?;
+    // the new jar may have been written over the old one
+    let new_file = version.primary_file().map(|x| x.filename.as_str());
+    if let Some(file_name) = file_name.filter(|x| Some(*x) != new_file) {
+        let path = mods_dir(collection_id).join(file_name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
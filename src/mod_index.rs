@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use dioxus_logger::tracing::error;
+use rust_lib::api::shared_resources::collection::CollectionId;
+use sha2::{Digest, Sha512};
+use snafu::prelude::*;
+
+use crate::{
+    jar_metadata::{JarMetadata, LoaderKind},
+    local_mods::collection_loader,
+    modrinth::{self, ModrinthVersion},
+    ErrorFormatted,
+};
+
+/// How long a Modrinth answer about a jar is trusted before it is asked again.
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Jars already read, keyed by path. An entry is reused while the file's modification time and
+/// size stay the same, so rescanning only hashes what changed.
+static SCANNED: Mutex<BTreeMap<PathBuf, (Option<SystemTime>, u64, InstalledJar)>> =
+    Mutex::new(BTreeMap::new());
+
+/// A jar's hash, the collection's loader and its game version.
+type UpdateQuery = (String, Option<LoaderKind>, String);
+
+/// Modrinth's latest version of a jar and when it was asked, `None` when Modrinth does not know
+/// the jar.
+static LATEST: Mutex<BTreeMap<UpdateQuery, (Instant, Option<ModrinthVersion>)>> =
+    Mutex::new(BTreeMap::new());
+
+#[derive(Snafu, Debug)]
+pub enum ModIndexError {
+    #[snafu(display("Failed to read {}", path.display()))]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Scanning the collection's jars stopped before it finished"))]
+    Interrupted { source: tokio::task::JoinError },
+}
+
+/// A jar found in a collection's `mods` directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledJar {
+    pub file_name: String,
+    /// `None` when the jar has no metadata we understand
+    pub metadata: Option<JarMetadata>,
+    pub sha512: String,
+}
+
+/// What is on disk for a collection, used where `ModMetadata` alone is not enough.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ModIndex {
+    pub jars: Vec<InstalledJar>,
+    /// Newer versions on Modrinth, keyed by the file name of the installed jar
+    pub updates: BTreeMap<String, ModrinthVersion>,
+}
+
+/// Lowercases and strips everything that is not alphanumeric, `Fabric API` -> `fabricapi`.
+pub(crate) fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|x| x.to_ascii_lowercase())
+        .collect()
+}
+
+pub fn sha512_of(path: &Path) -> Result<String, ModIndexError> {
+    let mut file = std::fs::File::open(path).context(ReadSnafu { path })?;
+    let mut hasher = Sha512::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).context(ReadSnafu { path })?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|x| format!("{x:02x}"))
+        .collect())
+}
+
+pub fn mods_dir(collection_id: &CollectionId) -> PathBuf {
+    collection_id
+        .get_collection_owned()
+        .game_directory()
+        .join("mods")
+}
+
+fn read_jar(path: &Path) -> Result<InstalledJar, ModIndexError> {
+    let file = path.metadata().context(ReadSnafu { path })?;
+    let (modified, size) = (file.modified().ok(), file.len());
+    let mut scanned = SCANNED.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some((_, _, jar)) = scanned
+        .get(path)
+        .filter(|x| modified.is_some() && x.0 == modified && x.1 == size)
+    {
+        return Ok(jar.clone());
+    }
+    let jar = InstalledJar {
+        file_name: path
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        metadata: JarMetadata::read(path).ok(),
+        sha512: sha512_of(path)?,
+    };
+    scanned.insert(path.to_path_buf(), (modified, size, jar.clone()));
+    Ok(jar)
+}
+
+/// Reads every `.jar` in the collection's `mods` directory, hashing only jars that changed
+/// since they were last read.
+pub fn scan_jars(collection_id: &CollectionId) -> Result<Vec<InstalledJar>, ModIndexError> {
+    scan_dir(&mods_dir(collection_id))
+}
+
+fn scan_dir(dir: &Path) -> Result<Vec<InstalledJar>, ModIndexError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(x) => x,
+        Err(x) if x.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(x) => return Err(x).context(ReadSnafu { path: dir }),
+    };
+    let mut jars = Vec::new();
+    for entry in entries {
+        let path = entry.context(ReadSnafu { path: dir })?.path();
+        if path.extension().is_some_and(|x| x == "jar") {
+            jars.push(read_jar(&path)?);
+        }
+    }
+    jars.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(jars)
+}
+
+/// Reads the metadata of every `.jar` in `mods_dir`, without hashing them. Jars without
+/// metadata we understand are left out.
+pub fn read_metadata(mods_dir: &Path) -> Result<Vec<(String, JarMetadata)>, ModIndexError> {
+    let entries = match std::fs::read_dir(mods_dir) {
+        Ok(x) => x,
+        Err(x) if x.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(x) => return Err(x).context(ReadSnafu { path: mods_dir }),
+    };
+    let mut jars = Vec::new();
+    for entry in entries {
+        let path = entry.context(ReadSnafu { path: mods_dir })?.path();
+        if path.extension().is_some_and(|x| x == "jar") {
+            if let Ok(metadata) = JarMetadata::read(&path) {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                jars.push((file_name.to_string(), metadata));
+            }
+        }
+    }
+    jars.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(jars)
+}
+
+/// Scans the collection's jars on a blocking thread and asks Modrinth which of them have
+/// updates, only about jars it was not asked about within `UPDATE_CHECK_INTERVAL`. Failing to
+/// reach Modrinth is not an error, the index simply has no updates for the jars it could not ask
+/// about.
+pub async fn index_collection(collection_id: &CollectionId) -> Result<ModIndex, ModIndexError> {
+    let dir = mods_dir(collection_id);
+    let jars = tokio::task::spawn_blocking(move || scan_dir(&dir))
+        .await
+        .context(InterruptedSnafu)??;
+    let collection = collection_id.get_collection_owned();
+    let game_version = collection.minecraft_version().id.clone();
+    let loader = collection_loader(collection_id);
+    let key = |sha512: &str| (sha512.to_owned(), loader, game_version.clone());
+    let stale = {
+        let latest = LATEST.lock().unwrap_or_else(PoisonError::into_inner);
+        jars.iter()
+            .filter(|x| {
+                latest
+                    .get(&key(&x.sha512))
+                    .map_or(true, |x| x.0.elapsed() > UPDATE_CHECK_INTERVAL)
+            })
+            .map(|x| x.sha512.clone())
+            .collect::<Vec<_>>()
+    };
+    match modrinth::latest_versions(&stale, loader, &game_version).await {
+        Ok(mut answers) => {
+            let mut latest = LATEST.lock().unwrap_or_else(PoisonError::into_inner);
+            for sha512 in stale {
+                let version = answers.remove(&sha512);
+                latest.insert(key(&sha512), (Instant::now(), version));
+            }
+        }
+        Err(x) => error!("{}", x.to_formatted()),
+    }
+    let latest = LATEST.lock().unwrap_or_else(PoisonError::into_inner);
+    let updates = jars
+        .iter()
+        .filter_map(|jar| {
+            let version = latest.get(&key(&jar.sha512))?.1.as_ref()?;
+            let up_to_date = version.files.iter().any(|x| x.hashes.sha512 == jar.sha512);
+            (!up_to_date).then(|| (jar.file_name.clone(), version.clone()))
+        })
+        .collect();
+    drop(latest);
+    Ok(ModIndex { jars, updates })
+}
+
+impl ModIndex {
+    /// Finds the jar of an installed mod by its display name or mod id.
+    pub fn find(&self, name: &str) -> Option<&InstalledJar> {
+        let name = normalize(name);
+        if name.is_empty() {
+            return None;
+        }
+        self.jars.iter().find(|x| {
+            x.metadata
+                .as_ref()
+                .is_some_and(|x| normalize(&x.name) == name || normalize(&x.id) == name)
+        })
+    }
+}
@@ -0,0 +1,229 @@
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::jar_metadata::LoaderKind;
+
+#[derive(Snafu, Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    #[snafu(display("Unknown filter `{key}:`, expected one of {}", FILTER_KEYS.iter().map(|x| x.key).collect::<Vec<_>>().join(", ")))]
+    UnknownKey { key: String },
+    #[snafu(display("`{key}:{value}` is not valid, expected {expected}"))]
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: String,
+    },
+    #[snafu(display("`{key}:` is missing a value"))]
+    MissingValue { key: String },
+}
+
+pub struct FilterKey {
+    pub key: &'static str,
+    /// Values offered by autocompletion, empty for free-form values
+    pub values: &'static [&'static str],
+    pub description: &'static str,
+}
+
+pub const FILTER_KEYS: &[FilterKey] = &[
+    FilterKey {
+        key: "enabled",
+        values: &["true", "false"],
+        description: "是否啟用",
+    },
+    FilterKey {
+        key: "source",
+        values: &["modrinth", "local"],
+        description: "模組來源",
+    },
+    FilterKey {
+        key: "update",
+        values: &["available", "none"],
+        description: "是否有可用的更新",
+    },
+    FilterKey {
+        key: "author",
+        values: &[],
+        description: "作者名稱",
+    },
+    FilterKey {
+        key: "loader",
+        values: &["fabric", "quilt", "forge", "neoforge"],
+        description: "模組載入器",
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ModSource {
+    Modrinth,
+    /// Dropped in by the user, see [`crate::local_mods`]
+    Local,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    Enabled(bool),
+    Source(ModSource),
+    UpdateAvailable(bool),
+    Author(String),
+    Loader(LoaderKind),
+}
+
+/// A parsed search, e.g. `enabled:false author:jellysquid sodium`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ModQuery {
+    pub filters: Vec<Filter>,
+    pub text: String,
+}
+
+/// Everything a query can match against for a single row of the mod list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModFacts {
+    pub name: String,
+    pub description: Option<String>,
+    pub file_name: Option<String>,
+    pub enabled: bool,
+    pub source: ModSource,
+    pub update_available: bool,
+    pub authors: Vec<String>,
+    pub loader: Option<LoaderKind>,
+}
+
+/// Splits on whitespace, keeping `"quoted text"` together.
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for x in input.chars() {
+        match x {
+            '"' => quoted = !quoted,
+            x if x.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            x => current.push(x),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn invalid(key: &str, value: &str, expected: &str) -> QueryError {
+    QueryError::InvalidValue {
+        key: key.to_owned(),
+        value: value.to_owned(),
+        expected: expected.to_owned(),
+    }
+}
+
+fn parse_filter(key: &str, value: &str) -> Result<Filter, QueryError> {
+    ensure!(!value.is_empty(), MissingValueSnafu { key });
+    let value_lower = value.to_lowercase();
+    match key.to_lowercase().as_str() {
+        "enabled" => match value_lower.as_str() {
+            "true" | "yes" => Ok(Filter::Enabled(true)),
+            "false" | "no" => Ok(Filter::Enabled(false)),
+            _ => Err(invalid(key, value, "true or false")),
+        },
+        "source" => match value_lower.as_str() {
+            "modrinth" => Ok(Filter::Source(ModSource::Modrinth)),
+            "local" => Ok(Filter::Source(ModSource::Local)),
+            _ => Err(invalid(key, value, "modrinth or local")),
+        },
+        "update" => match value_lower.as_str() {
+            "available" => Ok(Filter::UpdateAvailable(true)),
+            "none" => Ok(Filter::UpdateAvailable(false)),
+            _ => Err(invalid(key, value, "available or none")),
+        },
+        "author" => Ok(Filter::Author(value_lower)),
+        "loader" => LoaderKind::from_loader_name(value)
+            .map(Filter::Loader)
+            .ok_or_else(|| invalid(key, value, "fabric, quilt, forge or neoforge")),
+        _ => UnknownKeySnafu { key }.fail(),
+    }
+}
+
+impl ModQuery {
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let mut query = Self::default();
+        let mut text = Vec::new();
+        for token in tokenize(input) {
+            match token.split_once(':') {
+                // `http://...` and other colons inside words are free text
+                Some((key, value)) if key.chars().all(|x| x.is_ascii_alphabetic()) => {
+                    query.filters.push(parse_filter(key, value)?);
+                }
+                _ => text.push(token),
+            }
+        }
+        query.text = text.join(" ");
+        Ok(query)
+    }
+
+    fn matches_filter(filter: &Filter, facts: &ModFacts) -> bool {
+        match filter {
+            Filter::Enabled(x) => facts.enabled == *x,
+            Filter::Source(x) => facts.source == *x,
+            Filter::UpdateAvailable(x) => facts.update_available == *x,
+            Filter::Author(x) => facts
+                .authors
+                .iter()
+                .any(|author| author.to_lowercase().contains(x.as_str())),
+            // Fabric jars run on Quilt, but `loader:fabric` should still mean "built for Fabric"
+            Filter::Loader(x) => facts.loader == Some(*x),
+        }
+    }
+
+    /// `None` when the row is filtered out, otherwise how well the free text matches (higher is
+    /// better). Without free text every remaining row scores the same.
+    pub fn score(&self, matcher: &SkimMatcherV2, facts: &ModFacts) -> Option<i64> {
+        if !self.filters.iter().all(|x| Self::matches_filter(x, facts)) {
+            return None;
+        }
+        if self.text.is_empty() {
+            return Some(0);
+        }
+        [
+            Some(facts.name.as_str()),
+            facts.file_name.as_deref(),
+            facts.description.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|x| matcher.fuzzy_match(x, &self.text))
+        .max()
+    }
+}
+
+/// Completions for the last token of `input`, each returned as the whole input so they can be
+/// offered directly by the input's suggestion list.
+pub fn completions(input: &str) -> Vec<String> {
+    if input.ends_with(char::is_whitespace) {
+        return Vec::new();
+    }
+    let (head, last) = input
+        .rsplit_once(char::is_whitespace)
+        .unwrap_or(("", input));
+    let prefix = if head.is_empty() {
+        String::new()
+    } else {
+        format!("{head} ")
+    };
+    match last.split_once(':') {
+        None => FILTER_KEYS
+            .iter()
+            .filter(|x| x.key.starts_with(&last.to_lowercase()))
+            .map(|x| format!("{prefix}{}:", x.key))
+            .collect(),
+        Some((key, value)) => FILTER_KEYS
+            .iter()
+            .filter(|x| x.key.eq_ignore_ascii_case(key))
+            .flat_map(|x| x.values.iter())
+            .filter(|x| x.starts_with(&value.to_lowercase()) && **x != value)
+            .map(|x| format!("{prefix}{key}:{x}"))
+            .collect(),
+    }
+}
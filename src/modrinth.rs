@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use snafu::prelude::*;
+
+use crate::jar_metadata::LoaderKind;
+
+const API: &str = "https://api.modrinth.com/v2";
+
+#[derive(Snafu, Debug)]
+pub enum ModrinthError {
+    #[snafu(display("Failed to reach Modrinth at {url}"))]
+    Request { url: String, source: reqwest::Error },
+    #[snafu(display("Modrinth answered {url} with an unexpected body"))]
+    Body {
+        url: String,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Failed to write {}", path.display()))]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("{url} does not match the hash Modrinth lists for it"))]
+    HashMismatch { url: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModrinthHashes {
+    pub sha512: String,
+    pub sha1: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModrinthFile {
+    pub hashes: ModrinthHashes,
+    pub url: String,
+    pub filename: String,
+    pub primary: bool,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModrinthVersion {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub version_number: String,
+    pub game_versions: Vec<String>,
+    pub loaders: Vec<String>,
+    pub files: Vec<ModrinthFile>,
+}
+
+impl ModrinthVersion {
+    pub fn primary_file(&self) -> Option<&ModrinthFile> {
+        self.files
+            .iter()
+            .find(|x| x.primary)
+            .or_else(|| self.files.first())
+    }
+}
+
+/// The loader names Modrinth filters by. Quilt collections can use Fabric mods too.
+pub fn loader_facets(loader: Option<LoaderKind>) -> Vec<&'static str> {
+    match loader {
+        Some(LoaderKind::Fabric) => vec!["fabric"],
+        Some(LoaderKind::Quilt) => vec!["quilt", "fabric"],
+        Some(LoaderKind::Forge) => vec!["forge"],
+        Some(LoaderKind::NeoForge) => vec!["neoforge"],
+        None => Vec::new(),
+    }
+}
+
+async fn get<T: serde::de::DeserializeOwned>(
+    path: &str,
+    query: &[(&str, String)],
+) -> Result<T, ModrinthError> {
+    let url = format!("{API}{path}");
+    let bytes = reqwest::Client::new()
+        .get(&url)
+        .query(query)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(RequestSnafu { url: &url })?
+        .bytes()
+        .await
+        .context(RequestSnafu { url: &url })?;
+    serde_json::from_slice(&bytes).context(BodySnafu { url })
+}
+
+async fn post<B: Serialize, T: serde::de::DeserializeOwned>(
+    path: &str,
+    body: &B,
+) -> Result<T, ModrinthError> {
+    let url = format!("{API}{path}");
+    let bytes = reqwest::Client::new()
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(body).context(BodySnafu { url: &url })?)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(RequestSnafu { url: &url })?
+        .bytes()
+        .await
+        .context(RequestSnafu { url: &url })?;
+    serde_json::from_slice(&bytes).context(BodySnafu { url })
+}
+
+#[derive(Serialize)]
+struct UpdateRequest<'a> {
+    hashes: &'a [String],
+    algorithm: &'static str,
+    loaders: Vec<&'static str>,
+    game_versions: Vec<&'a str>,
+}
+
+/// The newest version compatible with `game_version` and `loader` for every file hash Modrinth
+/// knows about, keyed by the sha512 of the installed file.
+pub async fn latest_versions(
+    sha512: &[String],
+    loader: Option<LoaderKind>,
+    game_version: &str,
+) -> Result<BTreeMap<String, ModrinthVersion>, ModrinthError> {
+    if sha512.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    post(
+        "/version_files/update",
+        &UpdateRequest {
+            hashes: sha512,
+            algorithm: "sha512",
+            loaders: loader_facets(loader),
+            game_versions: vec![game_version],
+        },
+    )
+    .await
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModrinthProject {
+    pub project_id: String,
+    pub slug: String,
+    pub title: String,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    hits: Vec<ModrinthProject>,
+}
+
+fn json_list(values: &[&str]) -> String {
+    serde_json::to_string(values).unwrap_or_default()
+}
+
+/// Mods matching `query` that have a version for `game_version` and `loader`.
+pub async fn search_mods(
+    query: &str,
+    loader: Option<LoaderKind>,
+    game_version: &str,
+) -> Result<Vec<ModrinthProject>, ModrinthError> {
+    let loaders = loader_facets(loader)
+        .into_iter()
+        .map(|x| format!("categories:{x}"))
+        .collect::<Vec<_>>();
+    let mut facets = vec![
+        vec![String::from("project_type:mod")],
+        vec![format!("versions:{game_version}")],
+    ];
+    if !loaders.is_empty() {
+        facets.push(loaders);
+    }
+    let response: SearchResponse = get(
+        "/search",
+        &[
+            ("query", query.to_owned()),
+            ("facets", serde_json::to_string(&facets).unwrap_or_default()),
+            ("limit", String::from("5")),
+        ],
+    )
+    .await?;
+    Ok(response.hits)
+}
+
+/// Versions of a project for `game_version` and `loader`, newest first.
+pub async fn project_versions(
+    project: &str,
+    loader: Option<LoaderKind>,
+    game_version: &str,
+) -> Result<Vec<ModrinthVersion>, ModrinthError> {
+    get(
+        &format!("/project/{project}/version"),
+        &[
+            ("loaders", json_list(&loader_facets(loader))),
+            ("game_versions", json_list(&[game_version])),
+        ],
+    )
+    .await
+}
+
+/// Downloads a version file to `path` chunk by chunk, checking it against the SHA-512 Modrinth
+/// lists. A file that does not match is removed again.
+pub async fn download_file(file: &ModrinthFile, path: &Path) -> Result<(), ModrinthError> {
+    let url = &file.url;
+    let mut response = reqwest::get(url)
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(RequestSnafu { url })?;
+    let mut out = std::fs::File::create(path).context(WriteSnafu { path })?;
+    let mut hasher = Sha512::new();
+    while let Some(chunk) = response.chunk().await.context(RequestSnafu { url })? {
+        hasher.update(&chunk);
+        out.write_all(&chunk).context(WriteSnafu { path })?;
+    }
+    drop(out);
+    let sha512 = hasher
+        .finalize()
+        .iter()
+        .map(|x| format!("{x:02x}"))
+        .collect::<String>();
+    if !sha512.eq_ignore_ascii_case(&file.hashes.sha512) {
+        let _ = std::fs::remove_file(path);
+        return HashMismatchSnafu { url }.fail();
+    }
+    Ok(())
+}
@@ -0,0 +1,403 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use snafu::prelude::*;
+
+/// Lists and compounds nested deeper than this are rejected instead of overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+#[derive(Snafu, Debug)]
+pub enum NbtError {
+    #[snafu(display("Failed to access {}", path.display()))]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("{} ends in the middle of a tag", path.display()))]
+    Truncated { path: PathBuf },
+    #[snafu(display("{} has an unknown tag type {tag}", path.display()))]
+    UnknownTag { path: PathBuf, tag: u8 },
+    #[snafu(display("{} nests deeper than {MAX_DEPTH} levels", path.display()))]
+    TooDeep { path: PathBuf },
+    #[snafu(display("{} has a string that is not modified UTF-8", path.display()))]
+    InvalidString { path: PathBuf },
+    #[snafu(display("A string of {len} bytes does not fit in {}", path.display()))]
+    StringTooLong { path: PathBuf, len: usize },
+}
+
+/// A named binary tag. Compounds keep their order so a file can be written back unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Self::Compound(entries) => entries.iter().find(|x| x.0 == name).map(|x| &x.1),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Self::List(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&[(String, Tag)]> {
+        match self {
+            Self::Compound(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Byte(x) => Some(x.into()),
+            Self::Short(x) => Some(x.into()),
+            Self::Int(x) => Some(x.into()),
+            Self::Long(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            Self::Byte(_) => 1,
+            Self::Short(_) => 2,
+            Self::Int(_) => 3,
+            Self::Long(_) => 4,
+            Self::Float(_) => 5,
+            Self::Double(_) => 6,
+            Self::ByteArray(_) => 7,
+            Self::String(_) => 8,
+            Self::List(_) => 9,
+            Self::Compound(_) => 10,
+            Self::IntArray(_) => 11,
+            Self::LongArray(_) => 12,
+        }
+    }
+}
+
+/// Java's modified UTF-8: UTF-16 code units encoded one by one, so astral characters become
+/// two 3 byte surrogates, and NUL is `C0 80` so strings never contain a zero byte.
+fn encode_modified_utf8(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7f => bytes.push(unit as u8),
+            0x00 | 0x80..=0x7ff => {
+                bytes.push(0xc0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                bytes.push(0xe0 | (unit >> 12) as u8);
+                bytes.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    bytes
+}
+
+/// The reverse of [`encode_modified_utf8`]. Java strings may hold unpaired surrogates, those
+/// become U+FFFD.
+fn decode_modified_utf8(bytes: &[u8]) -> Option<String> {
+    fn continuation(bytes: &mut impl Iterator<Item = u8>) -> Option<u16> {
+        bytes
+            .next()
+            .filter(|x| x & 0xc0 == 0x80)
+            .map(|x| u16::from(x & 0x3f))
+    }
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter().copied();
+    while let Some(byte) = bytes.next() {
+        let unit = match byte {
+            0x00..=0x7f => u16::from(byte),
+            0xc0..=0xdf => (u16::from(byte & 0x1f) << 6) | continuation(&mut bytes)?,
+            0xe0..=0xef => {
+                let high = continuation(&mut bytes)?;
+                (u16::from(byte & 0x0f) << 12) | (high << 6) | continuation(&mut bytes)?
+            }
+            _ => return None,
+        };
+        units.push(unit);
+    }
+    Some(
+        char::decode_utf16(units)
+            .map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect(),
+    )
+}
+
+struct Writer<'a> {
+    out: Vec<u8>,
+    path: &'a Path,
+}
+
+impl Writer<'_> {
+    fn len(&mut self, len: usize) {
+        self.out.extend_from_slice(&(len as i32).to_be_bytes());
+    }
+
+    fn string(&mut self, value: &str) -> Result<(), NbtError> {
+        let bytes = encode_modified_utf8(value);
+        let len = u16::try_from(bytes.len())
+            .ok()
+            .context(StringTooLongSnafu {
+                path: self.path,
+                len: bytes.len(),
+            })?;
+        self.out.extend_from_slice(&len.to_be_bytes());
+        self.out.extend(bytes);
+        Ok(())
+    }
+
+    fn payload(&mut self, tag: &Tag) -> Result<(), NbtError> {
+        match tag {
+            Tag::Byte(x) => self.out.extend_from_slice(&x.to_be_bytes()),
+            Tag::Short(x) => self.out.extend_from_slice(&x.to_be_bytes()),
+            Tag::Int(x) => self.out.extend_from_slice(&x.to_be_bytes()),
+            Tag::Long(x) => self.out.extend_from_slice(&x.to_be_bytes()),
+            Tag::Float(x) => self.out.extend_from_slice(&x.to_be_bytes()),
+            Tag::Double(x) => self.out.extend_from_slice(&x.to_be_bytes()),
+            Tag::ByteArray(x) => {
+                self.len(x.len());
+                self.out.extend(x.iter().map(|x| *x as u8));
+            }
+            Tag::String(x) => self.string(x)?,
+            Tag::List(x) => {
+                // an empty list is written with the end tag as its element type
+                self.out.push(x.first().map_or(0, Tag::id));
+                self.len(x.len());
+                for element in x {
+                    self.payload(element)?;
+                }
+            }
+            Tag::Compound(x) => {
+                for (name, tag) in x {
+                    self.out.push(tag.id());
+                    self.string(name)?;
+                    self.payload(tag)?;
+                }
+                self.out.push(0);
+            }
+            Tag::IntArray(x) => {
+                self.len(x.len());
+                for int in x {
+                    self.out.extend_from_slice(&int.to_be_bytes());
+                }
+            }
+            Tag::LongArray(x) => {
+                self.len(x.len());
+                for long in x {
+                    self.out.extend_from_slice(&long.to_be_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    path: &'a Path,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], NbtError> {
+        ensure!(self.bytes.len() >= len, TruncatedSnafu { path: self.path });
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn len(&mut self) -> Result<usize, NbtError> {
+        // negative lengths only show up in empty lists
+        Ok(i32::from_be_bytes(self.array()?).max(0) as usize)
+    }
+
+    fn string(&mut self) -> Result<String, NbtError> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        let path = self.path;
+        decode_modified_utf8(self.take(len)?).context(InvalidStringSnafu { path })
+    }
+
+    fn payload(&mut self, tag: u8, depth: usize) -> Result<Tag, NbtError> {
+        ensure!(depth < MAX_DEPTH, TooDeepSnafu { path: self.path });
+        Ok(match tag {
+            1 => Tag::Byte(i8::from_be_bytes(self.array()?)),
+            2 => Tag::Short(i16::from_be_bytes(self.array()?)),
+            3 => Tag::Int(i32::from_be_bytes(self.array()?)),
+            4 => Tag::Long(i64::from_be_bytes(self.array()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let len = self.len()?;
+                Tag::ByteArray(self.take(len)?.iter().map(|x| *x as i8).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let [element] = self.array()?;
+                let len = self.len()?;
+                let mut list = Vec::new();
+                for _ in 0..len {
+                    list.push(self.payload(element, depth + 1)?);
+                }
+                Tag::List(list)
+            }
+            10 => {
+                let mut entries = Vec::new();
+                loop {
+                    let [tag] = self.array()?;
+                    if tag == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    entries.push((name, self.payload(tag, depth + 1)?));
+                }
+                Tag::Compound(entries)
+            }
+            11 => {
+                let len = self.len()?;
+                let mut ints = Vec::new();
+                for _ in 0..len {
+                    ints.push(i32::from_be_bytes(self.array()?));
+                }
+                Tag::IntArray(ints)
+            }
+            12 => {
+                let len = self.len()?;
+                let mut longs = Vec::new();
+                for _ in 0..len {
+                    longs.push(i64::from_be_bytes(self.array()?));
+                }
+                Tag::LongArray(longs)
+            }
+            tag => {
+                return UnknownTagSnafu {
+                    path: self.path,
+                    tag,
+                }
+                .fail()
+            }
+        })
+    }
+}
+
+/// Reads the root compound of an NBT file, gzipped (`level.dat`) or not (`servers.dat`).
+pub fn read_file(path: &Path) -> Result<Tag, NbtError> {
+    let raw = std::fs::read(path).context(IoSnafu { path })?;
+    let bytes = if raw.starts_with(&[0x1f, 0x8b]) {
+        let mut bytes = Vec::new();
+        flate2::read::GzDecoder::new(raw.as_slice())
+            .read_to_end(&mut bytes)
+            .context(IoSnafu { path })?;
+        bytes
+    } else {
+        raw
+    };
+    let mut reader = Reader {
+        bytes: &bytes,
+        path,
+    };
+    let [tag] = reader.array()?;
+    // the root's name is always empty
+    reader.string()?;
+    reader.payload(tag, 0)
+}
+
+/// Writes `root` as the root compound of an uncompressed NBT file like `servers.dat`, through a
+/// temporary file so the game never reads half of it.
+pub fn write_file(path: &Path, root: &Tag) -> Result<(), NbtError> {
+    let mut writer = Writer {
+        out: vec![root.id()],
+        path,
+    };
+    writer.string("")?;
+    writer.payload(root)?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, writer.out).context(IoSnafu { path: &tmp })?;
+    std::fs::rename(&tmp, path).context(IoSnafu { path })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modified_utf8() {
+        let bytes = encode_modified_utf8("a\0é😀");
+        assert_eq!(
+            bytes,
+            [b'a', 0xc0, 0x80, 0xc3, 0xa9, 0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]
+        );
+        assert_eq!(decode_modified_utf8(&bytes).as_deref(), Some("a\0é😀"));
+        // a lone high surrogate, which Java allows in a string
+        assert_eq!(
+            decode_modified_utf8(&[0xed, 0xa0, 0xbd]).as_deref(),
+            Some("\u{fffd}")
+        );
+        assert_eq!(decode_modified_utf8(&[0xc3]), None);
+        assert_eq!(decode_modified_utf8(&[0xf0, 0x9f, 0x98, 0x80]), None);
+    }
+
+    #[test]
+    fn writes_and_reads_back() {
+        let dir = std::env::temp_dir().join(format!("nbt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("servers.dat");
+        let root = Tag::Compound(vec![(
+            String::from("servers"),
+            Tag::List(vec![Tag::Compound(vec![
+                (
+                    String::from("name"),
+                    Tag::String(String::from("伺服器 😀\0")),
+                ),
+                (String::from("ip"), Tag::String(String::from("localhost"))),
+                (String::from("hidden"), Tag::Byte(0)),
+            ])]),
+        )]);
+        write_file(&path, &root).unwrap();
+        assert_eq!(read_file(&path).unwrap(), root);
+        assert!(!dir.join("servers.dat.tmp").exists());
+
+        let long = Tag::Compound(vec![(
+            String::from("name"),
+            Tag::String("é".repeat(40_000)),
+        )]);
+        let error = write_file(&path, &long).unwrap_err();
+        assert!(matches!(error, NbtError::StringTooLong { len: 80_000, .. }));
+        // a failed write leaves the file as it was
+        assert_eq!(read_file(&path).unwrap(), root);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
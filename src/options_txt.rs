@@ -0,0 +1,217 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+use strum::EnumIter;
+
+use crate::persistence::{self, PersistenceError};
+
+const DEFAULT_OPTIONS_FILE: &str = "default_options";
+
+/// Written into the `options.txt` of every newly created collection.
+pub static DEFAULT_OPTIONS: GlobalSignal<GameOptions> =
+    GlobalSignal::new(|| persistence::load(DEFAULT_OPTIONS_FILE));
+
+const VIDEO_KEYS: &[&str] = &[
+    "ao",
+    "biomeBlendRadius",
+    "bobView",
+    "darknessEffectScale",
+    "enableVsync",
+    "entityDistanceScaling",
+    "entityShadows",
+    "fov",
+    "fovEffectScale",
+    "fullscreen",
+    "fullscreenResolution",
+    "gamma",
+    "glintSpeed",
+    "glintStrength",
+    "graphicsMode",
+    "guiScale",
+    "maxFps",
+    "mipmapLevels",
+    "overrideHeight",
+    "overrideWidth",
+    "particles",
+    "prioritizeChunkUpdates",
+    "renderClouds",
+    "renderDistance",
+    "screenEffectScale",
+    "simulationDistance",
+];
+const AUDIO_KEYS: &[&str] = &["directionalAudio", "showSubtitles", "soundDevice"];
+const CONTROL_KEYS: &[&str] = &[
+    "autoJump",
+    "discrete_mouse_scroll",
+    "invertYMouse",
+    "mouseSensitivity",
+    "mouseWheelSensitivity",
+    "rawMouseInput",
+    "toggleCrouch",
+    "toggleSprint",
+    "touchscreen",
+];
+
+#[derive(Snafu, Debug)]
+pub enum OptionsError {
+    #[snafu(display("Failed to access {}", path.display()))]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// The groups the options editor and settings copy work with. Keys in none of them, like chat
+/// and accessibility settings, are left alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum OptionCategory {
+    Keybinds,
+    Video,
+    Audio,
+    Controls,
+}
+
+impl OptionCategory {
+    pub fn of(key: &str) -> Option<Self> {
+        if key.starts_with("key_") {
+            Some(Self::Keybinds)
+        } else if key.starts_with("soundCategory_") || AUDIO_KEYS.contains(&key) {
+            Some(Self::Audio)
+        } else if VIDEO_KEYS.contains(&key) {
+            Some(Self::Video)
+        } else if CONTROL_KEYS.contains(&key) {
+            Some(Self::Controls)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for OptionCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keybinds => write!(f, "按鍵綁定"),
+            Self::Video => write!(f, "顯示"),
+            Self::Audio => write!(f, "音效"),
+            Self::Controls => write!(f, "操作"),
+        }
+    }
+}
+
+/// The game's `options.txt`, `key:value` per line, kept in file order so rewriting it only
+/// touches the changed lines.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct GameOptions {
+    pub entries: Vec<(String, String)>,
+}
+
+impl GameOptions {
+    pub fn path(game_directory: &Path) -> PathBuf {
+        game_directory.join("options.txt")
+    }
+
+    pub fn parse(text: &str) -> Self {
+        Self {
+            entries: text
+                .lines()
+                .filter_map(|x| x.split_once(':'))
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect(),
+        }
+    }
+
+    /// Missing until the game has been started once.
+    pub fn load(game_directory: &Path) -> Result<Self, OptionsError> {
+        let path = Self::path(game_directory);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Ok(Self::parse(&text)),
+            Err(x) if x.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(OptionsError::Io { path, source }),
+        }
+    }
+
+    /// Creates the game directory when the collection has never been started.
+    pub fn save(&self, game_directory: &Path) -> Result<(), OptionsError> {
+        std::fs::create_dir_all(game_directory).context(IoSnafu {
+            path: game_directory,
+        })?;
+        let path = Self::path(game_directory);
+        std::fs::write(&path, self.to_string()).context(IoSnafu { path })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|x| x.0 == key)
+            .map(|x| x.1.as_str())
+    }
+
+    /// Replaces `key` in place, or appends it when the file does not have it yet.
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.entries.iter_mut().find(|x| x.0 == key) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((key.to_owned(), value)),
+        }
+    }
+
+    pub fn in_category(
+        &self,
+        category: OptionCategory,
+    ) -> impl Iterator<Item = &(String, String)> + '_ {
+        self.entries
+            .iter()
+            .filter(move |x| OptionCategory::of(&x.0) == Some(category))
+    }
+
+    /// Takes over every option `other` has in `categories`, keeping the rest of `self`.
+    pub fn copy_from(&mut self, other: &GameOptions, categories: &BTreeSet<OptionCategory>) {
+        for (key, value) in &other.entries {
+            if OptionCategory::of(key).is_some_and(|x| categories.contains(&x)) {
+                self.set(key, value.clone());
+            }
+        }
+    }
+}
+
+/// Keeps the options of `source` in `categories` as the template for new collections.
+pub fn save_default_options(
+    source: &GameOptions,
+    categories: &BTreeSet<OptionCategory>,
+) -> Result<(), PersistenceError> {
+    let mut template = GameOptions::default();
+    template.copy_from(source, categories);
+    *DEFAULT_OPTIONS.write() = template;
+    persistence::save(DEFAULT_OPTIONS_FILE, &*DEFAULT_OPTIONS.read())
+}
+
+pub fn clear_default_options() -> Result<(), PersistenceError> {
+    *DEFAULT_OPTIONS.write() = GameOptions::default();
+    persistence::save(DEFAULT_OPTIONS_FILE, &*DEFAULT_OPTIONS.read())
+}
+
+/// Writes the template into a new collection's `options.txt`, which the game picks up on its
+/// first start.
+pub fn apply_default_options(game_directory: &Path) -> Result<(), OptionsError> {
+    let template = DEFAULT_OPTIONS.read().clone();
+    if template.entries.is_empty() {
+        return Ok(());
+    }
+    let mut options = GameOptions::load(game_directory)?;
+    for (key, value) in template.entries {
+        options.set(&key, value);
+    }
+    options.save(game_directory)
+}
+
+impl std::fmt::Display for GameOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (key, value) in &self.entries {
+            writeln!(f, "{key}:{value}")?;
+        }
+        Ok(())
+    }
+}
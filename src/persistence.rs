@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dioxus_logger::tracing::{error, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use snafu::prelude::*;
+
+use crate::ErrorFormatted;
+
+#[derive(Snafu, Debug)]
+pub enum PersistenceError {
+    #[snafu(display("Failed to create data directory {}", path.display()))]
+    CreateDir {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to serialize {name}"))]
+    Serialize {
+        name: String,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Failed to write {}", path.display()))]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to read {}", path.display()))]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to parse {}", path.display()))]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+/// Directory where launcher-side state (that `rust_lib` does not know about) is kept.
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("prag-portal")
+}
+
+fn file_path(name: &str) -> PathBuf {
+    data_dir().join(format!("{name}.json"))
+}
+
+/// Loads `{data_dir}/{name}.json`, falling back to `T::default()` when it is missing or corrupt.
+/// A corrupt file is logged and moved aside to `{name}.json.{timestamp}.corrupt` first, so the
+/// next save does not overwrite what is left of it.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let path = file_path(name);
+    match read(&path) {
+        Ok(x) => x.unwrap_or_default(),
+        Err(x) => {
+            error!("{}", x.to_formatted());
+            if matches!(x, PersistenceError::Parse { .. }) {
+                set_aside(&path);
+            }
+            T::default()
+        }
+    }
+}
+
+fn read<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, PersistenceError> {
+    let bytes = match std::fs::read(path) {
+        Ok(x) => x,
+        Err(x) if x.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(x) => return Err(x).context(ReadSnafu { path }),
+    };
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .context(ParseSnafu { path })
+}
+
+fn set_aside(path: &Path) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let corrupt = path.with_extension(format!("json.{timestamp}.corrupt"));
+    match std::fs::rename(path, &corrupt) {
+        Ok(()) => warn!(
+            "Moved the unreadable {} to {}",
+            path.display(),
+            corrupt.display()
+        ),
+        Err(x) => error!("Failed to move {} aside: {x}", path.display()),
+    }
+}
+
+/// Writes `value` to `{data_dir}/{name}.json`, going through a temporary file so a crash never
+/// leaves a half written file behind.
+pub fn save<T: Serialize>(name: &str, value: &T) -> Result<(), PersistenceError> {
+    let dir = data_dir();
+    std::fs::create_dir_all(&dir).context(CreateDirSnafu { path: &dir })?;
+    let bytes = serde_json::to_vec_pretty(value).context(SerializeSnafu { name })?;
+    let path = file_path(name);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, bytes).context(WriteSnafu { path: &tmp })?;
+    std::fs::rename(&tmp, &path).context(WriteSnafu { path: &path })?;
+    Ok(())
+}
@@ -0,0 +1,420 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    nbt::{self, NbtError, Tag},
+    persistence::{self, PersistenceError},
+};
+
+const SHARED_SERVERS_FILE: &str = "shared_servers";
+const DEFAULT_PORT: u16 = 25565;
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// What a client sends when it pings to find out which version a server runs.
+const ANY_PROTOCOL: i32 = -1;
+/// Status responses are a JSON string of at most 32767 characters, with a favicon in it.
+const MAX_PACKET: usize = 1 << 21;
+
+/// Servers kept by the launcher, synced into the collections that should have them.
+pub static SHARED_SERVERS: GlobalSignal<Vec<SharedServer>> =
+    GlobalSignal::new(|| persistence::load(SHARED_SERVERS_FILE));
+
+#[derive(Snafu, Debug)]
+pub enum ServerError {
+    #[snafu(transparent)]
+    Nbt { source: NbtError },
+    #[snafu(display("Failed to connect to {address}"))]
+    Connect {
+        address: String,
+        source: std::io::Error,
+    },
+    #[snafu(display("{address} did not answer within {} seconds", PING_TIMEOUT.as_secs()))]
+    Timeout { address: String },
+    #[snafu(display("Failed to talk to {address}"))]
+    Exchange {
+        address: String,
+        source: std::io::Error,
+    },
+    #[snafu(display("{address} sent an invalid status: {reason}"))]
+    Response {
+        address: String,
+        reason: &'static str,
+    },
+    #[snafu(display("{address} sent a status that is not valid JSON"))]
+    Json {
+        address: String,
+        source: serde_json::Error,
+    },
+}
+
+/// An entry of `servers.dat`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerEntry {
+    pub name: String,
+    /// `host` or `host:port`
+    pub address: String,
+    /// Everything else the game keeps, like the icon and the resource pack choice
+    pub extra: Vec<(String, Tag)>,
+}
+
+impl ServerEntry {
+    pub fn new(name: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            address: address.into(),
+            extra: Vec::new(),
+        }
+    }
+
+    fn from_tag(tag: &Tag) -> Option<Self> {
+        let mut entry = Self::new("", tag.get("ip")?.as_str()?);
+        for (key, value) in tag.as_compound()? {
+            match key.as_str() {
+                "ip" => {}
+                "name" => entry.name = value.as_str().unwrap_or_default().to_owned(),
+                _ => entry.extra.push((key.clone(), value.clone())),
+            }
+        }
+        Some(entry)
+    }
+
+    fn to_tag(&self) -> Tag {
+        let mut entries = vec![
+            (String::from("name"), Tag::String(self.name.clone())),
+            (String::from("ip"), Tag::String(self.address.clone())),
+        ];
+        entries.extend(self.extra.iter().cloned());
+        Tag::Compound(entries)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SharedServer {
+    pub name: String,
+    pub address: String,
+}
+
+fn servers_path(game_directory: &Path) -> PathBuf {
+    game_directory.join("servers.dat")
+}
+
+/// The multiplayer servers saved in the game, in the order it lists them.
+pub fn read_servers(game_directory: &Path) -> Result<Vec<ServerEntry>, ServerError> {
+    let path = servers_path(game_directory);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let root = nbt::read_file(&path)?;
+    Ok(root
+        .get("servers")
+        .and_then(|x| x.as_list())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(ServerEntry::from_tag)
+        .collect())
+}
+
+pub fn write_servers(game_directory: &Path, servers: &[ServerEntry]) -> Result<(), ServerError> {
+    std::fs::create_dir_all(game_directory).map_err(|source| NbtError::Io {
+        path: game_directory.to_owned(),
+        source,
+    })?;
+    let root = Tag::Compound(vec![(
+        String::from("servers"),
+        Tag::List(servers.iter().map(ServerEntry::to_tag).collect()),
+    )]);
+    nbt::write_file(&servers_path(game_directory), &root)?;
+    Ok(())
+}
+
+pub fn save_shared_servers(servers: Vec<SharedServer>) -> Result<(), PersistenceError> {
+    *SHARED_SERVERS.write() = servers;
+    persistence::save(SHARED_SERVERS_FILE, &*SHARED_SERVERS.read())
+}
+
+/// Adds the shared servers a collection is missing and renames the ones it has, matched by
+/// address. Servers only the collection has are left where they are.
+pub fn sync_shared(game_directory: &Path) -> Result<(), ServerError> {
+    let mut servers = read_servers(game_directory)?;
+    for shared in SHARED_SERVERS.read().iter() {
+        match servers.iter_mut().find(|x| x.address == shared.address) {
+            Some(entry) => entry.name.clone_from(&shared.name),
+            None => servers.push(ServerEntry::new(&shared.name, &shared.address)),
+        }
+    }
+    write_servers(game_directory, &servers)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerStatus {
+    /// The description with its formatting codes taken out
+    pub motd: String,
+    pub online: u32,
+    pub max: u32,
+    pub version: String,
+    /// `None` when the server closed the connection instead of answering the ping
+    pub latency: Option<Duration>,
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    version: Option<StatusVersion>,
+    players: Option<StatusPlayers>,
+    description: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct StatusVersion {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct StatusPlayers {
+    online: u32,
+    max: u32,
+}
+
+/// Flattens a chat component, which the description is on 1.7 and later, to its text.
+fn component_text(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::String(x) => out.push_str(x),
+        serde_json::Value::Array(x) => x.iter().for_each(|x| component_text(x, out)),
+        serde_json::Value::Object(x) => {
+            if let Some(text) = x.get("text") {
+                component_text(text, out);
+            }
+            if let Some(extra) = x.get("extra") {
+                component_text(extra, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Drops the `§` formatting codes legacy descriptions use.
+fn strip_formatting(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// `host`, `host:port` and `[v6]:port`.
+pub(crate) fn split_address(address: &str) -> (String, u16) {
+    let address = address.trim();
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') || host.ends_with(']') => {
+            (host, port.parse().ok())
+        }
+        _ => (address, None),
+    };
+    (
+        host.trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_owned(),
+        port.unwrap_or(DEFAULT_PORT),
+    )
+}
+
+fn write_varint(out: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7f == 0 {
+            out.push(value as u8);
+            return;
+        }
+        out.push(((value & 0x7f) | 0x80) as u8);
+        value >>= 7;
+    }
+}
+
+async fn read_varint(stream: &mut (impl AsyncRead + Unpin)) -> std::io::Result<i32> {
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = stream.read_u8().await?;
+        value |= u32::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "VarInt is longer than 5 bytes",
+    ))
+}
+
+fn packet(id: i32, body: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    write_varint(&mut data, id);
+    data.extend_from_slice(body);
+    let mut packet = Vec::new();
+    write_varint(&mut packet, data.len() as i32);
+    packet.extend(data);
+    packet
+}
+
+/// Reads a packet, returning its id and body.
+async fn read_packet(
+    stream: &mut (impl AsyncRead + Unpin),
+    address: &str,
+) -> Result<(i32, Vec<u8>), ServerError> {
+    let len = read_varint(stream)
+        .await
+        .context(ExchangeSnafu { address })?;
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|x| *x <= MAX_PACKET)
+        .context(ResponseSnafu {
+            address,
+            reason: "packet length out of range",
+        })?;
+    let mut data = vec![0; len];
+    stream
+        .read_exact(&mut data)
+        .await
+        .context(ExchangeSnafu { address })?;
+    let mut body = data.as_slice();
+    let id = read_varint(&mut body)
+        .await
+        .context(ExchangeSnafu { address })?;
+    Ok((id, body.to_vec()))
+}
+
+/// Speaks the server list ping over `stream`, so anything that answers it, a stand-in
+/// listening on localhost included, can be queried.
+pub async fn query(
+    mut stream: impl AsyncRead + AsyncWrite + Unpin,
+    host: &str,
+    port: u16,
+) -> Result<ServerStatus, ServerError> {
+    let address = format!("{host}:{port}");
+    let address = address.as_str();
+    let mut handshake = Vec::new();
+    write_varint(&mut handshake, ANY_PROTOCOL);
+    write_varint(&mut handshake, host.len() as i32);
+    handshake.extend_from_slice(host.as_bytes());
+    handshake.extend_from_slice(&port.to_be_bytes());
+    // next state: status
+    write_varint(&mut handshake, 1);
+    let mut request = packet(0x00, &handshake);
+    request.extend(packet(0x00, &[]));
+    stream
+        .write_all(&request)
+        .await
+        .context(ExchangeSnafu { address })?;
+    let (id, body) = read_packet(&mut stream, address).await?;
+    ensure!(
+        id == 0x00,
+        ResponseSnafu {
+            address,
+            reason: "unexpected packet id",
+        }
+    );
+    let mut body = body.as_slice();
+    let len = read_varint(&mut body)
+        .await
+        .context(ExchangeSnafu { address })?;
+    let json = usize::try_from(len)
+        .ok()
+        .and_then(|x| body.get(..x))
+        .context(ResponseSnafu {
+            address,
+            reason: "truncated JSON",
+        })?;
+    let response: StatusResponse = serde_json::from_slice(json).context(JsonSnafu { address })?;
+    let mut motd = String::new();
+    if let Some(description) = &response.description {
+        component_text(description, &mut motd);
+    }
+    let sent = Instant::now();
+    let latency = async {
+        let payload = 0i64.to_be_bytes();
+        stream.write_all(&packet(0x01, &payload)).await.ok()?;
+        let (id, body) = read_packet(&mut stream, address).await.ok()?;
+        (id == 0x01 && body == payload).then(|| sent.elapsed())
+    }
+    .await;
+    Ok(ServerStatus {
+        motd: strip_formatting(&motd),
+        online: response.players.as_ref().map_or(0, |x| x.online),
+        max: response.players.as_ref().map_or(0, |x| x.max),
+        version: response.version.map(|x| x.name).unwrap_or_default(),
+        latency,
+    })
+}
+
+/// Connects to `address` and asks for its status.
+pub async fn ping(address: &str) -> Result<ServerStatus, ServerError> {
+    let (host, port) = split_address(address);
+    let exchange = async {
+        let stream = tokio::net::TcpStream::connect((host.as_str(), port))
+            .await
+            .context(ConnectSnafu { address })?;
+        query(stream, &host, port).await
+    };
+    tokio::time::timeout(PING_TIMEOUT, exchange)
+        .await
+        .ok()
+        .context(TimeoutSnafu { address })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers one status request and one ping the way a server does.
+    async fn stand_in(listener: tokio::net::TcpListener, status: &str) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let (id, _) = read_packet(&mut stream, "client").await.unwrap();
+        assert_eq!(id, 0x00, "handshake");
+        let (id, body) = read_packet(&mut stream, "client").await.unwrap();
+        assert_eq!((id, body.len()), (0x00, 0), "status request");
+        let mut response = Vec::new();
+        write_varint(&mut response, status.len() as i32);
+        response.extend_from_slice(status.as_bytes());
+        stream.write_all(&packet(0x00, &response)).await.unwrap();
+        let (id, payload) = read_packet(&mut stream, "client").await.unwrap();
+        assert_eq!(id, 0x01, "ping");
+        stream.write_all(&packet(0x01, &payload)).await.unwrap();
+    }
+
+    #[test]
+    fn queries_a_local_server() {
+        let status = r#"{
+            "version": {"name": "1.20.4", "protocol": 765},
+            "players": {"online": 3, "max": 20},
+            "description": {"text": "§a歡迎", "extra": [{"text": " 光臨"}]}
+        }"#;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let status = runtime
+            .block_on(async {
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let port = listener.local_addr().unwrap().port();
+                let server = tokio::spawn(stand_in(listener, status));
+                let stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+                    .await
+                    .unwrap();
+                let status = query(stream, "127.0.0.1", port).await;
+                server.await.unwrap();
+                status
+            })
+            .unwrap();
+        assert_eq!(status.motd, "歡迎 光臨");
+        assert_eq!((status.online, status.max), (3, 20));
+        assert_eq!(status.version, "1.20.4");
+        assert!(status.latency.is_some());
+    }
+}
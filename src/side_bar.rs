@@ -4,13 +4,15 @@ use dioxus::prelude::*;
 use rust_lib::api::shared_resources::{collection::CollectionId, entry::STORAGE};
 
 use crate::{
+    download_queue::queue_summary,
+    favorites::{favorites_of, is_favorite, toggle_favorite},
     text_scroller::use_text_scroller,
     BaseComponents::{
         atoms::button::{Button, Roundness},
         molecules::switcher::StateSwitcher,
         string_placements::{Alignment, ContentType, Contents, Image, Text},
     },
-    Pages, ARROW_RIGHT, EXPLORE, HISTORY, HOME, SIDEBAR_COLLECTION, SIM_CARD,
+    use_error_handler, Pages, ARROW_RIGHT, EXPLORE, HISTORY, HOME, SIDEBAR_COLLECTION, SIM_CARD,
 };
 
 #[component]
@@ -27,9 +29,15 @@ pub fn SideBar() -> Element {
 
     let keys = use_context::<Memo<Vec<CollectionId>>>();
 
+    let favorites = favorites_of(&keys());
+
     let binding = STORAGE.collections.read();
 
-    let collection_preview = binding.iter().take(3);
+    // the folded sidebar previews favorites when there are any
+    let collection_preview = binding
+        .iter()
+        .filter(|(id, _)| favorites.is_empty() || favorites.contains(id))
+        .take(3);
 
     let folded_images = rsx! {
         div {
@@ -156,7 +164,7 @@ pub fn SideBar() -> Element {
                                 .css(
                                     "hidden group-aria-[busy=false]:group-aria-selected/active:block group-aria-busy:hidden",
                                 ),
-                            ContentType::text("無下載佇列")
+                            ContentType::text(queue_summary())
                                 .align_right()
                                 .css("group-aria-selected/active:hidden group-aria-busy:hidden text-hint"),
                         ],
@@ -194,18 +202,27 @@ fn SidebarCollectionBlock(collection_id: ReadOnlySignal<CollectionId>) -> Elemen
             { ContentType::image(&picture_path)
             .css("absolute inset-0 transition-all w-full h-full object-cover inline-flex items-center rounded-[15px] group-aria-expanded:rounded-[5px]")
             }
-            div {
-                class: "absolute inset-x-0 bottom-0 w-3 h-3 bg-[#CCE246] rounded-full"
+            if is_favorite(&collection_id()) {
+                div {
+                    class: "absolute inset-x-0 bottom-0 w-3 h-3 bg-[#CCE246] rounded-full"
+                }
             }
         }
     };
 
     let (element, status, style) = use_text_scroller();
 
+    let mut error = use_error_handler();
+
     rsx! {
         div {
             class: "group",
             aria_selected: status(),
+            title: "按右鍵加入或移出我的錦集",
+            oncontextmenu: move |evt| {
+                evt.prevent_default();
+                error.set(Some(toggle_favorite(&collection_id()).map_err(Into::into)));
+            },
             Button {
                 roundness: Roundness::Squircle,
                 string_placements: vec![
@@ -0,0 +1,416 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use dioxus::prelude::*;
+use rust_lib::api::{
+    backend_exclusive::{mod_management::mods::ModMetadata, vanilla::version::VersionMetadata},
+    shared_resources::collection::{CollectionId, ModLoader},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snafu::prelude::*;
+
+use crate::{
+    collection_stats::unix_now,
+    journal::{with_mut_collection, Change, CollectionState},
+    local_mods::{local_mods_of, replace_local_mods, LocalMod},
+    persistence::{self, PersistenceError},
+};
+
+const SETTINGS_FILE: &str = "snapshot_settings";
+/// Directories of the game directory that are part of a snapshot.
+const SNAPSHOT_DIRECTORIES: [&str; 2] = ["mods", "config"];
+
+pub static SNAPSHOT_SETTINGS: GlobalSignal<SnapshotSettings> =
+    GlobalSignal::new(|| persistence::load(SETTINGS_FILE));
+
+#[derive(Snafu, Debug)]
+pub enum SnapshotError {
+    #[snafu(display("Failed to access {}", path.display()))]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Failed to parse snapshot manifest {}", path.display()))]
+    Manifest {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Snapshotting the collection's files stopped before it finished"))]
+    Interrupted { source: tokio::task::JoinError },
+    #[snafu(display("Snapshot object {hash} is missing"))]
+    MissingObject { hash: String },
+    #[snafu(transparent)]
+    Persistence { source: PersistenceError },
+    #[snafu(display("Failed to restore collection metadata: {message}"))]
+    Restore { message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotReason {
+    Manual,
+    BulkModUpdate,
+    BulkModRemoval,
+    LoaderChange,
+    VersionMigration,
+}
+
+impl std::fmt::Display for SnapshotReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Manual => "手動建立",
+            Self::BulkModUpdate => "批次更新模組前",
+            Self::BulkModRemoval => "批次移除模組前",
+            Self::LoaderChange => "更換模組載入器前",
+            Self::VersionMigration => "遷移遊戲版本前",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSettings {
+    /// Older snapshots beyond this count are deleted.
+    pub max_per_collection: usize,
+    /// Snapshots older than this are deleted, `None` keeps them forever.
+    pub max_age_days: Option<u64>,
+}
+
+impl Default for SnapshotSettings {
+    fn default() -> Self {
+        Self {
+            max_per_collection: 10,
+            max_age_days: Some(30),
+        }
+    }
+}
+
+pub fn save_settings(settings: SnapshotSettings) -> Result<(), PersistenceError> {
+    *SNAPSHOT_SETTINGS.write() = settings;
+    persistence::save(SETTINGS_FILE, &*SNAPSHOT_SETTINGS.read())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unix timestamp in seconds, also the manifest's file name. Unique within the collection,
+    /// a snapshot taken in the same second as another one moves on to the next free second.
+    pub created: u64,
+    pub reason: SnapshotReason,
+    pub state: CollectionState,
+    /// `None` in manifests written before the game was recorded
+    #[serde(default)]
+    pub game: Option<SnapshotGame>,
+    pub mods: Vec<ModMetadata>,
+    /// The dropped jars among `files`, `None` in manifests written before they were recorded
+    #[serde(default)]
+    pub local_mods: Option<Vec<LocalMod>>,
+    /// Path relative to the game directory -> content hash
+    pub files: BTreeMap<PathBuf, String>,
+    /// Sum of the snapshotted files, before deduplication
+    pub size: u64,
+}
+
+/// The game version and loader the collection ran when it was snapshotted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotGame {
+    pub minecraft_version: VersionMetadata,
+    pub mod_loader: Option<ModLoader>,
+}
+
+/// What changed between a snapshot and the collection as it is now.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SnapshotDiff {
+    pub changes: Vec<Change>,
+    pub added_files: Vec<PathBuf>,
+    pub removed_files: Vec<PathBuf>,
+    pub modified_files: Vec<PathBuf>,
+}
+
+fn snapshots_root() -> PathBuf {
+    persistence::data_dir().join("snapshots")
+}
+
+/// Files are stored once by content hash and shared between every snapshot.
+fn objects_dir() -> PathBuf {
+    snapshots_root().join("objects")
+}
+
+fn collection_dir(collection_id: &CollectionId) -> PathBuf {
+    let hash = hex(&Sha256::digest(collection_id.to_string().as_bytes()));
+    snapshots_root().join("collections").join(hash)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+fn hash_file(path: &Path) -> Result<String, SnapshotError> {
+    let mut file = std::fs::File::open(path).context(IoSnafu { path })?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).context(IoSnafu { path })?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+fn walk(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), SnapshotError> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir).context(IoSnafu { path: dir })? {
+        let path = entry.context(IoSnafu { path: dir })?.path();
+        if path.is_dir() {
+            walk(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+fn current_files(game_directory: &Path) -> Result<BTreeMap<PathBuf, String>, SnapshotError> {
+    let mut files = Vec::new();
+    for dir in SNAPSHOT_DIRECTORIES {
+        walk(game_directory, &game_directory.join(dir), &mut files)?;
+    }
+    files
+        .into_iter()
+        .map(|x| Ok((x.clone(), hash_file(&game_directory.join(x))?)))
+        .collect()
+}
+
+/// Stores a copy of `source` as an object. A hard link would share every later write to the
+/// game file, jars included, since they get copied over in place.
+fn store_object(source: &Path, hash: &str) -> Result<(), SnapshotError> {
+    let dir = objects_dir();
+    let target = dir.join(hash);
+    if target.exists() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(&dir).context(IoSnafu { path: &dir })?;
+    // an interrupted copy must not pass for the object
+    let partial = dir.join(format!("{hash}.partial"));
+    std::fs::copy(source, &partial).context(IoSnafu { path: &partial })?;
+    std::fs::rename(&partial, &target).context(IoSnafu { path: &target })?;
+    Ok(())
+}
+
+/// Hashes and stores every snapshotted file of `game_directory`, returning them with their
+/// total size.
+fn store_files(game_directory: &Path) -> Result<(BTreeMap<PathBuf, String>, u64), SnapshotError> {
+    let files = current_files(game_directory)?;
+    let mut size = 0;
+    for (relative, hash) in &files {
+        let path = game_directory.join(relative);
+        size += path.metadata().map(|x| x.len()).unwrap_or_default();
+        store_object(&path, hash)?;
+    }
+    Ok((files, size))
+}
+
+/// Snapshots the collection's mods, config directory and metadata, then applies the retention
+/// limits. The files are hashed and copied on a blocking thread.
+pub async fn take_snapshot(
+    collection_id: &CollectionId,
+    reason: SnapshotReason,
+) -> Result<Snapshot, SnapshotError> {
+    let collection = collection_id.get_collection_owned();
+    let game_directory = collection.game_directory();
+    let (files, size) = tokio::task::spawn_blocking(move || store_files(&game_directory))
+        .await
+        .context(InterruptedSnafu)??;
+    let mut snapshot = Snapshot {
+        created: unix_now(),
+        reason,
+        state: CollectionState::capture(collection_id),
+        game: Some(SnapshotGame {
+            minecraft_version: collection.minecraft_version().clone(),
+            mod_loader: collection.mod_loader().cloned(),
+        }),
+        mods: collection
+            .mod_controller()
+            .map(|x| x.manager.mods.clone())
+            .unwrap_or_default(),
+        local_mods: Some(local_mods_of(collection_id)),
+        files,
+        size,
+    };
+    let dir = collection_dir(collection_id);
+    std::fs::create_dir_all(&dir).context(IoSnafu { path: &dir })?;
+    // claiming the file name first keeps two snapshots of the same second apart
+    let (path, mut manifest) = loop {
+        let path = dir.join(format!("{}.json", snapshot.created));
+        match std::fs::File::create_new(&path) {
+            Ok(x) => break (path, x),
+            Err(x) if x.kind() == std::io::ErrorKind::AlreadyExists => snapshot.created += 1,
+            Err(x) => return Err(x).context(IoSnafu { path: &path }),
+        }
+    };
+    let bytes = serde_json::to_vec_pretty(&snapshot).context(ManifestSnafu { path: &path })?;
+    manifest
+        .write_all(&bytes)
+        .context(IoSnafu { path: &path })?;
+    apply_retention(collection_id)?;
+    Ok(snapshot)
+}
+
+/// Snapshots of the collection, newest first.
+pub fn list_snapshots(collection_id: &CollectionId) -> Result<Vec<Snapshot>, SnapshotError> {
+    let dir = collection_dir(collection_id);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots = Vec::new();
+    for entry in std::fs::read_dir(&dir).context(IoSnafu { path: &dir })? {
+        let path = entry.context(IoSnafu { path: &dir })?.path();
+        if path.extension().is_some_and(|x| x == "json") {
+            let bytes = std::fs::read(&path).context(IoSnafu { path: &path })?;
+            snapshots
+                .push(serde_json::from_slice(&bytes).context(ManifestSnafu { path: &path })?);
+        }
+    }
+    snapshots.sort_by_key(|x: &Snapshot| std::cmp::Reverse(x.created));
+    Ok(snapshots)
+}
+
+pub fn delete_snapshot(collection_id: &CollectionId, created: u64) -> Result<(), SnapshotError> {
+    let path = collection_dir(collection_id).join(format!("{created}.json"));
+    std::fs::remove_file(&path).context(IoSnafu { path: &path })?;
+    collect_garbage()
+}
+
+fn apply_retention(collection_id: &CollectionId) -> Result<(), SnapshotError> {
+    let settings = SNAPSHOT_SETTINGS.read().clone();
+    let now = unix_now();
+    for (index, snapshot) in list_snapshots(collection_id)?.into_iter().enumerate() {
+        let too_old = settings
+            .max_age_days
+            .is_some_and(|days| now.saturating_sub(snapshot.created) > days * 60 * 60 * 24);
+        // the snapshot just taken is always kept
+        if index > 0 && (index >= settings.max_per_collection || too_old) {
+            let path = collection_dir(collection_id).join(format!("{}.json", snapshot.created));
+            std::fs::remove_file(&path).context(IoSnafu { path: &path })?;
+        }
+    }
+    collect_garbage()
+}
+
+/// Removes objects that no snapshot of any collection references any more.
+fn collect_garbage() -> Result<(), SnapshotError> {
+    let collections = snapshots_root().join("collections");
+    let mut referenced = BTreeSet::new();
+    let mut manifests = Vec::new();
+    walk(&collections, &collections, &mut manifests)?;
+    for manifest in manifests {
+        let path = collections.join(manifest);
+        let bytes = std::fs::read(&path).context(IoSnafu { path: &path })?;
+        let snapshot: Snapshot =
+            serde_json::from_slice(&bytes).context(ManifestSnafu { path: &path })?;
+        referenced.extend(snapshot.files.into_values());
+    }
+    let objects = objects_dir();
+    if !objects.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(&objects).context(IoSnafu { path: &objects })? {
+        let path = entry.context(IoSnafu { path: &objects })?.path();
+        let name = path.file_name().and_then(|x| x.to_str()).unwrap_or_default();
+        if !referenced.contains(name) {
+            std::fs::remove_file(&path).context(IoSnafu { path: &path })?;
+        }
+    }
+    Ok(())
+}
+
+impl Snapshot {
+    pub fn diff(&self, collection_id: &CollectionId) -> Result<SnapshotDiff, SnapshotError> {
+        let game_directory = collection_id.get_collection_owned().game_directory();
+        let current = current_files(&game_directory)?;
+        let mut diff = SnapshotDiff {
+            changes: self.state.diff(&CollectionState::capture(collection_id)),
+            ..Default::default()
+        };
+        for (path, hash) in &current {
+            match self.files.get(path) {
+                None => diff.added_files.push(path.clone()),
+                Some(old) if old != hash => diff.modified_files.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed_files = self
+            .files
+            .keys()
+            .filter(|x| !current.contains_key(*x))
+            .cloned()
+            .collect();
+        Ok(diff)
+    }
+
+    /// Puts the snapshotted files and metadata back, replacing the current mods, config and
+    /// dropped jars.
+    /// The directories are rebuilt next to the current ones and only swapped in once complete,
+    /// so a failed copy leaves the collection as it was.
+    pub fn restore(&self, collection_id: &CollectionId) -> Result<(), SnapshotError> {
+        let game_directory = collection_id.get_collection_owned().game_directory();
+        for hash in self.files.values() {
+            if !objects_dir().join(hash).exists() {
+                return MissingObjectSnafu { hash }.fail();
+            }
+        }
+        let staging = |dir: &str| game_directory.join(format!("{dir}.restoring"));
+        for dir in SNAPSHOT_DIRECTORIES {
+            let path = staging(dir);
+            if path.exists() {
+                std::fs::remove_dir_all(&path).context(IoSnafu { path: &path })?;
+            }
+            std::fs::create_dir_all(&path).context(IoSnafu { path: &path })?;
+        }
+        for (relative, hash) in &self.files {
+            let mut components = relative.components();
+            let Some(dir) = components.next().and_then(|x| x.as_os_str().to_str()) else {
+                continue;
+            };
+            let target = staging(dir).join(components.as_path());
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).context(IoSnafu { path: parent })?;
+            }
+            std::fs::copy(objects_dir().join(hash), &target).context(IoSnafu { path: &target })?;
+        }
+        for dir in SNAPSHOT_DIRECTORIES {
+            let path = game_directory.join(dir);
+            let replaced = game_directory.join(format!("{dir}.replaced"));
+            if path.exists() {
+                std::fs::rename(&path, &replaced).context(IoSnafu { path: &path })?;
+            }
+            std::fs::rename(staging(dir), &path).context(IoSnafu { path: &path })?;
+            if replaced.exists() {
+                std::fs::remove_dir_all(&replaced).context(IoSnafu { path: &replaced })?;
+            }
+        }
+        if let Some(local_mods) = &self.local_mods {
+            replace_local_mods(collection_id, local_mods.clone())?;
+        }
+        let state = self.state.clone();
+        let game = self.game.clone();
+        let mods = self.mods.clone();
+        with_mut_collection(collection_id, |x| {
+            *x.display_name = state.display_name;
+            *x.picture_path = state.picture_path;
+            if let Some(game) = game {
+                *x.minecraft_version = game.minecraft_version;
+                *x.mod_loader = game.mod_loader;
+            }
+            if let Some(controller) = x.mod_controller.as_mut() {
+                controller.manager.mods = mods;
+            }
+        })
+        .map_err(|x| SnapshotError::Restore {
+            message: x.to_string(),
+        })
+    }
+}
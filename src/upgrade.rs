@@ -0,0 +1,456 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use dioxus::prelude::*;
+use rust_lib::api::{
+    backend_exclusive::{errors::ManifestProcessingError, vanilla::version::VersionMetadata},
+    shared_resources::{
+        collection::{CollectionError, CollectionId, ModLoader},
+        entry,
+    },
+};
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    collection_stats::unix_now,
+    download_queue::{queued, JobKind, QueueError},
+    jar_metadata::LoaderKind,
+    journal::{self, replace_collection, with_mut_collection},
+    loaders::{latest_stable_build, mod_loader_type},
+    local_mods::{collection_loader, import_download, local_mods_of, LocalModError},
+    mod_actions::added_mods,
+    mod_index::{mods_dir, normalize, scan_jars, InstalledJar, ModIndex, ModIndexError},
+    mod_query::ModSource,
+    modrinth::{self, ModrinthError, ModrinthVersion},
+    persistence::{self, PersistenceError},
+    snapshots::{take_snapshot, SnapshotError, SnapshotReason},
+};
+
+const REPORTS_FILE: &str = "upgrade_reports";
+/// Reports kept per collection, older ones are dropped.
+const MAX_REPORTS: usize = 10;
+
+/// Mods that stopped being maintained in favour of another project, as `(old, new)` where `new`
+/// is a Modrinth slug.
+const REPLACEMENTS: &[(&str, &str)] = &[
+    ("optifine", "sodium"),
+    ("optifabric", "sodium"),
+    ("phosphor", "starlight"),
+    ("starlight", "moonrise-opt"),
+];
+
+/// Upgrade reports of every collection, oldest first, keyed by `CollectionId::to_string`.
+pub static UPGRADE_REPORTS: GlobalSignal<BTreeMap<String, Vec<UpgradeReport>>> =
+    GlobalSignal::new(|| persistence::load(REPORTS_FILE));
+
+#[derive(Snafu, Debug)]
+pub enum UpgradeError {
+    #[snafu(display("Invalid version id {id}"))]
+    InvalidVersionId { id: String },
+    #[snafu(display("Failed to parse version id {id}"))]
+    VersionIdParsing {
+        id: String,
+        source: ManifestProcessingError,
+    },
+    #[snafu(display("Upgrade report {created} no longer exists"))]
+    MissingReport { created: u64 },
+    #[snafu(display("Failed to update the collection: {message}"))]
+    Update { message: String },
+    #[snafu(display("Failed to access {}", path.display()))]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(transparent)]
+    Collection { source: CollectionError },
+    #[snafu(transparent)]
+    Index { source: ModIndexError },
+    #[snafu(transparent)]
+    Modrinth { source: ModrinthError },
+    #[snafu(transparent)]
+    Snapshot { source: SnapshotError },
+    #[snafu(transparent)]
+    LocalMod { source: LocalModError },
+    #[snafu(transparent)]
+    Queue { source: QueueError },
+    #[snafu(transparent)]
+    Persistence { source: PersistenceError },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpgradeOutcome {
+    /// The same mod has a version for the target
+    Upgradable { version: ModrinthVersion },
+    /// The mod is gone but another project took over, `replacement` is its slug
+    Replaced {
+        replacement: String,
+        version: ModrinthVersion,
+    },
+    Unavailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeEntry {
+    pub name: String,
+    pub source: ModSource,
+    pub file_name: Option<String>,
+    pub current_version: Option<String>,
+    pub outcome: UpgradeOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppliedUpgrade {
+    /// `snapshot` is the `created` timestamp of the snapshot taken beforehand
+    InPlace { snapshot: u64 },
+    Copy { display_name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeReport {
+    /// Unix timestamp in seconds, also identifies the report
+    pub created: u64,
+    pub from: String,
+    pub to: String,
+    pub entries: Vec<UpgradeEntry>,
+    pub applied: Option<AppliedUpgrade>,
+}
+
+impl UpgradeReport {
+    pub fn upgradable(&self) -> impl Iterator<Item = &UpgradeEntry> {
+        self.entries
+            .iter()
+            .filter(|x| matches!(x.outcome, UpgradeOutcome::Upgradable { .. }))
+    }
+
+    pub fn replaced(&self) -> impl Iterator<Item = &UpgradeEntry> {
+        self.entries
+            .iter()
+            .filter(|x| matches!(x.outcome, UpgradeOutcome::Replaced { .. }))
+    }
+
+    pub fn unavailable(&self) -> impl Iterator<Item = &UpgradeEntry> {
+        self.entries
+            .iter()
+            .filter(|x| matches!(x.outcome, UpgradeOutcome::Unavailable))
+    }
+}
+
+pub fn reports_of(collection_id: &CollectionId) -> Vec<UpgradeReport> {
+    UPGRADE_REPORTS
+        .read()
+        .get(&collection_id.to_string())
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn save_report(collection_id: &CollectionId, report: UpgradeReport) -> Result<(), PersistenceError> {
+    let mut reports = UPGRADE_REPORTS.write();
+    let entries = reports.entry(collection_id.to_string()).or_default();
+    match entries.iter_mut().find(|x| x.created == report.created) {
+        Some(x) => *x = report,
+        None => entries.push(report),
+    }
+    let overflow = entries.len().saturating_sub(MAX_REPORTS);
+    entries.drain(..overflow);
+    persistence::save(REPORTS_FILE, &*reports)
+}
+
+fn find_report(collection_id: &CollectionId, created: u64) -> Result<UpgradeReport, UpgradeError> {
+    reports_of(collection_id)
+        .into_iter()
+        .find(|x| x.created == created)
+        .context(MissingReportSnafu { created })
+}
+
+async fn version_metadata(id: &str) -> Result<VersionMetadata, UpgradeError> {
+    VersionMetadata::from_id(id)
+        .await
+        .context(VersionIdParsingSnafu { id })?
+        .context(InvalidVersionIdSnafu { id })
+}
+
+async fn resolve(
+    name: &str,
+    jar: Option<&InstalledJar>,
+    latest: &BTreeMap<String, ModrinthVersion>,
+    loader: Option<LoaderKind>,
+    target: &str,
+) -> Result<UpgradeOutcome, ModrinthError> {
+    if let Some(version) = jar.and_then(|x| latest.get(&x.sha512)) {
+        return Ok(UpgradeOutcome::Upgradable {
+            version: version.clone(),
+        });
+    }
+    // jars Modrinth doesn't know by hash (local builds, repacks) are looked up by name instead
+    let keys = [
+        Some(normalize(name)),
+        jar.and_then(|x| x.metadata.as_ref())
+            .map(|x| normalize(&x.id)),
+    ];
+    let is_same = |x: &str| keys.iter().flatten().any(|key| *key == normalize(x));
+    let hits = modrinth::search_mods(name, loader, target).await?;
+    if let Some(hit) = hits.iter().find(|x| is_same(&x.slug) || is_same(&x.title)) {
+        let versions = modrinth::project_versions(&hit.project_id, loader, target).await?;
+        if let Some(version) = versions.into_iter().next() {
+            return Ok(UpgradeOutcome::Upgradable { version });
+        }
+    }
+    if let Some((_, replacement)) = REPLACEMENTS.iter().find(|(old, _)| is_same(old)) {
+        let versions = modrinth::project_versions(replacement, loader, target).await?;
+        if let Some(version) = versions.into_iter().next() {
+            return Ok(UpgradeOutcome::Replaced {
+                replacement: (*replacement).to_owned(),
+                version,
+            });
+        }
+    }
+    Ok(UpgradeOutcome::Unavailable)
+}
+
+/// Resolves every installed mod against `target` and keeps the report.
+pub async fn plan_upgrade(
+    collection_id: &CollectionId,
+    target: &str,
+) -> Result<UpgradeReport, UpgradeError> {
+    let target = target.trim();
+    version_metadata(target).await?;
+    let collection = collection_id.get_collection_owned();
+    let loader = collection_loader(collection_id);
+    let index = ModIndex {
+        jars: scan_jars(collection_id)?,
+        ..Default::default()
+    };
+    let hashes = index.jars.iter().map(|x| x.sha512.clone()).collect::<Vec<_>>();
+    let latest = modrinth::latest_versions(&hashes, loader, target).await?;
+    let mut entries = Vec::new();
+    for x in collection
+        .mod_controller()
+        .map(|x| x.manager.mods.clone())
+        .unwrap_or_default()
+    {
+        let jar = index.find(&x.name);
+        entries.push(UpgradeEntry {
+            outcome: resolve(&x.name, jar, &latest, loader, target).await?,
+            name: x.name,
+            source: ModSource::Modrinth,
+            file_name: jar.map(|x| x.file_name.clone()),
+            current_version: x.mod_version,
+        });
+    }
+    for x in local_mods_of(collection_id) {
+        let jar = index.jars.iter().find(|jar| jar.file_name == x.file_name);
+        entries.push(UpgradeEntry {
+            outcome: resolve(&x.metadata.name, jar, &latest, loader, target).await?,
+            name: x.metadata.name,
+            source: ModSource::Local,
+            file_name: Some(x.file_name),
+            current_version: x.metadata.version,
+        });
+    }
+    let report = UpgradeReport {
+        created: unix_now(),
+        from: collection.minecraft_version().id.clone(),
+        to: target.to_owned(),
+        entries,
+        applied: None,
+    };
+    save_report(collection_id, report.clone())?;
+    Ok(report)
+}
+
+fn new_version(outcome: &UpgradeOutcome) -> Option<&ModrinthVersion> {
+    match outcome {
+        UpgradeOutcome::Upgradable { version } | UpgradeOutcome::Replaced { version, .. } => {
+            Some(version)
+        }
+        UpgradeOutcome::Unavailable => None,
+    }
+}
+
+/// Downloads the resolved files of local mods and imports them like a dropped jar.
+async fn upgrade_local_mods(
+    collection_id: &CollectionId,
+    report: &UpgradeReport,
+) -> Result<(), UpgradeError> {
+    for entry in report.entries.iter().filter(|x| x.source == ModSource::Local) {
+        if let Some(file) = new_version(&entry.outcome).and_then(ModrinthVersion::primary_file) {
+            import_download(collection_id, file).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves the collection to `version` and `loader` and swaps its mods as `report` resolved them.
+async fn migrate(
+    collection_id: &CollectionId,
+    report: &UpgradeReport,
+    version: VersionMetadata,
+    loader: Option<ModLoader>,
+) -> Result<(), UpgradeError> {
+    let modrinth = report
+        .entries
+        .iter()
+        .filter(|x| x.source == ModSource::Modrinth)
+        .collect::<Vec<_>>();
+    let replaced = modrinth.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+    with_mut_collection(collection_id, |x| {
+        *x.minecraft_version = version;
+        if loader.is_some() {
+            *x.mod_loader = loader;
+        }
+        if let Some(controller) = x.mod_controller.as_mut() {
+            controller.manager.mods.retain(|x| !replaced.contains(&x.name));
+        }
+    })
+    .map_err(|x| UpgradeError::Update {
+        message: x.to_string(),
+    })?;
+    let projects = modrinth
+        .iter()
+        .filter_map(|x| new_version(&x.outcome).map(|x| x.project_id.clone()))
+        .collect::<Vec<_>>();
+    let mut collection = collection_id.get_collection_owned();
+    let before = collection
+        .mod_controller()
+        .map(|x| x.manager.mods.clone())
+        .unwrap_or_default();
+    collection
+        .add_multiple_modrinth_mod(projects.iter().map(String::as_str).collect(), vec![], None)
+        .await?;
+    queued(
+        collection_id,
+        JobKind::Upgrade {
+            created: report.created,
+        },
+        collection.download_mods(),
+    )
+    .await??;
+    // the collection may have been edited while the download was queued, only the new mods go in
+    let added = added_mods(&before, &collection);
+    with_mut_collection(collection_id, |x| {
+        if let Some(controller) = x.mod_controller.as_mut() {
+            controller.manager.mods.extend(added);
+        }
+    })
+    .map_err(|x| UpgradeError::Update {
+        message: x.to_string(),
+    })?;
+    // the old jars go once the new ones are in, unless a new one took the same name
+    let downloaded = modrinth
+        .iter()
+        .filter_map(|x| new_version(&x.outcome).and_then(ModrinthVersion::primary_file))
+        .map(|x| x.filename.clone())
+        .collect::<Vec<_>>();
+    let mods_dir = mods_dir(collection_id);
+    for file_name in modrinth.iter().filter_map(|x| x.file_name.as_ref()) {
+        let path = mods_dir.join(file_name);
+        if !downloaded.contains(file_name) && path.exists() {
+            std::fs::remove_file(&path).context(IoSnafu { path: &path })?;
+        }
+    }
+    upgrade_local_mods(collection_id, report).await
+}
+
+/// Takes a snapshot, then moves the collection itself to the report's target version and the
+/// newest stable build of its loader for it. Mods without a compatible version are removed,
+/// the others are swapped for the resolved version or their successor. Whatever was changed is
+/// journaled as one entry reverted through the snapshot, a migration that failed halfway
+/// included.
+pub async fn apply_in_place(collection_id: &CollectionId, created: u64) -> Result<(), UpgradeError> {
+    let mut report = find_report(collection_id, created)?;
+    let version = version_metadata(&report.to).await?;
+    let loader = match collection_loader(collection_id) {
+        // the pinned build may not exist for the target version
+        Some(loader) => Some(ModLoader::new(
+            mod_loader_type(loader),
+            latest_stable_build(loader, &report.to).await.ok().flatten(),
+        )),
+        None => None,
+    };
+    let snapshot = take_snapshot(collection_id, SnapshotReason::VersionMigration).await?;
+    let start = journal::entry_count(collection_id);
+    let result = migrate(collection_id, &report, version, loader).await;
+    let description = if result.is_ok() {
+        format!("將遊戲版本升級至 {}", report.to)
+    } else {
+        format!("將遊戲版本升級至 {}（未完成）", report.to)
+    };
+    journal::collapse_since(collection_id, start, description);
+    journal::attach_snapshot(collection_id, start, snapshot.created);
+    result?;
+    report.applied = Some(AppliedUpgrade::InPlace {
+        snapshot: snapshot.created,
+    });
+    save_report(collection_id, report)?;
+    Ok(())
+}
+
+/// Copies `from` into `to` recursively, for files that are kept as they are.
+fn copy_dir(from: &Path, to: &Path) -> Result<(), UpgradeError> {
+    std::fs::create_dir_all(to).context(IoSnafu { path: to })?;
+    for entry in std::fs::read_dir(from).context(IoSnafu { path: from })? {
+        let path = entry.context(IoSnafu { path: from })?.path();
+        let target = to.join(path.file_name().unwrap_or_default());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            std::fs::copy(&path, &target).context(IoSnafu { path: &target })?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates a new collection on the report's target version with every mod that could be
+/// resolved and the original's config, leaving the original untouched.
+pub async fn apply_to_copy(collection_id: &CollectionId, created: u64) -> Result<(), UpgradeError> {
+    let mut report = find_report(collection_id, created)?;
+    let version = version_metadata(&report.to).await?;
+    let original = collection_id.get_collection_owned();
+    let display_name = format!("{} ({})", original.display_name(), report.to);
+    let loader = match collection_loader(collection_id) {
+        // the pinned build may not exist for the target version
+        Some(loader) => Some(ModLoader::new(
+            mod_loader_type(loader),
+            latest_stable_build(loader, &report.to).await.ok().flatten(),
+        )),
+        None => None,
+    };
+    let mut collection = entry::create_collection(
+        display_name.clone(),
+        original.picture_path().to_path_buf(),
+        version,
+        loader,
+        None,
+    )
+    .await?;
+    let copy_id = collection.get_collection_id();
+    let config = original.game_directory().join("config");
+    if config.is_dir() {
+        copy_dir(&config, &collection.game_directory().join("config"))?;
+    }
+    let projects = report
+        .entries
+        .iter()
+        .filter(|x| x.source == ModSource::Modrinth)
+        .filter_map(|x| new_version(&x.outcome).map(|x| x.project_id.clone()))
+        .collect::<Vec<_>>();
+    collection
+        .add_multiple_modrinth_mod(projects.iter().map(String::as_str).collect(), vec![], None)
+        .await?;
+    queued(
+        &copy_id,
+        JobKind::Upgrade { created },
+        collection.download_mods(),
+    )
+    .await??;
+    // nothing else knows the copy yet, so it is taken over whole
+    replace_collection(&copy_id, collection).map_err(|x| UpgradeError::Update {
+        message: x.to_string(),
+    })?;
+    // dropped jars are registered with the copy like they were with the original
+    upgrade_local_mods(&copy_id, &report).await?;
+    report.applied = Some(AppliedUpgrade::Copy { display_name });
+    save_report(collection_id, report)?;
+    Ok(())
+}